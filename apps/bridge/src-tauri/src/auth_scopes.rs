@@ -13,6 +13,43 @@ pub const SCOPE_POWER_CONTROL: &str = "power:control";
 pub const SCOPE_STREAM_READ: &str = "stream:read";
 pub const SCOPE_WS_CONNECT: &str = "ws:connect";
 
+/// Every scope a key can be granted.
+pub const ALL_SCOPES: [&str; 11] = [
+    SCOPE_ADMIN,
+    SCOPE_SYSTEM_READ,
+    SCOPE_USAGE_READ,
+    SCOPE_STATS_READ,
+    SCOPE_MEDIA_READ,
+    SCOPE_MEDIA_CONTROL,
+    SCOPE_PROCESSES_READ,
+    SCOPE_PROCESSES_CONTROL,
+    SCOPE_POWER_CONTROL,
+    SCOPE_STREAM_READ,
+    SCOPE_WS_CONNECT,
+];
+
+/// Reject unknown scopes so a typo can't silently produce a key that is
+/// refused everywhere. Returns the scopes with duplicates removed.
+pub fn validate_scopes(scopes: Vec<String>) -> Result<Vec<String>, String> {
+    if scopes.is_empty() {
+        return Err("A key needs at least one scope".to_string());
+    }
+    let mut valid: Vec<String> = Vec::new();
+    for scope in scopes {
+        if !ALL_SCOPES.contains(&scope.as_str()) {
+            return Err(format!(
+                "Unknown scope \"{}\" (expected one of {})",
+                scope,
+                ALL_SCOPES.join(", ")
+            ));
+        }
+        if !valid.contains(&scope) {
+            valid.push(scope);
+        }
+    }
+    Ok(valid)
+}
+
 #[derive(Clone, Debug)]
 pub struct AuthContext {
     pub mode: AuthMode,
@@ -34,23 +71,61 @@ pub fn required_scope_for_request(method: &str, path: &str) -> Option<&'static s
         // /api/status is public (outside auth middleware)
         ("GET", "/api/system") => Some(SCOPE_SYSTEM_READ),
         ("GET", "/api/usage") => Some(SCOPE_USAGE_READ),
+        ("GET", "/api/usage/stats") => Some(SCOPE_USAGE_READ),
+        ("POST", "/api/system/refresh") => Some(SCOPE_USAGE_READ),
+        ("GET", "/api/disks/health") => Some(SCOPE_SYSTEM_READ),
+        ("GET", "/api/disks/layout") => Some(SCOPE_SYSTEM_READ),
+        // Any valid key: clients check it before offering a control
+        ("GET", "/api/features") => None,
+        ("GET", "/api/capabilities") => None,
         ("GET", "/api/clients") => Some(SCOPE_ADMIN),
+        ("GET", "/api/clients/history") => Some(SCOPE_ADMIN),
+        ("GET", "/api/debug/topics") => Some(SCOPE_ADMIN),
+        ("GET", "/api/logs") => Some(SCOPE_ADMIN),
         ("GET", "/api/media/status") => Some(SCOPE_MEDIA_READ),
         ("POST", "/api/media/control") => Some(SCOPE_MEDIA_CONTROL),
+        ("GET", "/api/media/devices") => Some(SCOPE_MEDIA_READ),
+        ("GET", "/api/media/capabilities") => Some(SCOPE_MEDIA_READ),
+        ("POST", "/api/media/device") => Some(SCOPE_MEDIA_CONTROL),
         ("GET", "/api/stream") => Some(SCOPE_STREAM_READ),
         ("GET", "/api/ws") => Some(SCOPE_WS_CONNECT),
         ("POST", "/api/processes/kill") => Some(SCOPE_PROCESSES_CONTROL),
+        ("POST", "/api/processes/priority") => Some(SCOPE_PROCESSES_CONTROL),
+        ("POST", "/api/processes/suspend") => Some(SCOPE_PROCESSES_CONTROL),
+        ("POST", "/api/processes/resume") => Some(SCOPE_PROCESSES_CONTROL),
         ("POST", "/api/processes/focus") => Some(SCOPE_PROCESSES_CONTROL),
         ("POST", "/api/processes/launch") => Some(SCOPE_PROCESSES_CONTROL),
         ("POST", "/api/pw/shutdown") => Some(SCOPE_POWER_CONTROL),
         ("POST", "/api/pw/restart") => Some(SCOPE_POWER_CONTROL),
+        ("POST", "/api/pw/restart_firmware") => Some(SCOPE_POWER_CONTROL),
         ("POST", "/api/pw/sleep") => Some(SCOPE_POWER_CONTROL),
         ("POST", "/api/pw/hibernate") => Some(SCOPE_POWER_CONTROL),
+        ("GET", "/api/pw/scheduled") => Some(SCOPE_POWER_CONTROL),
+        ("POST", "/api/pw/cancel") => Some(SCOPE_POWER_CONTROL),
         ("GET", "/api/processes") => Some(SCOPE_PROCESSES_READ),
+        // Registered commands launch processes, so they share the process scopes
+        ("GET", "/api/commands") => Some(SCOPE_PROCESSES_READ),
+        // Opening a URL launches the browser
+        ("POST", "/api/open") => Some(SCOPE_PROCESSES_CONTROL),
+        // The clipboard often holds passwords and tokens
+        ("GET", "/api/clipboard") => Some(SCOPE_ADMIN),
+        ("POST", "/api/clipboard") => Some(SCOPE_ADMIN),
+        // Whatever is on screen, including passwords being typed
+        ("GET", "/api/screenshot") => Some(SCOPE_ADMIN),
+        ("GET", "/api/displays") => Some(SCOPE_SYSTEM_READ),
+        // Injected input can do anything the logged-in user can
+        ("POST", "/api/input") => Some(SCOPE_ADMIN),
+        ("GET", "/api/autostart") => Some(SCOPE_ADMIN),
+        ("POST", "/api/autostart") => Some(SCOPE_ADMIN),
         _ => {
-            if path.starts_with("/api/processes/") && method == "GET" {
+            if (path.starts_with("/api/processes/") || path.starts_with("/api/process/"))
+                && method == "GET"
+            {
                 return Some(SCOPE_PROCESSES_READ);
             }
+            if path.starts_with("/api/commands/") && path.ends_with("/run") && method == "POST" {
+                return Some(SCOPE_PROCESSES_CONTROL);
+            }
             if path.starts_with("/api/") {
                 return Some(SCOPE_ADMIN);
             }
@@ -98,7 +173,16 @@ pub fn required_scope_for_ws_message(msg: &WebSocketMessage) -> Option<&'static
         WebSocketMessage::Media(_) => Some(SCOPE_MEDIA_CONTROL),
         WebSocketMessage::ProcessKill(_) => Some(SCOPE_PROCESSES_CONTROL),
         WebSocketMessage::ProcessLaunch(_) => Some(SCOPE_PROCESSES_CONTROL),
+        WebSocketMessage::ProcessRun(_) => Some(SCOPE_PROCESSES_CONTROL),
         WebSocketMessage::ProcessFocus(_) => Some(SCOPE_PROCESSES_CONTROL),
         WebSocketMessage::Subscribe(_) => None,
+        // The restored topics are checked like a subscribe's
+        WebSocketMessage::Resume(_) => None,
+        WebSocketMessage::SetInterval(_) => None,
+        WebSocketMessage::SetDelta(_) => None,
+        WebSocketMessage::SetFeature(_) => Some(SCOPE_ADMIN),
+        WebSocketMessage::GetSystemInfo => Some(SCOPE_SYSTEM_READ),
+        // Only acted on as the first message; ignored afterwards
+        WebSocketMessage::Auth(_) => None,
     }
 }
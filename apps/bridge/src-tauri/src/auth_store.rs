@@ -6,6 +6,7 @@ use argon2::{
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, warn};
 
 const AUTH_STORE_VERSION: u32 = 1;
 const KEYRING_SERVICE: &str = "cntrl.bridge";
@@ -63,7 +64,7 @@ pub struct AuthStateLoad {
     pub existed: bool,
 }
 
-fn now_unix() -> i64 {
+pub fn now_unix() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -78,7 +79,7 @@ pub fn load_auth_state() -> AuthStateLoad {
     let entry = match keyring_entry() {
         Ok(e) => e,
         Err(err) => {
-            println!("[auth_store] Keyring entry error: {}", err);
+            warn!("Keyring entry error: {}", err);
             return AuthStateLoad {
                 state: AuthState::default(),
                 existed: false,
@@ -93,7 +94,7 @@ pub fn load_auth_state() -> AuthStateLoad {
                 existed: true,
             },
             Err(err) => {
-                println!("[auth_store] Failed to parse auth state: {}", err);
+                warn!("Failed to parse auth state: {}", err);
                 AuthStateLoad {
                     state: AuthState::default(),
                     existed: true,
@@ -145,7 +146,7 @@ pub fn load_or_migrate(config: &AppConfig) -> AuthState {
     }
 
     if let Err(err) = save_auth_state(&state) {
-        println!("[auth_store] Failed to save auth state: {}", err);
+        error!("Failed to save auth state: {}", err);
     }
 
     state
@@ -184,24 +185,33 @@ pub fn create_key_record_from_token(
     })
 }
 
-pub fn find_active_record(state: &AuthState, token: &str) -> Option<ApiKeyRecord> {
+/// What a presented token matched.
+pub enum KeyLookup {
+    Active(ApiKeyRecord),
+    Expired,
+    Revoked,
+    Unknown,
+}
+
+/// Find the key for `token`. Revoked and expired keys are still matched so
+/// the caller can tell the client why the key stopped working.
+pub fn lookup_key(state: &AuthState, token: &str) -> KeyLookup {
     let now = now_unix();
 
     for record in &state.keys {
-        if record.revoked_at.is_some() {
+        if !verify_hash(token, &record.hash) {
             continue;
         }
-        if let Some(exp) = record.expires_at {
-            if exp <= now {
-                continue;
-            }
+        if record.revoked_at.is_some() {
+            return KeyLookup::Revoked;
         }
-        if verify_hash(token, &record.hash) {
-            return Some(record.clone());
+        if record.expires_at.is_some_and(|exp| exp <= now) {
+            return KeyLookup::Expired;
         }
+        return KeyLookup::Active(record.clone());
     }
 
-    None
+    KeyLookup::Unknown
 }
 
 pub fn has_scope(record: &ApiKeyRecord, required: &str) -> bool {
@@ -1,10 +1,15 @@
+use crate::logging::LOG_LEVELS;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
 use tauri::AppHandle;
-use tauri::Manager;
+use tauri::{Manager, Runtime};
+use tracing::warn;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct AppConfig {
     pub server: ServerConfig,
@@ -14,22 +19,157 @@ pub struct AppConfig {
     pub auth: AuthConfig,
     #[serde(default)]
     pub websocket: WebSocketConfig,
+    #[serde(default)]
+    pub media: MediaConfig,
+    /// Pre-registered commands runnable through `/api/commands/:id/run`.
+    #[serde(default)]
+    pub commands: Vec<CommandConfig>,
+    /// Validate power and process actions without carrying them out, as if
+    /// every request passed `?dry_run=true`.
+    #[serde(default)]
+    pub safe_mode: bool,
+    /// `error`, `warn`, `info`, `debug` or `trace`.
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// Name of the profile the live settings above belong to.
+    #[serde(default = "default_profile_name")]
+    pub active_profile: String,
+    /// Saved snapshots of the other profiles, keyed by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+fn default_profile_name() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+/// Accepted values for `display.temp_unit`.
+pub const TEMP_UNITS: [&str; 2] = ["celsius", "fahrenheit"];
+
+fn default_temp_unit() -> String {
+    TEMP_UNITS[0].to_string()
+}
+
+fn default_max_clients() -> usize {
+    50
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_advertise() -> bool {
+    true
+}
+
+fn default_discovery_port() -> u16 {
+    9991
+}
+
+fn default_channel_capacity() -> usize {
+    100
+}
+
+fn default_volume_step() -> u32 {
+    5
+}
+
+fn default_refresh_min_interval_ms() -> u64 {
+    250
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Pseudo filesystems hidden from disk lists unless `stats.disk_exclude` is
+/// changed.
+fn default_disk_exclude() -> Vec<String> {
+    ["tmpfs", "devtmpfs", "devfs", "overlay", "squashfs"]
+        .map(String::from)
+        .to_vec()
+}
+
+/// A named set of settings that can be swapped in as the live config.
+/// A config file without profiles is treated as the "default" profile.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ProfileConfig {
+    pub server: ServerConfig,
+    pub display: DisplayConfig,
+    pub features: FeaturesConfig,
+    pub stats: StatsConfig,
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+    #[serde(default)]
+    pub media: MediaConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct ServerConfig {
     pub port: u16,
     pub host: String,
+    /// Only accept connections from this machine: listen on 127.0.0.1 and
+    /// ::1, whatever `host` says.
+    #[serde(default)]
+    pub local_only: bool,
+    /// Most WebSocket and SSE clients connected at once.
+    #[serde(default = "default_max_clients")]
+    pub max_clients: usize,
+    /// Advertise the bridge over mDNS as `_cntrl._tcp`.
+    #[serde(default = "default_advertise")]
+    pub advertise: bool,
+    /// UDP port answering `CNTRL_DISCOVER` broadcasts (when `advertise` is on).
+    #[serde(default = "default_discovery_port")]
+    pub discovery_port: u16,
+    /// Browser origins allowed to call the API (e.g. `https://dash.example.com`).
+    /// Empty allows any origin.
+    #[serde(default)]
+    pub cors_origins: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct DisplayConfig {
     pub hostname: String,
+    /// Unit temperatures are reported in. Readings are kept in Celsius and
+    /// only converted when a response is built.
+    #[serde(default = "default_temp_unit")]
+    pub temp_unit: String,
+    /// Format `?humanize=true` byte strings in binary units (GiB, 1024-based)
+    /// instead of decimal ones (GB, 1000-based).
+    #[serde(default)]
+    pub binary_units: bool,
+}
+
+impl DisplayConfig {
+    /// Name the bridge presents to clients: `hostname` when it isn't blank,
+    /// else the OS host name. `None` only if the OS has none either.
+    pub fn host_name(&self) -> Option<String> {
+        let custom = self.hostname.trim();
+        if custom.is_empty() {
+            sysinfo::System::host_name()
+        } else {
+            Some(custom.to_string())
+        }
+    }
+
+    /// A Celsius reading in `temp_unit`. Zero and negative readings mean
+    /// "unknown" and are passed through unchanged.
+    pub fn temperature(&self, celsius: f64) -> f64 {
+        if self.temp_unit == "fahrenheit" && celsius > 0.0 {
+            celsius * 9.0 / 5.0 + 32.0
+        } else {
+            celsius
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct FeaturesConfig {
     pub enable_shutdown: bool,
@@ -43,39 +183,209 @@ pub struct FeaturesConfig {
     pub enable_processes: bool,
     pub enable_stream: bool,
     pub enable_autostart: bool,
+    #[serde(default)]
+    pub enable_commands: bool,
+    #[serde(default)]
+    pub enable_open: bool,
+    #[serde(default)]
+    pub enable_clipboard: bool,
+    #[serde(default)]
+    pub enable_screenshot: bool,
+    #[serde(default)]
+    pub enable_display: bool,
+    #[serde(default)]
+    pub enable_input: bool,
+    /// Reading and changing autostart over `/api/autostart`.
+    #[serde(default)]
+    pub enable_autostart_api: bool,
+    /// Killing processes, on top of `enable_processes`.
+    #[serde(default = "default_true")]
+    pub enable_process_kill: bool,
+    /// Launching processes, on top of `enable_processes`.
+    #[serde(default = "default_true")]
+    pub enable_process_launch: bool,
+    /// Changing process priority, on top of `enable_processes`.
+    #[serde(default)]
+    pub enable_process_priority: bool,
+    /// Suspending and resuming processes, on top of `enable_processes`.
+    #[serde(default)]
+    pub enable_process_suspend: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl FeaturesConfig {
+    /// The flag behind a feature name, as used by the dashboard toggles and
+    /// the WS `set_feature` op: "media" for `enable_media` and so on.
+    pub fn flag_mut(&mut self, feature: &str) -> Option<&mut bool> {
+        Some(match feature {
+            "system" => &mut self.enable_system,
+            "usage" => &mut self.enable_usage,
+            "stats" => &mut self.enable_stats,
+            "stream" => &mut self.enable_stream,
+            "media" => &mut self.enable_media,
+            "processes" => &mut self.enable_processes,
+            "shutdown" => &mut self.enable_shutdown,
+            "restart" => &mut self.enable_restart,
+            "hibernate" => &mut self.enable_hibernate,
+            "sleep" => &mut self.enable_sleep,
+            "commands" => &mut self.enable_commands,
+            "open" => &mut self.enable_open,
+            "clipboard" => &mut self.enable_clipboard,
+            "screenshot" => &mut self.enable_screenshot,
+            "display" => &mut self.enable_display,
+            "input" => &mut self.enable_input,
+            "process_kill" => &mut self.enable_process_kill,
+            "process_launch" => &mut self.enable_process_launch,
+            "process_priority" => &mut self.enable_process_priority,
+            "process_suspend" => &mut self.enable_process_suspend,
+            "autostart" => &mut self.enable_autostart,
+            "autostart_api" => &mut self.enable_autostart_api,
+            _ => return None,
+        })
+    }
+}
+
+/// A user-defined command. Only commands listed in config can be run over
+/// the API; the program and arguments are never taken from the request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CommandConfig {
+    pub id: String,
+    pub label: String,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct StatsConfig {
     pub gpu_enabled: bool,
     pub disk_cache_seconds: u64,
+    /// Missing in configs written before it existed; `parse_config` fills it
+    /// in from `disk_cache_seconds`, which used to drive the GPU cache.
+    #[serde(default)]
+    pub gpu_cache_seconds: u64,
     pub stream_interval_seconds: u64,
+    /// Read fan speeds for `/api/usage`. Off by default since sensor reads
+    /// can be slow.
+    #[serde(default)]
+    pub sensors_enabled: bool,
+    /// Weight of each new sample in the moving average of the stats loop's
+    /// `current_load`, from 0 to 1. 0 sends the raw samples.
+    #[serde(default)]
+    pub cpu_smoothing: f64,
+    /// Only list disks matching one of these. Empty lists every disk.
+    #[serde(default)]
+    pub disk_include: Vec<String>,
+    /// Never list disks matching one of these.
+    #[serde(default = "default_disk_exclude")]
+    pub disk_exclude: Vec<String>,
+    /// CPU, memory and disk readings younger than this are reused instead
+    /// of refreshed again, so concurrent pollers share one refresh.
+    #[serde(default = "default_refresh_min_interval_ms")]
+    pub refresh_min_interval_ms: u64,
+}
+
+impl StatsConfig {
+    pub fn refresh_min_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.refresh_min_interval_ms)
+    }
+
+    /// Whether a disk is listed under `disk_include` and `disk_exclude`.
+    /// Patterns match the mount point or the filesystem type, and `*`
+    /// matches any run of characters, e.g. `/snap/*` or `fuse.*`.
+    pub fn shows_disk(&self, mount: &str, file_system: &str) -> bool {
+        let matches = |pattern: &String| {
+            wildcard_match(pattern, mount) || wildcard_match(pattern, file_system)
+        };
+        (self.disk_include.is_empty() || self.disk_include.iter().any(matches))
+            && !self.disk_exclude.iter().any(matches)
+    }
+}
+
+/// `text` matches `pattern`, where `*` stands for any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // Without a `*` there's one part, which must be all of `text`
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct AuthConfig {
     pub enabled: bool,
+    /// Secret: cleared by `AppConfig::redacted`.
     pub api_key: Option<String>,
     pub allowed_ips: Vec<String>,
     #[serde(default)]
     pub blocked_ips: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+// By hand so a logged config never prints the key
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field("enabled", &self.enabled)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("allowed_ips", &self.allowed_ips)
+            .field("blocked_ips", &self.blocked_ips)
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct WebSocketConfig {
     pub stats: TopicConfig,
     pub media: TopicConfig,
     pub processes: TopicConfig,
+    /// Events buffered per client before a slow client starts missing updates.
+    /// Applied when the server starts.
+    #[serde(default = "default_channel_capacity")]
+    pub channel_capacity: usize,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct TopicConfig {
     pub enabled: bool,
     pub interval_ms: u64,
+    /// Cap on list items per broadcast (processes only). `None` = unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+    /// Skip broadcasts whose content hasn't changed (processes only).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub coalesce: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MediaConfig {
+    /// Percentage points `volume_up` / `volume_down` move the output volume.
+    #[serde(default = "default_volume_step")]
+    pub volume_step: u32,
+}
+
+impl Default for MediaConfig {
+    fn default() -> Self {
+        Self {
+            volume_step: default_volume_step(),
+        }
+    }
 }
 
 impl Default for WebSocketConfig {
@@ -84,15 +394,22 @@ impl Default for WebSocketConfig {
             stats: TopicConfig {
                 enabled: true,
                 interval_ms: 1000,
+                max_items: None,
+                coalesce: false,
             },
             media: TopicConfig {
                 enabled: true,
                 interval_ms: 500,
+                max_items: None,
+                coalesce: false,
             },
             processes: TopicConfig {
                 enabled: true,
                 interval_ms: 3000,
+                max_items: None,
+                coalesce: false,
             },
+            channel_capacity: default_channel_capacity(),
         }
     }
 }
@@ -103,9 +420,16 @@ impl Default for AppConfig {
             server: ServerConfig {
                 port: 9990,
                 host: "0.0.0.0".to_string(),
+                local_only: false,
+                max_clients: default_max_clients(),
+                advertise: default_advertise(),
+                discovery_port: default_discovery_port(),
+                cors_origins: Vec::new(),
             },
             display: DisplayConfig {
                 hostname: "".to_string(),
+                temp_unit: default_temp_unit(),
+                binary_units: false,
             },
             features: FeaturesConfig {
                 enable_shutdown: false,
@@ -119,11 +443,28 @@ impl Default for AppConfig {
                 enable_processes: true,
                 enable_stream: true,
                 enable_autostart: true,
+                enable_commands: false,
+                enable_open: false,
+                enable_clipboard: false,
+                enable_screenshot: false,
+                enable_display: false,
+                enable_input: false,
+                enable_autostart_api: false,
+                enable_process_kill: true,
+                enable_process_launch: true,
+                enable_process_priority: false,
+                enable_process_suspend: false,
             },
             stats: StatsConfig {
                 gpu_enabled: true,
                 disk_cache_seconds: 30,
+                gpu_cache_seconds: 30,
                 stream_interval_seconds: 2,
+                sensors_enabled: false,
+                cpu_smoothing: 0.0,
+                disk_include: vec![],
+                disk_exclude: default_disk_exclude(),
+                refresh_min_interval_ms: default_refresh_min_interval_ms(),
             },
             auth: AuthConfig {
                 enabled: false,
@@ -132,40 +473,519 @@ impl Default for AppConfig {
                 blocked_ips: vec![],
             },
             websocket: WebSocketConfig::default(),
+            media: MediaConfig::default(),
+            commands: vec![],
+            safe_mode: false,
+            log_level: default_log_level(),
+            active_profile: default_profile_name(),
+            profiles: BTreeMap::new(),
         }
     }
 }
 
-pub fn load_config(app: &AppHandle) -> AppConfig {
-    let config_path = get_config_path(app);
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            if let Ok(config) = serde_json::from_str::<AppConfig>(&content) {
-                // Re-save to add any new fields with defaults (e.g., websocket)
-                save_config(app, &config);
-                return config;
+impl AppConfig {
+    /// Copy with every secret cleared, for anything that leaves the Tauri
+    /// IPC boundary: HTTP responses, logs and profile snapshots. A new
+    /// secret field must be cleared here too.
+    pub fn redacted(&self) -> AppConfig {
+        let mut config = self.clone();
+        config.auth.api_key = None;
+        for profile in config.profiles.values_mut() {
+            profile.auth.api_key = None;
+        }
+        config
+    }
+
+    /// Snapshot of the live settings, suitable for storing as a profile.
+    pub fn to_profile(&self) -> ProfileConfig {
+        let AppConfig {
+            server,
+            display,
+            features,
+            stats,
+            auth,
+            websocket,
+            media,
+            ..
+        } = self.redacted();
+        ProfileConfig {
+            server,
+            display,
+            features,
+            stats,
+            auth,
+            websocket,
+            media,
+        }
+    }
+
+    /// All profile names, including the active one even if it was never saved.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        if !names.contains(&self.active_profile) {
+            names.push(self.active_profile.clone());
+            names.sort();
+        }
+        names
+    }
+
+    /// Store the live settings under `name` and make it the active profile.
+    pub fn save_as_profile(&mut self, name: &str) -> Result<(), String> {
+        let name = validate_profile_name(name)?;
+        self.profiles.insert(name.clone(), self.to_profile());
+        self.active_profile = name;
+        Ok(())
+    }
+
+    /// Build the config that results from switching to profile `name`.
+    /// The current live settings are stashed under the active profile first so
+    /// no edits are lost. Environment overrides still win over the profile.
+    pub fn with_profile(&self, name: &str) -> Result<AppConfig, String> {
+        let name = validate_profile_name(name)?;
+        if name == self.active_profile {
+            return Ok(self.clone());
+        }
+        let profile = self
+            .profiles
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| format!("Profile \"{}\" not found", name))?;
+
+        let mut next = self.clone();
+        next.profiles
+            .insert(self.active_profile.clone(), self.to_profile());
+        next.server = profile.server;
+        next.display = profile.display;
+        next.features = profile.features;
+        next.stats = profile.stats;
+        next.auth = AuthConfig {
+            api_key: self.auth.api_key.clone(),
+            ..profile.auth
+        };
+        next.websocket = profile.websocket;
+        next.media = profile.media;
+        next.active_profile = name;
+
+        apply_env_overrides(&mut next)?;
+        next.validate()
+            .map_err(|e| format!("Invalid profile \"{}\": {}", next.active_profile, e))?;
+        Ok(next)
+    }
+
+    /// Check value ranges. Bounds mirror the validators on the individual
+    /// Tauri update commands so a hand-edited file can't bypass them.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.server.port < 1024 {
+            return Err(format!(
+                "server.port must be between 1024 and 65535 (got {})",
+                self.server.port
+            ));
+        }
+        if self.server.host.parse::<std::net::IpAddr>().is_err() {
+            return Err(format!(
+                "server.host must be a valid IP address (got \"{}\")",
+                self.server.host
+            ));
+        }
+        if self.server.discovery_port < 1024 {
+            return Err(format!(
+                "server.discovery_port must be between 1024 and 65535 (got {})",
+                self.server.discovery_port
+            ));
+        }
+        for origin in &self.server.cors_origins {
+            if validate_cors_origin(origin).as_deref() != Ok(origin.as_str()) {
+                return Err(format!(
+                    "server.cors_origins entries must look like \"https://host[:port]\" (got \"{}\")",
+                    origin
+                ));
+            }
+        }
+        if self.server.max_clients < 1 || self.server.max_clients > 1000 {
+            return Err(format!(
+                "server.max_clients must be between 1 and 1000 (got {})",
+                self.server.max_clients
+            ));
+        }
+        if !LOG_LEVELS.contains(&self.log_level.as_str()) {
+            return Err(format!(
+                "log_level must be one of {} (got \"{}\")",
+                LOG_LEVELS.join(", "),
+                self.log_level
+            ));
+        }
+        if !TEMP_UNITS.contains(&self.display.temp_unit.as_str()) {
+            return Err(format!(
+                "display.temp_unit must be one of {} (got \"{}\")",
+                TEMP_UNITS.join(", "),
+                self.display.temp_unit
+            ));
+        }
+        if self.stats.disk_cache_seconds < 1 || self.stats.disk_cache_seconds > 300 {
+            return Err(format!(
+                "stats.disk_cache_seconds must be between 1 and 300 (got {})",
+                self.stats.disk_cache_seconds
+            ));
+        }
+        if self.stats.gpu_cache_seconds < 1 || self.stats.gpu_cache_seconds > 300 {
+            return Err(format!(
+                "stats.gpu_cache_seconds must be between 1 and 300 (got {})",
+                self.stats.gpu_cache_seconds
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.stats.cpu_smoothing) {
+            return Err(format!(
+                "stats.cpu_smoothing must be between 0 and 1 (got {})",
+                self.stats.cpu_smoothing
+            ));
+        }
+        if self.stats.refresh_min_interval_ms > 10000 {
+            return Err(format!(
+                "stats.refresh_min_interval_ms must be at most 10000 (got {})",
+                self.stats.refresh_min_interval_ms
+            ));
+        }
+        if self.stats.stream_interval_seconds < 1 || self.stats.stream_interval_seconds > 60 {
+            return Err(format!(
+                "stats.stream_interval_seconds must be between 1 and 60 (got {})",
+                self.stats.stream_interval_seconds
+            ));
+        }
+        for (topic, cfg) in [
+            ("stats", &self.websocket.stats),
+            ("media", &self.websocket.media),
+            ("processes", &self.websocket.processes),
+        ] {
+            if cfg.interval_ms < 100 || cfg.interval_ms > 60000 {
+                return Err(format!(
+                    "websocket.{}.interval_ms must be between 100 and 60000 (got {})",
+                    topic, cfg.interval_ms
+                ));
+            }
+        }
+        if self.websocket.processes.max_items == Some(0) {
+            return Err("websocket.processes.max_items must be at least 1".to_string());
+        }
+        if self.websocket.channel_capacity < 16 || self.websocket.channel_capacity > 10000 {
+            return Err(format!(
+                "websocket.channel_capacity must be between 16 and 10000 (got {})",
+                self.websocket.channel_capacity
+            ));
+        }
+        if self.media.volume_step < 1 || self.media.volume_step > 50 {
+            return Err(format!(
+                "media.volume_step must be between 1 and 50 (got {})",
+                self.media.volume_step
+            ));
+        }
+        let mut seen_ids = std::collections::HashSet::new();
+        for cmd in &self.commands {
+            if cmd.id.trim().is_empty() {
+                return Err("commands: id cannot be empty".to_string());
+            }
+            if !seen_ids.insert(cmd.id.as_str()) {
+                return Err(format!("commands: duplicate id \"{}\"", cmd.id));
             }
+            if cmd.program.trim().is_empty() {
+                return Err(format!("commands.{}: program cannot be empty", cmd.id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Normalize a CORS origin to the `scheme://host[:port]` form browsers send
+/// in the `Origin` header, rejecting anything with a path or query.
+pub fn validate_cors_origin(origin: &str) -> Result<String, String> {
+    let url = url::Url::parse(origin.trim())
+        .map_err(|_| format!("Invalid origin \"{}\"", origin.trim()))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+        return Err("Origin must be an http:// or https:// URL".to_string());
+    }
+    if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+        return Err("Origin must not have a path, query or fragment".to_string());
+    }
+    Ok(url.origin().ascii_serialization())
+}
+
+fn validate_profile_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if name.len() > 64 {
+        return Err("Profile name must be at most 64 characters".to_string());
+    }
+    Ok(name.to_string())
+}
+
+pub const ENV_PORT: &str = "CNTRL_PORT";
+pub const ENV_HOST: &str = "CNTRL_HOST";
+pub const ENV_API_KEY: &str = "CNTRL_API_KEY";
+pub const ENV_AUTH_ENABLED: &str = "CNTRL_AUTH_ENABLED";
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Apply `CNTRL_*` environment overrides on top of a config read from disk.
+///
+/// Precedence, highest first:
+/// 1. Environment variables (`CNTRL_PORT`, `CNTRL_HOST`, `CNTRL_API_KEY`,
+///    `CNTRL_AUTH_ENABLED`)
+/// 2. Values in `config.json`
+/// 3. Built-in defaults (`AppConfig::default()`)
+///
+/// Overridden values only live in memory: `save_config` writes the on-disk
+/// value back for any field that is currently overridden, and the API key is
+/// never written at all.
+fn apply_env_overrides(config: &mut AppConfig) -> Result<(), String> {
+    if let Some(port) = env_var(ENV_PORT) {
+        config.server.port = port
+            .parse::<u16>()
+            .map_err(|_| format!("{} must be a port number (got \"{}\")", ENV_PORT, port))?;
+    }
+    if let Some(host) = env_var(ENV_HOST) {
+        config.server.host = host;
+    }
+    if let Some(key) = env_var(ENV_API_KEY) {
+        config.auth.api_key = Some(key);
+    }
+    if let Some(enabled) = env_var(ENV_AUTH_ENABLED) {
+        config.auth.enabled = match enabled.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" => false,
+            _ => {
+                return Err(format!(
+                    "{} must be true or false (got \"{}\")",
+                    ENV_AUTH_ENABLED, enabled
+                ))
+            }
+        };
+    }
+    Ok(())
+}
+
+/// Whether the given `CNTRL_*` variable is set and non-empty.
+pub fn env_override_active(name: &str) -> bool {
+    env_var(name).is_some()
+}
+
+/// Drop an API key read from `config.json` (legacy keys are migrated into the
+/// keyring) while keeping one supplied through `CNTRL_API_KEY`.
+pub fn clear_file_api_key(config: &mut AppConfig) {
+    config.auth.api_key = env_var(ENV_API_KEY);
+}
+
+/// Put the on-disk value back for every field currently overridden by an
+/// environment variable, so overrides never get persisted.
+fn restore_overridden_fields(config: &mut AppConfig, config_path: &Path) {
+    let port = env_var(ENV_PORT).is_some();
+    let host = env_var(ENV_HOST).is_some();
+    let auth_enabled = env_var(ENV_AUTH_ENABLED).is_some();
+    if !port && !host && !auth_enabled {
+        return;
+    }
+
+    let on_disk = fs::read_to_string(config_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<AppConfig>(&content).ok())
+        .unwrap_or_default();
+    if port {
+        config.server.port = on_disk.server.port;
+    }
+    if host {
+        config.server.host = on_disk.server.host;
+    }
+    if auth_enabled {
+        config.auth.enabled = on_disk.auth.enabled;
+    }
+}
+
+/// Fill in fields added after a config file was written, using the value of
+/// whatever setting used to cover them.
+fn migrate_legacy_fields(config: &mut AppConfig) {
+    let stats = std::iter::once(&mut config.stats)
+        .chain(config.profiles.values_mut().map(|p| &mut p.stats));
+    for stats in stats {
+        if stats.gpu_cache_seconds == 0 {
+            stats.gpu_cache_seconds = stats.disk_cache_seconds;
         }
     }
+}
+
+/// Parse the contents of `config.json`, apply environment overrides, and
+/// validate the result.
+pub fn parse_config(content: &str) -> Result<AppConfig, String> {
+    let mut config = serde_json::from_str::<AppConfig>(content)
+        .map_err(|e| format!("Invalid config.json: {}", e))?;
+    migrate_legacy_fields(&mut config);
+    apply_env_overrides(&mut config)?;
+    config
+        .validate()
+        .map_err(|e| format!("Invalid config.json: {}", e))?;
+    Ok(config)
+}
+
+/// Load `config.json`, creating it with defaults if it doesn't exist.
+/// Environment overrides are applied on top; see `apply_env_overrides`.
+///
+/// A file that can't be read, parsed or validated is left untouched on disk
+/// and the specific problem is returned, so callers can keep whatever config
+/// they already hold instead of silently falling back to defaults.
+pub fn load_config<R: Runtime>(app: &AppHandle<R>) -> Result<AppConfig, String> {
+    let config_path = get_config_path(app);
+    if config_path.exists() {
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read config.json: {}", e))?;
+        let config = parse_config(&content)?;
+        // Re-save to add any new fields with defaults (e.g., websocket)
+        save_config(app, &config);
+        return Ok(config);
+    }
 
     // Save default if not exists
-    let default_config = AppConfig::default();
+    let mut default_config = AppConfig::default();
     save_config(app, &default_config);
-    default_config
+    apply_env_overrides(&mut default_config)?;
+    default_config.validate()?;
+    Ok(default_config)
+}
+
+/// The server outlives restarts and isn't handed the app, so `lib.rs` sets
+/// this once at startup for server code that saves the config.
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+pub fn init_app(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// The handle set by `init_app`.
+pub fn app() -> Result<&'static AppHandle, String> {
+    APP.get()
+        .ok_or_else(|| "The app has not finished starting".to_string())
 }
 
-pub fn save_config(app: &AppHandle, config: &AppConfig) {
+pub fn save_config<R: Runtime>(app: &AppHandle<R>, config: &AppConfig) {
     let config_path = get_config_path(app);
     if let Some(parent) = config_path.parent() {
         let _ = fs::create_dir_all(parent);
     }
     let mut safe_config = config.clone();
     safe_config.auth.api_key = None;
+    restore_overridden_fields(&mut safe_config, &config_path);
     if let Ok(content) = serde_json::to_string_pretty(&safe_config) {
         let _ = fs::write(config_path, content);
     }
 }
 
-pub fn get_config_path(app: &AppHandle) -> PathBuf {
+pub fn get_config_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
     app.path().app_config_dir().unwrap().join("config.json")
 }
+
+/// Quiet period after the last filesystem event before the file is re-read.
+/// Editors often write a file in several steps (truncate, write, rename).
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watch `config.json` for external edits and call `on_change` with the
+/// freshly parsed config once writes settle.
+///
+/// The parent directory is watched rather than the file itself so that
+/// editors which save via rename-over are still picked up. Files that fail
+/// to parse or validate are ignored; the in-memory config stays as it was.
+pub fn spawn_config_watcher<F>(app: &AppHandle, on_change: F)
+where
+    F: Fn(AppConfig) + Send + 'static,
+{
+    use notify::{RecursiveMode, Watcher};
+
+    let config_path = get_config_path(app);
+    let Some(dir) = config_path.parent().map(|p| p.to_path_buf()) else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(w) => w,
+            Err(e) => {
+                warn!("Failed to create config watcher: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            warn!("Failed to watch {}: {}", dir.display(), e);
+            return;
+        }
+
+        while let Ok(first) = rx.recv() {
+            let mut relevant = is_config_event(&first, &config_path);
+            // Debounce: keep draining until no event arrives for WATCH_DEBOUNCE
+            while let Ok(event) = rx.recv_timeout(WATCH_DEBOUNCE) {
+                relevant |= is_config_event(&event, &config_path);
+            }
+            if !relevant {
+                continue;
+            }
+
+            let parsed = fs::read_to_string(&config_path)
+                .map_err(|e| format!("Failed to read config.json: {}", e))
+                .and_then(|content| parse_config(&content));
+            match parsed {
+                Ok(config) => on_change(config),
+                Err(e) => warn!("Ignoring config.json change: {}", e),
+            }
+        }
+    });
+}
+
+fn is_config_event(event: &notify::Result<notify::Event>, config_path: &Path) -> bool {
+    match event {
+        Ok(ev) => {
+            !ev.kind.is_access()
+                && ev
+                    .paths
+                    .iter()
+                    .any(|p| p.file_name() == config_path.file_name())
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacted_drops_every_api_key() {
+        let mut config = AppConfig::default();
+        config.auth.api_key = Some("top-level-secret".to_string());
+        let mut profile = config.to_profile();
+        profile.auth.api_key = Some("profile-secret".to_string());
+        config.profiles.insert("work".to_string(), profile);
+
+        let json = serde_json::to_string(&config.redacted()).unwrap();
+        assert!(!json.contains("top-level-secret"));
+        assert!(!json.contains("profile-secret"));
+        // The original is untouched
+        assert_eq!(config.auth.api_key.as_deref(), Some("top-level-secret"));
+    }
+
+    #[test]
+    fn validate_bounds_gpu_cache_seconds() {
+        // `update_config` relies on this; a saved 0 would be read back as
+        // missing and replaced by `disk_cache_seconds`
+        let mut config = AppConfig::default();
+        for seconds in [0, 301] {
+            config.stats.gpu_cache_seconds = seconds;
+            assert!(config.validate().is_err(), "{} accepted", seconds);
+        }
+        for seconds in [1, 300] {
+            config.stats.gpu_cache_seconds = seconds;
+            assert!(config.validate().is_ok(), "{} rejected", seconds);
+        }
+    }
+}
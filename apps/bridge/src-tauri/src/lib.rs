@@ -12,6 +12,7 @@ fn get_app_version() -> String {
 mod auth_scopes;
 mod auth_store;
 mod config;
+mod logging;
 mod mac_rounded_corners;
 mod server;
 mod tray;
@@ -19,15 +20,18 @@ mod tray;
 use auth_store::{ApiKeyRecord, ApiKeySource, AuthMode, AuthState};
 use config::AppConfig;
 use serde::Serialize;
-use server::types::{ServerState, ServerStatus};
+use server::types::{BroadcastEvent, ClientSeen, LogEntry, ServerState, ServerStatus};
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
-use tauri_plugin_autostart::ManagerExt;
+use tracing::{error, info};
 
 // Server Control State
+#[derive(Clone)]
 struct ServerControl {
     shutdown_tx: Arc<Mutex<Option<tokio::sync::broadcast::Sender<()>>>>,
     status_tx: Arc<tokio::sync::watch::Sender<ServerStatus>>,
+    /// The running server's event channel, so the tray can count connected clients.
+    broadcast_tx: Arc<Mutex<Option<tokio::sync::broadcast::Sender<BroadcastEvent>>>>,
 }
 
 fn spawn_server(
@@ -36,6 +40,7 @@ fn spawn_server(
     auth_state: Arc<Mutex<AuthState>>,
     status_tx: Arc<tokio::sync::watch::Sender<ServerStatus>>,
     shutdown_holder: Arc<Mutex<Option<tokio::sync::broadcast::Sender<()>>>>,
+    broadcast_holder: Arc<Mutex<Option<tokio::sync::broadcast::Sender<BroadcastEvent>>>>,
 ) {
     status_tx.send_modify(|s| *s = ServerStatus::Starting);
 
@@ -46,7 +51,7 @@ fn spawn_server(
     }
 
     tauri::async_runtime::spawn(async move {
-        server::start_server(port, config, auth_state, status_tx, rx).await;
+        server::start_server(port, config, auth_state, status_tx, rx, broadcast_holder).await;
     });
 }
 
@@ -61,6 +66,15 @@ async fn wait_for_status(
     let _ = tokio::time::timeout(timeout, rx.wait_for(|s| pred(s))).await;
 }
 
+/// Ask the running server (if any) to shut down. Its shutdown path aborts
+/// the monitoring loops before reporting `Stopped`.
+fn signal_shutdown(shutdown_tx: &Mutex<Option<tokio::sync::broadcast::Sender<()>>>) {
+    let control = shutdown_tx.lock().unwrap();
+    if let Some(tx) = &*control {
+        let _ = tx.send(());
+    }
+}
+
 #[tauri::command]
 fn get_config(state: tauri::State<Arc<Mutex<AppConfig>>>) -> AppConfig {
     state.lock().unwrap().clone()
@@ -72,8 +86,32 @@ fn get_server_status(
     config: tauri::State<Arc<Mutex<AppConfig>>>,
 ) -> ServerState {
     let status = control_state.status_tx.borrow().clone();
-    let port = config.lock().unwrap().server.port;
-    ServerState { status, port }
+    server_state(status, &config)
+}
+
+/// `status` plus the listening details the dashboard shows next to it. While
+/// running these come from the bound listener, so a config edit that has not
+/// been applied yet doesn't show up as the live address.
+fn server_state(status: ServerStatus, config: &Mutex<AppConfig>) -> ServerState {
+    let bound = match status {
+        ServerStatus::Running => server::bound_addr(),
+        _ => None,
+    };
+    match bound {
+        Some(addr) => ServerState {
+            status,
+            port: addr.port(),
+            local_only: addr.ip().is_loopback(),
+        },
+        None => {
+            let config = config.lock().unwrap();
+            ServerState {
+                status,
+                port: config.server.port,
+                local_only: config.server.local_only,
+            }
+        }
+    }
 }
 
 #[tauri::command]
@@ -115,12 +153,7 @@ async fn stop_service(
     let shutdown_tx = control_state.shutdown_tx.clone();
     let config_arc = config.inner().clone();
 
-    {
-        let control = shutdown_tx.lock().unwrap();
-        if let Some(tx) = &*control {
-            let _ = tx.send(());
-        }
-    }
+    signal_shutdown(&shutdown_tx);
 
     wait_for_status(&status_tx, |s| {
         matches!(s, ServerStatus::Stopped | ServerStatus::Error(_))
@@ -128,30 +161,27 @@ async fn stop_service(
     .await;
 
     let status = status_tx.borrow().clone();
-    let port = config_arc.lock().unwrap().server.port;
-    Ok(ServerState { status, port })
+    Ok(server_state(status, &config_arc))
 }
 
-#[tauri::command]
-async fn restart_service(
-    state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
-    auth_state: tauri::State<'_, Arc<Mutex<AuthState>>>,
-    control_state: tauri::State<'_, ServerControl>,
-    app: tauri::AppHandle,
-) -> Result<ServerState, String> {
-    // Extract Arc clones upfront so State borrows don't cross await points
-    let status_tx = control_state.status_tx.clone();
-    let shutdown_tx = control_state.shutdown_tx.clone();
-    let config_arc = state.inner().clone();
-    let auth_arc = auth_state.inner().clone();
+/// mDNS advertising and the UDP discovery socket are set up when the server
+/// starts, so changing either only takes effect through a restart.
+fn discovery_changed(current: &config::ServerConfig, new: &config::ServerConfig) -> bool {
+    current.advertise != new.advertise || current.discovery_port != new.discovery_port
+}
 
+/// Stop the running server, swap in `new_config`, and start it again.
+/// Returns once the new server has left the `Starting` state.
+async fn restart_with_config(
+    config_arc: Arc<Mutex<AppConfig>>,
+    auth_arc: Arc<Mutex<AuthState>>,
+    status_tx: Arc<tokio::sync::watch::Sender<ServerStatus>>,
+    shutdown_tx: Arc<Mutex<Option<tokio::sync::broadcast::Sender<()>>>>,
+    broadcast_tx: Arc<Mutex<Option<tokio::sync::broadcast::Sender<BroadcastEvent>>>>,
+    new_config: AppConfig,
+) {
     // 1. Stop existing server
-    {
-        let control = shutdown_tx.lock().unwrap();
-        if let Some(tx) = &*control {
-            let _ = tx.send(());
-        }
-    }
+    signal_shutdown(&shutdown_tx);
 
     // 2. Wait for server to actually stop
     wait_for_status(&status_tx, |s| {
@@ -159,9 +189,7 @@ async fn restart_service(
     })
     .await;
 
-    // 3. Reload config from disk
-    let mut new_config = config::load_config(&app);
-    new_config.auth.api_key = None;
+    // 3. Swap in the new config
     {
         let mut config = config_arc.lock().unwrap();
         *config = new_config.clone();
@@ -170,10 +198,11 @@ async fn restart_service(
     // 4. Start new server
     spawn_server(
         new_config.server.port,
-        config_arc.clone(),
+        config_arc,
         auth_arc,
         status_tx.clone(),
         shutdown_tx,
+        broadcast_tx,
     );
 
     // 5. Wait for server to finish starting
@@ -181,10 +210,39 @@ async fn restart_service(
         !matches!(s, ServerStatus::Starting)
     })
     .await;
+}
+
+#[tauri::command]
+async fn restart_service(
+    state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+    auth_state: tauri::State<'_, Arc<Mutex<AuthState>>>,
+    control_state: tauri::State<'_, ServerControl>,
+    app: tauri::AppHandle,
+) -> Result<ServerState, String> {
+    // Extract Arc clones upfront so State borrows don't cross await points
+    let status_tx = control_state.status_tx.clone();
+    let shutdown_tx = control_state.shutdown_tx.clone();
+    let broadcast_tx = control_state.broadcast_tx.clone();
+    let config_arc = state.inner().clone();
+    let auth_arc = auth_state.inner().clone();
+
+    // Reload config from disk. An invalid file leaves the running server and
+    // in-memory config untouched.
+    let mut new_config = config::load_config(&app)?;
+    config::clear_file_api_key(&mut new_config);
+
+    restart_with_config(
+        config_arc.clone(),
+        auth_arc,
+        status_tx.clone(),
+        shutdown_tx,
+        broadcast_tx,
+        new_config,
+    )
+    .await;
 
     let status = status_tx.borrow().clone();
-    let port = config_arc.lock().unwrap().server.port;
-    Ok(ServerState { status, port })
+    Ok(server_state(status, &config_arc))
 }
 
 #[tauri::command]
@@ -195,23 +253,42 @@ async fn start_service(
     app: tauri::AppHandle,
 ) -> Result<ServerState, String> {
     // Extract Arc clones upfront so State borrows don't cross await points
-    let status_tx = control_state.status_tx.clone();
-    let shutdown_tx = control_state.shutdown_tx.clone();
+    let control = control_state.inner().clone();
     let config_arc = state.inner().clone();
     let auth_arc = auth_state.inner().clone();
 
+    Ok(start_from_disk(&app, config_arc, auth_arc, control).await)
+}
+
+/// Reload config from disk and start the server, unless it is already running.
+/// Shared by `start_service` and the tray's start/stop item.
+async fn start_from_disk<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    config_arc: Arc<Mutex<AppConfig>>,
+    auth_arc: Arc<Mutex<AuthState>>,
+    control: ServerControl,
+) -> ServerState {
+    let status_tx = control.status_tx;
+
     // If already running, return current state
     {
         let current = status_tx.borrow().clone();
         if matches!(current, ServerStatus::Running) {
-            let port = config_arc.lock().unwrap().server.port;
-            return Ok(ServerState { status: current, port });
+            return server_state(current, &config_arc);
         }
     }
 
-    // Reload config from disk
-    let mut new_config = config::load_config(&app);
-    new_config.auth.api_key = None;
+    // Reload config from disk, keeping the old config if the file is invalid
+    let mut new_config = match config::load_config(app) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Config error: {}", e);
+            status_tx.send_modify(|s| *s = ServerStatus::Error(e));
+            let status = status_tx.borrow().clone();
+            return server_state(status, &config_arc);
+        }
+    };
+    config::clear_file_api_key(&mut new_config);
     {
         let mut config = config_arc.lock().unwrap();
         *config = new_config.clone();
@@ -223,18 +300,15 @@ async fn start_service(
         config_arc.clone(),
         auth_arc,
         status_tx.clone(),
-        shutdown_tx,
+        control.shutdown_tx,
+        control.broadcast_tx,
     );
 
     // Wait for server to finish starting
-    wait_for_status(&status_tx, |s| {
-        !matches!(s, ServerStatus::Starting)
-    })
-    .await;
+    wait_for_status(&status_tx, |s| !matches!(s, ServerStatus::Starting)).await;
 
     let status = status_tx.borrow().clone();
-    let port = config_arc.lock().unwrap().server.port;
-    Ok(ServerState { status, port })
+    server_state(status, &config_arc)
 }
 
 #[tauri::command]
@@ -244,25 +318,13 @@ fn toggle_feature(
     feature: String,
 ) -> Result<AppConfig, String> {
     let mut config = state.lock().unwrap();
-    match feature.as_str() {
-        "system" => config.features.enable_system = !config.features.enable_system,
-        "usage" => config.features.enable_usage = !config.features.enable_usage,
-        "stream" => config.features.enable_stream = !config.features.enable_stream,
-        "media" => config.features.enable_media = !config.features.enable_media,
-        "processes" => config.features.enable_processes = !config.features.enable_processes,
-        "shutdown" => config.features.enable_shutdown = !config.features.enable_shutdown,
-        "restart" => config.features.enable_restart = !config.features.enable_restart,
-        "hibernate" => config.features.enable_hibernate = !config.features.enable_hibernate,
-        "sleep" => config.features.enable_sleep = !config.features.enable_sleep,
-        "autostart" => {
-            config.features.enable_autostart = !config.features.enable_autostart;
-            if config.features.enable_autostart {
-                let _ = app.autolaunch().enable();
-            } else {
-                let _ = app.autolaunch().disable();
-            }
-        }
-        _ => return Err("Feature not found".to_string()),
+    let flag = config
+        .features
+        .flag_mut(&feature)
+        .ok_or("Feature not found")?;
+    *flag = !*flag;
+    if feature == "autostart" {
+        let _ = server::autostart::apply(&app, config.features.enable_autostart);
     }
 
     // Persist changes
@@ -368,6 +430,127 @@ fn update_hostname(
     Ok(config.clone())
 }
 
+/// Allow a browser origin to call the API. The first entry switches CORS
+/// from allow-any to the allowlist.
+#[tauri::command]
+fn add_cors_origin(
+    state: tauri::State<Arc<Mutex<AppConfig>>>,
+    app: tauri::AppHandle,
+    origin: String,
+) -> Result<AppConfig, String> {
+    let origin = config::validate_cors_origin(&origin)?;
+
+    let mut config = state.lock().unwrap();
+    if !config.server.cors_origins.contains(&origin) {
+        config.server.cors_origins.push(origin);
+    }
+    config::save_config(&app, &config);
+    Ok(config.clone())
+}
+
+#[tauri::command]
+fn remove_cors_origin(
+    state: tauri::State<Arc<Mutex<AppConfig>>>,
+    app: tauri::AppHandle,
+    origin: String,
+) -> Result<AppConfig, String> {
+    let mut config = state.lock().unwrap();
+    config.server.cors_origins.retain(|o| o != &origin);
+    config::save_config(&app, &config);
+    Ok(config.clone())
+}
+
+// ============================================================================
+// Profile Commands
+// ============================================================================
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+struct ProfileList {
+    active: String,
+    profiles: Vec<String>,
+}
+
+#[tauri::command]
+fn list_profiles(state: tauri::State<Arc<Mutex<AppConfig>>>) -> ProfileList {
+    let config = state.lock().unwrap();
+    ProfileList {
+        active: config.active_profile.clone(),
+        profiles: config.profile_names(),
+    }
+}
+
+#[tauri::command]
+fn save_current_as_profile(
+    state: tauri::State<Arc<Mutex<AppConfig>>>,
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<AppConfig, String> {
+    let mut config = state.lock().unwrap();
+    config.save_as_profile(&name)?;
+    config::save_config(&app, &config);
+    Ok(config.clone())
+}
+
+#[tauri::command]
+async fn switch_profile(
+    state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+    auth_state: tauri::State<'_, Arc<Mutex<AuthState>>>,
+    control_state: tauri::State<'_, ServerControl>,
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<AppConfig, String> {
+    // Extract Arc clones upfront so State borrows don't cross await points
+    let status_tx = control_state.status_tx.clone();
+    let shutdown_tx = control_state.shutdown_tx.clone();
+    let broadcast_tx = control_state.broadcast_tx.clone();
+    let config_arc = state.inner().clone();
+    let auth_arc = auth_state.inner().clone();
+
+    let (current, new_config) = {
+        let config = config_arc.lock().unwrap();
+        (config.clone(), config.with_profile(&name)?)
+    };
+
+    let running = matches!(*status_tx.borrow(), ServerStatus::Running);
+    let address_changed = current.server.host != new_config.server.host
+        || current.server.port != new_config.server.port
+        || current.server.local_only != new_config.server.local_only
+        || discovery_changed(&current.server, &new_config.server);
+    if running && address_changed {
+        restart_with_config(
+            config_arc.clone(),
+            auth_arc.clone(),
+            status_tx,
+            shutdown_tx,
+            broadcast_tx,
+            new_config.clone(),
+        )
+        .await;
+    } else {
+        *config_arc.lock().unwrap() = new_config.clone();
+    }
+
+    // Persist after the live config is swapped so the file watcher sees no change
+    config::save_config(&app, &new_config);
+
+    // The middleware enforces the stored auth mode, not `auth.enabled`
+    let protected = matches!(auth_arc.lock().unwrap().mode, AuthMode::Protected);
+    if new_config.auth.enabled != protected {
+        let mode = if new_config.auth.enabled {
+            AuthMode::Protected
+        } else {
+            AuthMode::Public
+        };
+        apply_auth_mode(&app, &config_arc, &auth_arc, mode)?;
+    }
+
+    if current.features.enable_autostart != new_config.features.enable_autostart {
+        let _ = server::autostart::apply(&app, new_config.features.enable_autostart);
+    }
+
+    Ok(new_config)
+}
 
 /// Validate an IP address, CIDR, or hostname entry.
 /// Accepts: raw IP ("192.168.1.1"), CIDR ("192.168.1.0/24"), or hostname ("pi.local").
@@ -551,13 +734,24 @@ fn set_auth_mode(
         _ => return Err("Invalid auth mode".to_string()),
     };
 
+    apply_auth_mode(&app, &state, &auth_state, mode)
+}
+
+/// Persist the auth mode and mirror it into `config.auth.enabled`.
+/// Shared by `set_auth_mode` and the tray's auth toggle.
+fn apply_auth_mode<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &Mutex<AppConfig>,
+    auth_state: &Mutex<AuthState>,
+    mode: AuthMode,
+) -> Result<(), String> {
     let mut auth = auth_state.lock().unwrap();
     auth.mode = mode;
     auth_store::save_auth_state(&auth)?;
 
     let mut config = state.lock().unwrap();
     config.auth.enabled = matches!(auth.mode, AuthMode::Protected);
-    config::save_config(&app, &config);
+    config::save_config(app, &config);
     Ok(())
 }
 
@@ -569,14 +763,26 @@ fn list_api_keys(
     Ok(auth.keys.iter().map(summarize_key).collect())
 }
 
+/// Create a key and return its plaintext, which is never shown again. A
+/// `ttl_seconds` key (e.g. for a guest) expires that long after creation.
 #[tauri::command]
 fn create_api_key(
     auth_state: tauri::State<Arc<Mutex<AuthState>>>,
     name: Option<String>,
     scopes: Vec<String>,
     expires_at: Option<i64>,
+    ttl_seconds: Option<i64>,
 ) -> Result<CreateApiKeyResponse, String> {
     let name = name.unwrap_or_else(|| "API Key".to_string());
+    let scopes = auth_scopes::validate_scopes(scopes)?;
+    let expires_at = match (expires_at, ttl_seconds) {
+        (Some(_), Some(_)) => {
+            return Err("Set either expires_at or ttl_seconds, not both".to_string())
+        }
+        (_, Some(ttl)) if ttl <= 0 => return Err("ttl_seconds must be positive".to_string()),
+        (_, Some(ttl)) => Some(auth_store::now_unix() + ttl),
+        (expires_at, None) => expires_at,
+    };
     let (record, key) = auth_store::create_api_key(name, scopes, expires_at, ApiKeySource::User)?;
 
     let mut auth = auth_state.lock().unwrap();
@@ -617,6 +823,7 @@ fn update_api_key_scopes(
     id: String,
     scopes: Vec<String>,
 ) -> Result<bool, String> {
+    let scopes = auth_scopes::validate_scopes(scopes)?;
     let mut auth = auth_state.lock().unwrap();
     let updated = auth_store::update_key_scopes(&mut auth, &id, scopes);
     auth_store::save_auth_state(&auth)?;
@@ -666,6 +873,22 @@ fn update_disk_cache_seconds(
     Ok(config.clone())
 }
 
+#[tauri::command]
+fn update_gpu_cache_seconds(
+    state: tauri::State<Arc<Mutex<AppConfig>>>,
+    app: tauri::AppHandle,
+    seconds: u64,
+) -> Result<AppConfig, String> {
+    if seconds < 1 || seconds > 300 {
+        return Err("Seconds must be between 1 and 300".to_string());
+    }
+
+    let mut config = state.lock().unwrap();
+    config.stats.gpu_cache_seconds = seconds;
+    config::save_config(&app, &config);
+    Ok(config.clone())
+}
+
 #[tauri::command]
 fn update_stream_interval(
     state: tauri::State<Arc<Mutex<AppConfig>>>,
@@ -689,10 +912,16 @@ fn update_stream_interval(
 #[tauri::command]
 fn update_config(
     state: tauri::State<Arc<Mutex<AppConfig>>>,
+    auth_state: tauri::State<Arc<Mutex<AuthState>>>,
+    control_state: tauri::State<ServerControl>,
     app: tauri::AppHandle,
     updates: serde_json::Value,
 ) -> Result<AppConfig, String> {
     let mut config = state.lock().unwrap();
+    let previous = config.server.clone();
+    // Edits go to a copy that must pass the same checks as config.json, so a
+    // bad value is rejected here instead of saved and refused on next load
+    let mut next = config.clone();
 
     // Apply server updates
     if let Some(server) = updates.get("server") {
@@ -700,36 +929,117 @@ fn update_config(
             if port < 1024 || port > 65535 {
                 return Err("Port must be between 1024 and 65535".to_string());
             }
-            config.server.port = port as u16;
+            next.server.port = port as u16;
         }
         if let Some(host) = server.get("host").and_then(|v| v.as_str()) {
             if host.parse::<std::net::IpAddr>().is_err() {
                 return Err("Invalid IP address".to_string());
             }
-            config.server.host = host.to_string();
+            next.server.host = host.to_string();
+        }
+        if let Some(max) = server.get("max_clients").and_then(|v| v.as_u64()) {
+            if !(1..=1000).contains(&max) {
+                return Err("max_clients must be between 1 and 1000".to_string());
+            }
+            next.server.max_clients = max as usize;
+        }
+        if let Some(local_only) = server.get("local_only").and_then(|v| v.as_bool()) {
+            next.server.local_only = local_only;
+        }
+        if let Some(advertise) = server.get("advertise").and_then(|v| v.as_bool()) {
+            next.server.advertise = advertise;
+        }
+        if let Some(port) = server.get("discovery_port").and_then(|v| v.as_u64()) {
+            if port < 1024 || port > 65535 {
+                return Err("Discovery port must be between 1024 and 65535".to_string());
+            }
+            next.server.discovery_port = port as u16;
+        }
+        if let Some(origins) = server.get("cors_origins").and_then(|v| v.as_array()) {
+            let mut cors_origins = Vec::new();
+            for origin in origins {
+                let origin = origin.as_str().ok_or("cors_origins must be strings")?;
+                let origin = config::validate_cors_origin(origin)?;
+                if !cors_origins.contains(&origin) {
+                    cors_origins.push(origin);
+                }
+            }
+            next.server.cors_origins = cors_origins;
+        }
+    }
+
+    if let Some(safe_mode) = updates.get("safe_mode").and_then(|v| v.as_bool()) {
+        next.safe_mode = safe_mode;
+    }
+    if let Some(level) = updates.get("log_level").and_then(|v| v.as_str()) {
+        if !logging::LOG_LEVELS.contains(&level) {
+            return Err(format!(
+                "log_level must be one of {}",
+                logging::LOG_LEVELS.join(", ")
+            ));
         }
+        next.log_level = level.to_string();
     }
 
     // Apply display updates
     if let Some(display) = updates.get("display") {
         if let Some(hostname) = display.get("hostname").and_then(|v| v.as_str()) {
-            config.display.hostname = hostname.to_string();
+            next.display.hostname = hostname.to_string();
+        }
+        if let Some(unit) = display.get("temp_unit").and_then(|v| v.as_str()) {
+            if !config::TEMP_UNITS.contains(&unit) {
+                return Err(format!(
+                    "display.temp_unit must be one of {}",
+                    config::TEMP_UNITS.join(", ")
+                ));
+            }
+            next.display.temp_unit = unit.to_string();
+        }
+        if let Some(binary) = display.get("binary_units").and_then(|v| v.as_bool()) {
+            next.display.binary_units = binary;
         }
     }
 
     // Apply stats updates
     if let Some(stats) = updates.get("stats") {
         if let Some(gpu) = stats.get("gpu_enabled").and_then(|v| v.as_bool()) {
-            config.stats.gpu_enabled = gpu;
+            next.stats.gpu_enabled = gpu;
         }
         if let Some(disk) = stats.get("disk_cache_seconds").and_then(|v| v.as_u64()) {
-            config.stats.disk_cache_seconds = disk;
+            next.stats.disk_cache_seconds = disk;
+        }
+        if let Some(gpu) = stats.get("gpu_cache_seconds").and_then(|v| v.as_u64()) {
+            next.stats.gpu_cache_seconds = gpu;
+        }
+        if let Some(sensors) = stats.get("sensors_enabled").and_then(|v| v.as_bool()) {
+            next.stats.sensors_enabled = sensors;
+        }
+        if let Some(smoothing) = stats.get("cpu_smoothing").and_then(|v| v.as_f64()) {
+            next.stats.cpu_smoothing = smoothing;
+        }
+        if let Some(ms) = stats
+            .get("refresh_min_interval_ms")
+            .and_then(|v| v.as_u64())
+        {
+            next.stats.refresh_min_interval_ms = ms;
+        }
+        for (key, list) in [
+            ("disk_include", &mut next.stats.disk_include),
+            ("disk_exclude", &mut next.stats.disk_exclude),
+        ] {
+            if let Some(patterns) = stats.get(key).and_then(|v| v.as_array()) {
+                *list = patterns
+                    .iter()
+                    .map(|p| p.as_str().map(String::from))
+                    .collect::<Option<_>>()
+                    .ok_or(format!("{} must be strings", key))?;
+            }
         }
         if let Some(stream) = stats
             .get("stream_interval_seconds")
             .and_then(|v| v.as_u64())
         {
-            config.stats.stream_interval_seconds = stream;
+            next.stats.stream_interval_seconds = stream;
         }
     }
 
@@ -737,31 +1047,77 @@ fn update_config(
     if let Some(ws) = updates.get("websocket") {
         if let Some(stats_cfg) = ws.get("stats") {
             if let Some(enabled) = stats_cfg.get("enabled").and_then(|v| v.as_bool()) {
-                config.websocket.stats.enabled = enabled;
+                next.websocket.stats.enabled = enabled;
             }
             if let Some(interval) = stats_cfg.get("interval_ms").and_then(|v| v.as_u64()) {
-                config.websocket.stats.interval_ms = interval;
+                next.websocket.stats.interval_ms = interval;
             }
         }
         if let Some(media_cfg) = ws.get("media") {
             if let Some(enabled) = media_cfg.get("enabled").and_then(|v| v.as_bool()) {
-                config.websocket.media.enabled = enabled;
+                next.websocket.media.enabled = enabled;
             }
             if let Some(interval) = media_cfg.get("interval_ms").and_then(|v| v.as_u64()) {
-                config.websocket.media.interval_ms = interval;
+                next.websocket.media.interval_ms = interval;
             }
         }
         if let Some(proc_cfg) = ws.get("processes") {
             if let Some(enabled) = proc_cfg.get("enabled").and_then(|v| v.as_bool()) {
-                config.websocket.processes.enabled = enabled;
+                next.websocket.processes.enabled = enabled;
             }
             if let Some(interval) = proc_cfg.get("interval_ms").and_then(|v| v.as_u64()) {
-                config.websocket.processes.interval_ms = interval;
+                next.websocket.processes.interval_ms = interval;
+            }
+            // null clears the cap (unlimited)
+            if let Some(max_items) = proc_cfg.get("max_items") {
+                if max_items.as_u64() == Some(0) {
+                    return Err("max_items must be at least 1".to_string());
+                }
+                next.websocket.processes.max_items = max_items.as_u64().map(|n| n as usize);
+            }
+            if let Some(coalesce) = proc_cfg.get("coalesce").and_then(|v| v.as_bool()) {
+                next.websocket.processes.coalesce = coalesce;
             }
         }
+        if let Some(capacity) = ws.get("channel_capacity").and_then(|v| v.as_u64()) {
+            if !(16..=10000).contains(&capacity) {
+                return Err("channel_capacity must be between 16 and 10000".to_string());
+            }
+            next.websocket.channel_capacity = capacity as usize;
+        }
     }
 
+    // Apply media updates
+    if let Some(media) = updates.get("media") {
+        if let Some(step) = media.get("volume_step").and_then(|v| v.as_u64()) {
+            if !(1..=50).contains(&step) {
+                return Err("volume_step must be between 1 and 50".to_string());
+            }
+            next.media.volume_step = step as u32;
+        }
+    }
+
+    next.validate()?;
+    if next.log_level != config.log_level {
+        logging::set_level(&next.log_level);
+    }
+    *config = next;
     config::save_config(&app, &config);
+
+    // Host, port and local_only wait for the dashboard's restart button, but
+    // nothing there applies discovery changes, so restart for those here
+    let running = matches!(*control_state.status_tx.borrow(), ServerStatus::Running);
+    if running && discovery_changed(&previous, &config.server) {
+        info!("Restarting server to apply discovery settings");
+        tauri::async_runtime::spawn(restart_with_config(
+            state.inner().clone(),
+            auth_state.inner().clone(),
+            control_state.status_tx.clone(),
+            control_state.shutdown_tx.clone(),
+            control_state.broadcast_tx.clone(),
+            config.clone(),
+        ));
+    }
     Ok(config.clone())
 }
 
@@ -769,15 +1125,37 @@ fn update_config(
 // Network Utility Commands
 // ============================================================================
 
+/// Recent log lines for the dashboard; same filtering as `GET /api/logs`.
 #[tauri::command]
-fn get_local_ips() -> Vec<String> {
+fn get_recent_logs(level: Option<String>, limit: Option<usize>) -> Vec<LogEntry> {
+    logging::recent_logs(level.as_deref(), limit.unwrap_or(logging::RECENT_CAPACITY))
+}
+
+/// Client IPs that have called the API, most recently seen first.
+#[tauri::command]
+fn get_client_history() -> Vec<ClientSeen> {
+    server::client_history::shared().lock().unwrap().list()
+}
+
+/// List non-loopback local addresses. IPv6 addresses are only included when
+/// `include_ipv6` is set, since most clients still connect over IPv4.
+#[tauri::command]
+fn get_local_ips(include_ipv6: Option<bool>) -> Vec<String> {
+    let include_ipv6 = include_ipv6.unwrap_or(false);
     let mut ips = Vec::new();
 
     if let Ok(interfaces) = local_ip_address::list_afinet_netifas() {
         for (_, ip) in interfaces {
-            if let std::net::IpAddr::V4(ipv4) = ip {
-                if !ipv4.is_loopback() {
-                    ips.push(ipv4.to_string());
+            match ip {
+                std::net::IpAddr::V4(ipv4) => {
+                    if !ipv4.is_loopback() {
+                        ips.push(ipv4.to_string());
+                    }
+                }
+                std::net::IpAddr::V6(ipv6) => {
+                    if include_ipv6 && !ipv6.is_loopback() && !ips.contains(&ipv6.to_string()) {
+                        ips.push(ipv6.to_string());
+                    }
                 }
             }
         }
@@ -822,12 +1200,32 @@ pub fn run() {
             {
                 app.set_activation_policy(tauri::ActivationPolicy::Accessory);
             }
-            let mut cfg = config::load_config(app.handle());
+            let config_dir = app.path().app_config_dir()?;
+            logging::init(&config_dir.join("logs"));
+
+            // An invalid config.json is left as-is on disk; run with defaults in
+            // memory and report the problem instead of starting the server.
+            let (mut cfg, config_error) = match config::load_config(app.handle()) {
+                Ok(c) => (c, None),
+                Err(e) => {
+                    error!("Config error: {}", e);
+                    (AppConfig::default(), Some(e))
+                }
+            };
+            logging::set_level(&cfg.log_level);
             let port = cfg.server.port;
             let autostart_enabled = cfg.features.enable_autostart;
 
-            let auth_state = auth_store::load_or_migrate(&cfg);
-            cfg.auth.api_key = None;
+            let mut auth_state = auth_store::load_or_migrate(&cfg);
+            // CNTRL_AUTH_ENABLED wins over the persisted mode for this run only
+            if config::env_override_active(config::ENV_AUTH_ENABLED) {
+                auth_state.mode = if cfg.auth.enabled {
+                    AuthMode::Protected
+                } else {
+                    AuthMode::Public
+                };
+            }
+            config::clear_file_api_key(&mut cfg);
             let shared_auth = Arc::new(Mutex::new(auth_state));
             app.manage(shared_auth.clone());
 
@@ -839,26 +1237,74 @@ pub fn run() {
             let (status_tx, _status_rx) = tokio::sync::watch::channel(ServerStatus::Starting);
             let status_tx = Arc::new(status_tx);
             let shutdown_tx = Arc::new(Mutex::new(None));
+            let broadcast_tx = Arc::new(Mutex::new(None));
 
             app.manage(ServerControl {
                 shutdown_tx: shutdown_tx.clone(),
                 status_tx: status_tx.clone(),
+                broadcast_tx: broadcast_tx.clone(),
             });
 
+            config::init_app(app.handle().clone());
+
             // Start initial server
             let auth_state = app.state::<Arc<Mutex<AuthState>>>().inner().clone();
-            spawn_server(port, shared_config, auth_state, status_tx, shutdown_tx);
+            if let Some(e) = config_error {
+                status_tx.send_modify(|s| *s = ServerStatus::Error(e));
+            } else {
+                spawn_server(
+                    port,
+                    shared_config.clone(),
+                    auth_state.clone(),
+                    status_tx.clone(),
+                    shutdown_tx.clone(),
+                    broadcast_tx.clone(),
+                );
+            }
+
+            // Hot-reload config.json edits. Loops re-read the shared config every
+            // iteration, so only address and discovery changes need a full restart.
+            config::spawn_config_watcher(app.handle(), move |mut new_config| {
+                config::clear_file_api_key(&mut new_config);
+                logging::set_level(&new_config.log_level);
+                let current = shared_config.lock().unwrap().clone();
+                let status = status_tx.borrow().clone();
+
+                let address_changed = current.server.host != new_config.server.host
+                    || current.server.port != new_config.server.port
+                    || current.server.local_only != new_config.server.local_only
+                    || discovery_changed(&current.server, &new_config.server);
+                // A server left in Error (e.g. by a rejected config) comes back up
+                // once a valid file is written.
+                let needs_restart = match status {
+                    ServerStatus::Running => address_changed,
+                    ServerStatus::Error(_) => true,
+                    _ => false,
+                };
+
+                if needs_restart {
+                    info!("Restarting server with updated config");
+                    tauri::async_runtime::spawn(restart_with_config(
+                        shared_config.clone(),
+                        auth_state.clone(),
+                        status_tx.clone(),
+                        shutdown_tx.clone(),
+                        broadcast_tx.clone(),
+                        new_config,
+                    ));
+                } else if current != new_config {
+                    info!("Applied config changes without restart");
+                    *shared_config.lock().unwrap() = new_config;
+                }
+            });
 
             // Sync Autostart
             // let autostart_enabled is extracted above
 
-            if autostart_enabled {
-                let _ = app.handle().autolaunch().enable();
-            } else {
-                let _ = app.handle().autolaunch().disable();
-            }
+            let _ = server::autostart::apply(app.handle(), autostart_enabled);
 
-            tray::create_tray(app.handle())?;
+            let control = app.state::<ServerControl>().inner().clone();
+            tray::create_tray(app.handle(), control)?;
 
             // Only create the window when NOT launched minimized (e.g. autostart)
             let minimized = std::env::args().any(|a| a == "--minimized");
@@ -896,6 +1342,12 @@ pub fn run() {
             update_server_port,
             update_server_host,
             update_hostname,
+            add_cors_origin,
+            remove_cors_origin,
+            // Profiles
+            list_profiles,
+            switch_profile,
+            save_current_as_profile,
             // Auth
             get_auth_mode,
             set_auth_mode,
@@ -913,11 +1365,15 @@ pub fn run() {
             // Stats config
             toggle_gpu_stats,
             update_disk_cache_seconds,
+            update_gpu_cache_seconds,
             update_stream_interval,
             // Batch update
             update_config,
             // Network utilities
             get_local_ips,
+            // Logs
+            get_recent_logs,
+            get_client_history,
             // macOS window styling
             mac_rounded_corners::enable_rounded_corners,
             mac_rounded_corners::enable_modern_window_style,
@@ -0,0 +1,151 @@
+use crate::server::types::LogEntry;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::{fmt, prelude::*, reload, EnvFilter, Registry};
+
+/// Accepted values for `log_level`, quietest first.
+pub const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+/// Days of log files kept in the log directory.
+const MAX_LOG_FILES: usize = 7;
+
+/// Log lines kept in memory for `recent_logs`.
+pub const RECENT_CAPACITY: usize = 1000;
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static RECENT: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+/// `level` applies to the bridge and its HTTP request traces; other crates
+/// only log warnings. `RUST_LOG` replaces the whole filter when set.
+fn filter(level: &str) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("warn,bridge_lib={0},tower_http={0}", level)))
+}
+
+/// Install the global subscriber: stdout, plus a daily file in `log_dir`
+/// (`bridge.YYYY-MM-DD.log`). Logs at `info` until `set_level` is called
+/// with the configured level.
+pub fn init(log_dir: &Path) {
+    let (filter, handle) = reload::Layer::new(filter("info"));
+    let file = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("bridge")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir);
+
+    let (file_layer, file_error) = match file {
+        Ok(writer) => (
+            Some(fmt::layer().with_ansi(false).with_writer(writer)),
+            None,
+        ),
+        Err(e) => (None, Some(e)),
+    };
+    if tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .with(file_layer)
+        .with(RecentLogs)
+        .try_init()
+        .is_err()
+    {
+        return;
+    }
+    let _ = FILTER.set(handle);
+
+    if let Some(e) = file_error {
+        tracing::warn!(
+            "Logging to stdout only, can't write to {}: {}",
+            log_dir.display(),
+            e
+        );
+    }
+}
+
+/// Apply a `log_level` from config. No-op before `init`.
+pub fn set_level(level: &str) {
+    if let Some(handle) = FILTER.get() {
+        let _ = handle.reload(filter(level));
+    }
+}
+
+/// The newest `limit` captured lines at `level` or more severe (all levels
+/// when `None`), oldest first. Only lines that passed `log_level` are kept.
+pub fn recent_logs(level: Option<&str>, limit: usize) -> Vec<LogEntry> {
+    let max_rank = level.map_or(LOG_LEVELS.len(), rank);
+    let recent = RECENT.lock().unwrap();
+    let mut entries: Vec<LogEntry> = recent
+        .iter()
+        .rev()
+        .filter(|e| rank(&e.level) <= max_rank)
+        .take(limit)
+        .cloned()
+        .collect();
+    entries.reverse();
+    entries
+}
+
+/// Position in `LOG_LEVELS`; lower is more severe.
+fn rank(level: &str) -> usize {
+    LOG_LEVELS
+        .iter()
+        .position(|l| *l == level)
+        .unwrap_or(LOG_LEVELS.len())
+}
+
+/// Copies every event that passes the filter into `RECENT`.
+struct RecentLogs;
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let entry = LogEntry {
+            timestamp,
+            level: metadata.level().as_str().to_lowercase(),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+        };
+
+        let mut recent = RECENT.lock().unwrap();
+        if recent.len() == RECENT_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(entry);
+    }
+}
+
+/// Renders an event as its message followed by ` key=value` for other fields.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields += &format!(" {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields += &format!(" {}={:?}", field.name(), value);
+        }
+    }
+}
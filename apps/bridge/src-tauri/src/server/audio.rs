@@ -0,0 +1,316 @@
+use crate::server::types::AudioDevice;
+
+/// Active audio output devices, or `None` on platforms where they can't be
+/// listed.
+pub fn list_output_devices() -> Option<Vec<AudioDevice>> {
+    platform::list_output_devices()
+}
+
+/// Name of the current default output device.
+pub fn default_output_device_name() -> Option<String> {
+    list_output_devices()?
+        .into_iter()
+        .find(|d| d.is_default)
+        .map(|d| d.name)
+}
+
+/// Make the device with this `id` (as returned by `list_output_devices`) the
+/// default output.
+pub fn set_default_output_device(id: &str) -> Result<(), String> {
+    platform::set_default_output_device(id)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use crate::server::types::AudioDevice;
+    use std::ffi::c_void;
+    use windows::core::{IUnknown, Interface, GUID, HRESULT, PCWSTR};
+    use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+    use windows::Win32::Media::Audio::{
+        eCommunications, eConsole, eMultimedia, eRender, ERole, IMMDevice, IMMDeviceEnumerator,
+        MMDeviceEnumerator, DEVICE_STATE_ACTIVE,
+    };
+    use windows::Win32::System::Com::StructuredStorage::PropVariantClear;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_MULTITHREADED,
+        STGM_READ,
+    };
+
+    // IPolicyConfig is undocumented but has been stable since Vista; it's
+    // what the Sound control panel itself uses to change the default device.
+    const CLSID_POLICY_CONFIG_CLIENT: GUID =
+        GUID::from_u128(0x870af99c_171d_4f9e_af0d_e63df40c2bc9);
+    const IID_POLICY_CONFIG: GUID = GUID::from_u128(0xf8679f50_850a_41cf_9c72_430f290290c8);
+    /// Vtable slot of `SetDefaultEndpoint` (after IUnknown's 3 and 10 others).
+    const SET_DEFAULT_ENDPOINT_SLOT: usize = 13;
+    const RELEASE_SLOT: usize = 2;
+
+    type SetDefaultEndpointFn = unsafe extern "system" fn(*mut c_void, PCWSTR, ERole) -> HRESULT;
+    type ReleaseFn = unsafe extern "system" fn(*mut c_void) -> u32;
+
+    unsafe fn enumerator() -> Option<IMMDeviceEnumerator> {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()
+    }
+
+    unsafe fn device_id(device: &IMMDevice) -> Option<String> {
+        let raw = device.GetId().ok()?;
+        let id = raw.to_string().ok();
+        CoTaskMemFree(Some(raw.0 as *const c_void));
+        id
+    }
+
+    unsafe fn device_name(device: &IMMDevice) -> Option<String> {
+        let store = device.OpenPropertyStore(STGM_READ).ok()?;
+        let mut value = store.GetValue(&PKEY_Device_FriendlyName).ok()?;
+        let name = value.Anonymous.Anonymous.Anonymous.pwszVal.to_string().ok();
+        let _ = PropVariantClear(&mut value);
+        name
+    }
+
+    pub fn list_output_devices() -> Option<Vec<AudioDevice>> {
+        unsafe {
+            let enumerator = enumerator()?;
+            let default_id = enumerator
+                .GetDefaultAudioEndpoint(eRender, eConsole)
+                .ok()
+                .and_then(|d| device_id(&d));
+            let collection = enumerator
+                .EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)
+                .ok()?;
+
+            let mut devices = Vec::new();
+            for i in 0..collection.GetCount().ok()? {
+                let Ok(device) = collection.Item(i) else {
+                    continue;
+                };
+                let Some(id) = device_id(&device) else {
+                    continue;
+                };
+                devices.push(AudioDevice {
+                    name: device_name(&device).unwrap_or_else(|| id.clone()),
+                    is_default: default_id.as_deref() == Some(id.as_str()),
+                    id,
+                });
+            }
+            Some(devices)
+        }
+    }
+
+    pub fn set_default_output_device(id: &str) -> Result<(), String> {
+        let wide: Vec<u16> = id.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let unknown: IUnknown = CoCreateInstance(&CLSID_POLICY_CONFIG_CLIENT, None, CLSCTX_ALL)
+                .map_err(|e| format!("IPolicyConfig unavailable: {}", e))?;
+            let mut policy: *mut c_void = std::ptr::null_mut();
+            unknown
+                .query(&IID_POLICY_CONFIG, &mut policy as *mut _ as _)
+                .ok()
+                .map_err(|e| format!("IPolicyConfig unavailable: {}", e))?;
+
+            let vtable = *(policy as *const *const usize);
+            let set_default: SetDefaultEndpointFn =
+                std::mem::transmute(*vtable.add(SET_DEFAULT_ENDPOINT_SLOT));
+            let release: ReleaseFn = std::mem::transmute(*vtable.add(RELEASE_SLOT));
+
+            // Switch every role so calls and media follow the new device too.
+            let mut result = Ok(());
+            for role in [eConsole, eMultimedia, eCommunications] {
+                if let Err(e) = set_default(policy, PCWSTR(wide.as_ptr()), role).ok() {
+                    result = Err(format!("Failed to set default device: {}", e));
+                    break;
+                }
+            }
+            release(policy);
+            result
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use crate::server::types::AudioDevice;
+    use cocoa::base::{id, nil};
+    use objc::{msg_send, sel, sel_impl};
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::c_char;
+
+    #[repr(C)]
+    struct PropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectGetPropertyDataSize(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            size: *mut u32,
+        ) -> i32;
+        fn AudioObjectGetPropertyData(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+        fn AudioObjectSetPropertyData(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            size: u32,
+            data: *const c_void,
+        ) -> i32;
+    }
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: id);
+    }
+
+    const fn fourcc(code: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*code)
+    }
+
+    const SYSTEM_OBJECT: u32 = 1;
+    const SCOPE_GLOBAL: u32 = fourcc(b"glob");
+    const SCOPE_OUTPUT: u32 = fourcc(b"outp");
+    const ELEMENT_MAIN: u32 = 0;
+    const PROP_DEVICES: u32 = fourcc(b"dev#");
+    const PROP_DEFAULT_OUTPUT: u32 = fourcc(b"dOut");
+    const PROP_NAME: u32 = fourcc(b"lnam");
+    const PROP_UID: u32 = fourcc(b"uid ");
+    const PROP_STREAMS: u32 = fourcc(b"stm#");
+
+    fn address(selector: u32, scope: u32) -> PropertyAddress {
+        PropertyAddress {
+            selector,
+            scope,
+            element: ELEMENT_MAIN,
+        }
+    }
+
+    unsafe fn data_size(object: u32, addr: &PropertyAddress) -> Option<u32> {
+        let mut size = 0u32;
+        (AudioObjectGetPropertyDataSize(object, addr, 0, std::ptr::null(), &mut size) == 0)
+            .then_some(size)
+    }
+
+    unsafe fn get<T: Copy>(object: u32, addr: &PropertyAddress, init: T) -> Option<T> {
+        let mut value = init;
+        let mut size = std::mem::size_of::<T>() as u32;
+        let status = AudioObjectGetPropertyData(
+            object,
+            addr,
+            0,
+            std::ptr::null(),
+            &mut size,
+            &mut value as *mut T as *mut c_void,
+        );
+        (status == 0).then_some(value)
+    }
+
+    /// Reads a CFString property (toll-free bridged to NSString).
+    unsafe fn get_string(object: u32, selector: u32) -> Option<String> {
+        let cf: id = get(object, &address(selector, SCOPE_GLOBAL), nil)?;
+        if cf == nil {
+            return None;
+        }
+        let utf8: *const c_char = msg_send![cf, UTF8String];
+        let s = (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned());
+        CFRelease(cf);
+        s
+    }
+
+    unsafe fn all_devices() -> Vec<u32> {
+        let addr = address(PROP_DEVICES, SCOPE_GLOBAL);
+        let Some(size) = data_size(SYSTEM_OBJECT, &addr) else {
+            return Vec::new();
+        };
+        let mut ids = vec![0u32; size as usize / std::mem::size_of::<u32>()];
+        let mut size = size;
+        let status = AudioObjectGetPropertyData(
+            SYSTEM_OBJECT,
+            &addr,
+            0,
+            std::ptr::null(),
+            &mut size,
+            ids.as_mut_ptr() as *mut c_void,
+        );
+        if status != 0 {
+            return Vec::new();
+        }
+        ids.truncate(size as usize / std::mem::size_of::<u32>());
+        ids
+    }
+
+    /// Devices with at least one output stream (skips microphones).
+    unsafe fn output_devices() -> Vec<(u32, String)> {
+        all_devices()
+            .into_iter()
+            .filter(|&dev| data_size(dev, &address(PROP_STREAMS, SCOPE_OUTPUT)).unwrap_or(0) > 0)
+            .filter_map(|dev| Some((dev, get_string(dev, PROP_UID)?)))
+            .collect()
+    }
+
+    pub fn list_output_devices() -> Option<Vec<AudioDevice>> {
+        unsafe {
+            let default = get(
+                SYSTEM_OBJECT,
+                &address(PROP_DEFAULT_OUTPUT, SCOPE_GLOBAL),
+                0u32,
+            );
+            Some(
+                output_devices()
+                    .into_iter()
+                    .map(|(dev, uid)| AudioDevice {
+                        name: get_string(dev, PROP_NAME).unwrap_or_else(|| uid.clone()),
+                        is_default: default == Some(dev),
+                        id: uid,
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    pub fn set_default_output_device(uid: &str) -> Result<(), String> {
+        unsafe {
+            let dev = output_devices()
+                .into_iter()
+                .find(|(_, u)| u == uid)
+                .map(|(dev, _)| dev)
+                .ok_or_else(|| format!("Unknown output device: {}", uid))?;
+            let status = AudioObjectSetPropertyData(
+                SYSTEM_OBJECT,
+                &address(PROP_DEFAULT_OUTPUT, SCOPE_GLOBAL),
+                0,
+                std::ptr::null(),
+                std::mem::size_of::<u32>() as u32,
+                &dev as *const u32 as *const c_void,
+            );
+            if status != 0 {
+                return Err(format!("CoreAudio error {}", status));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+mod platform {
+    use crate::server::types::AudioDevice;
+
+    pub fn list_output_devices() -> Option<Vec<AudioDevice>> {
+        None
+    }
+
+    pub fn set_default_output_device(_id: &str) -> Result<(), String> {
+        Err("Not supported on this platform".to_string())
+    }
+}
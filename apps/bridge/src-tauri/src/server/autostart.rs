@@ -0,0 +1,35 @@
+use crate::config::{self, AppConfig};
+use std::sync::Mutex;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_autostart::ManagerExt;
+
+/// Register or unregister the app as a login item.
+pub fn apply<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Whether the app is currently registered to start on login.
+pub fn is_enabled() -> Result<bool, String> {
+    let app = config::app()?;
+    app.autolaunch().is_enabled().map_err(|e| e.to_string())
+}
+
+/// Apply `enabled` and record it as `features.enable_autostart`, in the live
+/// config and on disk.
+pub fn set_enabled(config: &Mutex<AppConfig>, enabled: bool) -> Result<(), String> {
+    let app = config::app()?;
+    apply(app, enabled)?;
+
+    let mut config = config.lock().unwrap();
+    if config.features.enable_autostart != enabled {
+        config.features.enable_autostart = enabled;
+        config::save_config(app, &config);
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::server::types::ClientSeen;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Most client IPs remembered; the least recently seen is dropped first.
+pub const MAX_CLIENTS: usize = 256;
+
+/// Last-seen time and request count per client IP.
+#[derive(Default)]
+pub struct ClientHistory {
+    clients: HashMap<IpAddr, ClientSeen>,
+}
+
+impl ClientHistory {
+    pub fn record(&mut self, ip: IpAddr) {
+        let now = now_millis();
+        if let Some(seen) = self.clients.get_mut(&ip) {
+            seen.last_seen = now;
+            seen.request_count += 1;
+            return;
+        }
+
+        if self.clients.len() >= MAX_CLIENTS {
+            if let Some(oldest) = self
+                .clients
+                .iter()
+                .min_by_key(|(_, seen)| seen.last_seen)
+                .map(|(ip, _)| *ip)
+            {
+                self.clients.remove(&oldest);
+            }
+        }
+        self.clients.insert(
+            ip,
+            ClientSeen {
+                ip: ip.to_string(),
+                first_seen: now,
+                last_seen: now,
+                request_count: 1,
+            },
+        );
+    }
+
+    /// Every remembered client, most recently seen first.
+    pub fn list(&self) -> Vec<ClientSeen> {
+        let mut clients: Vec<ClientSeen> = self.clients.values().cloned().collect();
+        clients.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        clients
+    }
+}
+
+/// The history shared by every server instance, so it survives restarts and
+/// the dashboard can read it while the server is stopped.
+pub fn shared() -> Arc<Mutex<ClientHistory>> {
+    static HISTORY: OnceLock<Arc<Mutex<ClientHistory>>> = OnceLock::new();
+    HISTORY.get_or_init(Default::default).clone()
+}
+
+fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
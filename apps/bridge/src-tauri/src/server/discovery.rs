@@ -0,0 +1,38 @@
+use serde_json::json;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// Datagram a client broadcasts to find bridges on the LAN.
+const DISCOVER_MESSAGE: &str = "CNTRL_DISCOVER";
+
+/// Answer `CNTRL_DISCOVER` datagrams on `discovery_port` with the bridge's
+/// name, API port and version. Binds every IPv4 interface, since broadcasts
+/// aren't delivered to a socket bound to a single address.
+pub async fn spawn_responder(
+    discovery_port: u16,
+    hostname: String,
+    api_port: u16,
+) -> std::io::Result<JoinHandle<()>> {
+    let socket = UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, discovery_port))).await?;
+    let reply = json!({
+        "hostname": hostname,
+        "port": api_port,
+        "version": env!("CARGO_PKG_VERSION"),
+    })
+    .to_string();
+
+    Ok(tokio::spawn(async move {
+        let mut buf = [0u8; 64];
+        loop {
+            // Errors here are per-datagram (e.g. an ICMP port-unreachable
+            // from an earlier reply on Windows), so keep listening
+            let Ok((len, from)) = socket.recv_from(&mut buf).await else {
+                continue;
+            };
+            if std::str::from_utf8(&buf[..len]).map(str::trim) == Ok(DISCOVER_MESSAGE) {
+                let _ = socket.send_to(reply.as_bytes(), from).await;
+            }
+        }
+    }))
+}
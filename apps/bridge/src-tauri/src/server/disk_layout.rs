@@ -0,0 +1,285 @@
+use crate::server::gpu::run;
+use crate::server::types::{DiskLayout, DiskPartition, PhysicalDisk};
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+fn unavailable(message: impl Into<String>) -> DiskLayout {
+    DiskLayout {
+        available: false,
+        message: Some(message.into()),
+        disks: Vec::new(),
+    }
+}
+
+/// Physical disks and the partitions on them, with where each is mounted.
+pub fn get_disk_layout() -> DiskLayout {
+    match read_layout() {
+        Ok(disks) => DiskLayout {
+            available: true,
+            message: None,
+            disks,
+        },
+        Err(message) => unavailable(message),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_layout() -> Result<Vec<PhysicalDisk>, String> {
+    use serde_json::Value;
+
+    let mut cmd = Command::new("lsblk");
+    cmd.args([
+        "--json",
+        "--bytes",
+        "--output",
+        "NAME,TYPE,SIZE,FSTYPE,MOUNTPOINT,MODEL,RM",
+    ]);
+    let json: Value = serde_json::from_slice(&run(&mut cmd)?)
+        .map_err(|e| format!("Unexpected lsblk output: {}", e))?;
+
+    fn text(device: &Value, key: &str) -> Option<String> {
+        device
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    // Older lsblk versions print numbers as strings
+    fn number(device: &Value, key: &str) -> u64 {
+        device
+            .get(key)
+            .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+            .unwrap_or(0)
+    }
+
+    fn flag(device: &Value, key: &str) -> Option<bool> {
+        device
+            .get(key)
+            .and_then(|v| v.as_bool().or_else(|| v.as_str().map(|s| s == "1")))
+    }
+
+    // Mounts of a partition include those of LVM or LUKS devices inside it
+    fn mounts(device: &Value, out: &mut Vec<String>) {
+        if let Some(mount) = text(device, "mountpoint") {
+            out.push(mount);
+        }
+        for child in children(device) {
+            mounts(child, out);
+        }
+    }
+
+    fn children(device: &Value) -> &[Value] {
+        device
+            .get("children")
+            .and_then(|c| c.as_array())
+            .map(|c| c.as_slice())
+            .unwrap_or_default()
+    }
+
+    let devices = json
+        .get("blockdevices")
+        .and_then(|d| d.as_array())
+        .ok_or("Unexpected lsblk output: no blockdevices")?;
+
+    Ok(devices
+        .iter()
+        .filter(|d| text(d, "type").as_deref() == Some("disk"))
+        .map(|disk| {
+            let partitions = children(disk)
+                .iter()
+                .filter(|p| text(p, "type").as_deref() == Some("part"))
+                .map(|part| {
+                    let mut mount_points = Vec::new();
+                    mounts(part, &mut mount_points);
+                    DiskPartition {
+                        name: format!("/dev/{}", text(part, "name").unwrap_or_default()),
+                        size: number(part, "size"),
+                        file_system: text(part, "fstype"),
+                        mount_points,
+                    }
+                })
+                .collect();
+            // A filesystem written straight to the disk, with no partition table
+            let mut mount_points = Vec::new();
+            if let Some(mount) = text(disk, "mountpoint") {
+                mount_points.push(mount);
+            }
+            PhysicalDisk {
+                name: format!("/dev/{}", text(disk, "name").unwrap_or_default()),
+                model: text(disk, "model"),
+                size: number(disk, "size"),
+                removable: flag(disk, "rm"),
+                mount_points,
+                partitions,
+            }
+        })
+        .collect())
+}
+
+#[cfg(target_os = "macos")]
+fn read_layout() -> Result<Vec<PhysicalDisk>, String> {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct List {
+        all_disks_and_partitions: Vec<Disk>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Disk {
+        device_identifier: String,
+        #[serde(default)]
+        size: u64,
+        mount_point: Option<String>,
+        #[serde(default)]
+        partitions: Vec<Volume>,
+        #[serde(default, rename = "APFSVolumes")]
+        apfs_volumes: Vec<Volume>,
+        /// Set for APFS containers, which are synthesized disks backed by a
+        /// partition on a physical one.
+        #[serde(default, rename = "APFSPhysicalStores")]
+        apfs_physical_stores: Vec<Store>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Volume {
+        device_identifier: String,
+        #[serde(default)]
+        size: u64,
+        content: Option<String>,
+        mount_point: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Store {
+        device_identifier: String,
+    }
+
+    let mut cmd = Command::new("diskutil");
+    cmd.args(["list", "-plist"]);
+    let list: List = plist::from_bytes(&run(&mut cmd)?)
+        .map_err(|e| format!("Unexpected diskutil output: {}", e))?;
+
+    // Show the volumes of each APFS container under the partition it lives on
+    let mut container_mounts: std::collections::HashMap<&str, Vec<String>> = Default::default();
+    for container in &list.all_disks_and_partitions {
+        let volume_mounts: Vec<String> = container
+            .apfs_volumes
+            .iter()
+            .filter_map(|v| v.mount_point.clone())
+            .collect();
+        for store in &container.apfs_physical_stores {
+            container_mounts
+                .entry(store.device_identifier.as_str())
+                .or_default()
+                .extend(volume_mounts.iter().cloned());
+        }
+    }
+
+    Ok(list
+        .all_disks_and_partitions
+        .iter()
+        .filter(|d| d.apfs_physical_stores.is_empty())
+        .map(|disk| PhysicalDisk {
+            name: format!("/dev/{}", disk.device_identifier),
+            model: None,
+            size: disk.size,
+            removable: None,
+            mount_points: disk.mount_point.clone().into_iter().collect(),
+            partitions: disk
+                .partitions
+                .iter()
+                .map(|part| {
+                    let mut mount_points: Vec<String> =
+                        part.mount_point.clone().into_iter().collect();
+                    if let Some(mounts) = container_mounts.get(part.device_identifier.as_str()) {
+                        mount_points.extend(mounts.iter().cloned());
+                    }
+                    DiskPartition {
+                        name: format!("/dev/{}", part.device_identifier),
+                        size: part.size,
+                        file_system: part.content.clone(),
+                        mount_points,
+                    }
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+#[cfg(target_os = "windows")]
+fn read_layout() -> Result<Vec<PhysicalDisk>, String> {
+    use serde::Deserialize;
+
+    // PowerShell's ConvertTo-Json writes a one-element array as a bare
+    // object, hence the @() around every list
+    let script = "$disks = Get-CimInstance Win32_DiskDrive | ForEach-Object { \
+        $parts = Get-CimAssociatedInstance -InputObject $_ -ResultClassName Win32_DiskPartition | ForEach-Object { \
+            $volumes = @(Get-CimAssociatedInstance -InputObject $_ -ResultClassName Win32_LogicalDisk); \
+            [pscustomobject]@{ name = $_.Name; size = [uint64]$_.Size; \
+                file_system = ($volumes | Select-Object -First 1).FileSystem; \
+                mount_points = @($volumes | ForEach-Object { $_.DeviceID + '\\' }) } }; \
+        [pscustomobject]@{ name = $_.DeviceID; model = $_.Model; size = [uint64]$_.Size; \
+            removable = $_.MediaType -like 'Removable*'; partitions = @($parts) } }; \
+        ConvertTo-Json -Depth 4 -Compress -InputObject @($disks)";
+
+    #[derive(Deserialize)]
+    struct Disk {
+        name: String,
+        model: Option<String>,
+        size: Option<u64>,
+        removable: Option<bool>,
+        #[serde(default)]
+        partitions: Vec<Partition>,
+    }
+
+    #[derive(Deserialize)]
+    struct Partition {
+        name: String,
+        size: Option<u64>,
+        file_system: Option<String>,
+        #[serde(default)]
+        mount_points: Vec<String>,
+    }
+
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", script]);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let disks: Vec<Disk> = serde_json::from_slice(&run(&mut cmd)?)
+        .map_err(|e| format!("Unexpected WMI output: {}", e))?;
+
+    Ok(disks
+        .into_iter()
+        .map(|disk| PhysicalDisk {
+            name: disk.name,
+            model: disk.model.map(|m| m.trim().to_string()),
+            size: disk.size.unwrap_or(0),
+            removable: disk.removable,
+            mount_points: Vec::new(),
+            partitions: disk
+                .partitions
+                .into_iter()
+                .map(|part| DiskPartition {
+                    name: part.name,
+                    size: part.size.unwrap_or(0),
+                    file_system: part.file_system,
+                    mount_points: part.mount_points,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn read_layout() -> Result<Vec<PhysicalDisk>, String> {
+    Err("Disk layout is not supported on this platform".to_string())
+}
@@ -0,0 +1,228 @@
+use crate::server::types::DisplayInfo;
+use xcap::Monitor;
+
+pub enum BrightnessError {
+    /// No display with that id
+    NotFound,
+    /// The display can't have its brightness set (e.g. no DDC/CI)
+    Unsupported(String),
+    Failed(String),
+}
+
+/// Connected displays. `id` is the one `set_brightness` takes.
+pub fn list_displays() -> Result<Vec<DisplayInfo>, String> {
+    let monitors = Monitor::all().map_err(|e| format!("Failed to list displays: {}", e))?;
+    Ok(monitors
+        .iter()
+        .map(|m| DisplayInfo {
+            id: m.id(),
+            name: m.name().to_string(),
+            width: m.width(),
+            height: m.height(),
+            x: m.x(),
+            y: m.y(),
+            scale_factor: m.scale_factor(),
+            is_primary: m.is_primary(),
+            brightness: platform::get_brightness(m),
+        })
+        .collect())
+}
+
+/// Set display `id`'s brightness to `value` percent (0-100).
+pub fn set_brightness(id: u32, value: u32) -> Result<(), BrightnessError> {
+    let monitors = Monitor::all()
+        .map_err(|e| BrightnessError::Failed(format!("Failed to list displays: {}", e)))?;
+    let monitor = monitors
+        .iter()
+        .find(|m| m.id() == id)
+        .ok_or(BrightnessError::NotFound)?;
+    platform::set_brightness(monitor, value.min(100))
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::BrightnessError;
+    use windows::Win32::Devices::Display::{
+        DestroyPhysicalMonitors, GetMonitorBrightness, GetNumberOfPhysicalMonitorsFromHMONITOR,
+        GetPhysicalMonitorsFromHMONITOR, SetMonitorBrightness, PHYSICAL_MONITOR,
+    };
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::Graphics::Gdi::{MonitorFromPoint, MONITOR_DEFAULTTONULL};
+    use xcap::Monitor;
+
+    /// Physical monitors behind the display, found by its top-left corner.
+    /// Must be released with `DestroyPhysicalMonitors`.
+    unsafe fn physical_monitors(monitor: &Monitor) -> Vec<PHYSICAL_MONITOR> {
+        let point = POINT {
+            x: monitor.x(),
+            y: monitor.y(),
+        };
+        let hmonitor = MonitorFromPoint(point, MONITOR_DEFAULTTONULL);
+        if hmonitor.is_invalid() {
+            return Vec::new();
+        }
+        let mut count = 0u32;
+        if GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut count) == 0 || count == 0 {
+            return Vec::new();
+        }
+        let mut physical = vec![PHYSICAL_MONITOR::default(); count as usize];
+        if GetPhysicalMonitorsFromHMONITOR(hmonitor, &mut physical) == 0 {
+            return Vec::new();
+        }
+        physical
+    }
+
+    /// DDC/CI brightness as (min, current, max).
+    unsafe fn read(physical: &PHYSICAL_MONITOR) -> Option<(u32, u32, u32)> {
+        let (mut min, mut current, mut max) = (0u32, 0u32, 0u32);
+        (GetMonitorBrightness(physical.hPhysicalMonitor, &mut min, &mut current, &mut max) != 0
+            && max > min)
+            .then_some((min, current, max))
+    }
+
+    pub fn get_brightness(monitor: &Monitor) -> Option<u32> {
+        unsafe {
+            let physical = physical_monitors(monitor);
+            let brightness = physical
+                .first()
+                .and_then(|p| read(p))
+                // Some monitors report a current level outside their own range
+                .map(|(min, current, max)| {
+                    (current.saturating_sub(min) * 100 / (max - min)).min(100)
+                });
+            DestroyPhysicalMonitors(&physical);
+            brightness
+        }
+    }
+
+    pub fn set_brightness(monitor: &Monitor, value: u32) -> Result<(), BrightnessError> {
+        unsafe {
+            let physical = physical_monitors(monitor);
+            // Built-in laptop panels have no DDC/CI; only external monitors answer.
+            let mut result = Err(BrightnessError::Unsupported(format!(
+                "{} does not support DDC/CI brightness control",
+                monitor.name()
+            )));
+            for p in &physical {
+                let Some((min, _, max)) = read(p) else {
+                    continue;
+                };
+                let level = min + (max - min) * value / 100;
+                result = if SetMonitorBrightness(p.hPhysicalMonitor, level) != 0 {
+                    Ok(())
+                } else {
+                    Err(BrightnessError::Failed(format!(
+                        "{} rejected the brightness change",
+                        monitor.name()
+                    )))
+                };
+            }
+            DestroyPhysicalMonitors(&physical);
+            result
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::BrightnessError;
+    use std::process::Command;
+    use xcap::Monitor;
+
+    pub fn get_brightness(_monitor: &Monitor) -> Option<u32> {
+        None
+    }
+
+    /// Uses the `brightness` CLI (`brew install brightness`), which drives
+    /// Apple displays. Third-party monitors over DDC are not reachable this way.
+    pub fn set_brightness(monitor: &Monitor, value: u32) -> Result<(), BrightnessError> {
+        let output = Command::new("brightness")
+            .args([
+                "-d".to_string(),
+                monitor.id().to_string(),
+                format!("{:.2}", value as f32 / 100.0),
+            ])
+            .output()
+            .map_err(|_| {
+                BrightnessError::Unsupported(
+                    "The `brightness` tool is not installed (brew install brightness)".to_string(),
+                )
+            })?;
+        if !output.status.success() {
+            return Err(BrightnessError::Unsupported(format!(
+                "{} does not support brightness control: {}",
+                monitor.name(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+mod platform {
+    use super::BrightnessError;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use xcap::Monitor;
+
+    /// The kernel backlight drives the built-in panel (`eDP-1`, `LVDS-1`).
+    fn backlight(monitor: &Monitor) -> Option<PathBuf> {
+        let name = monitor.name();
+        if !(name.starts_with("eDP") || name.starts_with("LVDS")) {
+            return None;
+        }
+        std::fs::read_dir("/sys/class/backlight")
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .next()
+    }
+
+    fn read_u32(path: PathBuf) -> Option<u32> {
+        std::fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    pub fn get_brightness(monitor: &Monitor) -> Option<u32> {
+        let dir = backlight(monitor)?;
+        let max = read_u32(dir.join("max_brightness")).filter(|&m| m > 0)?;
+        Some(read_u32(dir.join("brightness"))? * 100 / max)
+    }
+
+    /// Writes the backlight for the built-in panel; other outputs get
+    /// `xrandr --brightness`, which only scales the gamma in software.
+    pub fn set_brightness(monitor: &Monitor, value: u32) -> Result<(), BrightnessError> {
+        if let Some(dir) = backlight(monitor) {
+            let max = read_u32(dir.join("max_brightness")).unwrap_or(0);
+            return std::fs::write(dir.join("brightness"), (max * value / 100).to_string())
+                .map_err(|e| {
+                    BrightnessError::Failed(format!(
+                        "Failed to write {}: {} (the user needs write access to the backlight)",
+                        dir.join("brightness").display(),
+                        e
+                    ))
+                });
+        }
+
+        let output = Command::new("xrandr")
+            .args([
+                "--output",
+                monitor.name(),
+                "--brightness",
+                &format!("{:.2}", value as f32 / 100.0),
+            ])
+            .output()
+            .map_err(|_| {
+                BrightnessError::Unsupported(
+                    "No backlight for this display and xrandr is not installed".to_string(),
+                )
+            })?;
+        if !output.status.success() {
+            return Err(BrightnessError::Failed(format!(
+                "xrandr failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// Error returned by REST handlers.
+/// Serialized as `{"code": "...", "message": "..."}`, the same shape as the
+/// `data` of a WebSocket `error` message.
+#[derive(Serialize, Debug)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+    /// The values that would have been accepted, for `UNSUPPORTED_ACTION`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supported: Option<Vec<String>>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code: code.to_string(),
+            message: message.into(),
+            supported: None,
+        }
+    }
+
+    /// List the values that are valid in place of the rejected one.
+    pub fn with_supported(mut self, supported: &[&str]) -> Self {
+        self.supported = Some(supported.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    /// The feature backing this endpoint is turned off in config.
+    pub fn feature_disabled(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "FEATURE_DISABLED", message)
+    }
+
+    /// No usable API key; `code` says whether it was missing, unknown,
+    /// expired or revoked.
+    pub fn unauthorized(code: &str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, code, message)
+    }
+
+    pub fn bad_request(code: &str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    pub fn not_found(code: &str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, code, message)
+    }
+
+    pub fn internal(code: &str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, code, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
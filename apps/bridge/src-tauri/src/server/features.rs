@@ -0,0 +1,23 @@
+use crate::config::{self, AppConfig};
+use crate::server::autostart;
+use std::sync::Mutex;
+
+/// Turn a feature on or off by name (see `FeaturesConfig::flag_mut`) and save
+/// the config, as the dashboard toggle does.
+pub fn set_feature(config: &Mutex<AppConfig>, feature: &str, enabled: bool) -> Result<(), String> {
+    let app = config::app()?;
+    let mut config = config.lock().unwrap();
+    let flag = config
+        .features
+        .flag_mut(feature)
+        .ok_or_else(|| format!("Unknown feature \"{}\"", feature))?;
+    if *flag == enabled {
+        return Ok(());
+    }
+    if feature == "autostart" {
+        autostart::apply(app, enabled)?;
+    }
+    *flag = enabled;
+    config::save_config(app, &config);
+    Ok(())
+}
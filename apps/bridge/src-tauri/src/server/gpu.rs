@@ -1,11 +1,17 @@
+use std::io::Read;
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
-use std::process::Command;
-use std::time::Instant;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
 
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// Longest a GPU or disk tool may run. A stalled `nvidia-smi` or
+/// `system_profiler` would otherwise hold up the stats loop and `/api/usage`
+/// with it.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Clone, Debug)]
 pub struct GpuData {
     pub vendor: String,
@@ -37,52 +43,28 @@ pub fn get_gpu_stats() -> Option<GpuData> {
 fn get_macos_gpu_stats() -> Option<GpuData> {
     use serde_json::Value;
 
-    let output = Command::new("system_profiler")
-        .args(["SPDisplaysDataType", "-json"])
-        .output()
-        .ok()?;
+    let output = run(Command::new("system_profiler").args(["SPDisplaysDataType", "-json"])).ok()?;
 
-    let json: Value = serde_json::from_slice(&output.stdout).ok()?;
+    let json: Value = serde_json::from_slice(&output).ok()?;
     let display_data = json.get("SPDisplaysDataType")?.as_array()?;
 
     for gpu in display_data {
-        let mut name = gpu
+        let raw_name = gpu
             .get("_name")
             .and_then(|v| v.as_str())
-            .unwrap_or("Unknown GPU")
-            .to_string();
-
-        // CLEANUP BRAND: "kHW_AppleM1Item" -> "Apple M1"
-        if name.starts_with("kHW_") && name.ends_with("Item") {
-            name = name
-                .trim_start_matches("kHW_")
-                .trim_end_matches("Item")
-                .to_string();
-            // Handle "AppleM1" -> "Apple M1"
-            if name.starts_with("AppleM") {
-                name = name.replace("AppleM", "Apple M");
-            }
-        }
+            .unwrap_or("Unknown GPU");
 
         // Skip items that are clearly not GPUs if possible
-        if name.contains("Display Connector") {
+        if raw_name.contains("Display Connector") {
             continue;
         }
 
-        let mut vendor = gpu
-            .get("spdisplays_vendor")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown")
-            .to_string();
-
-        // CLEANUP VENDOR: "sppci_vendor_Apple" -> "Apple"
-        if vendor.to_lowercase().contains("apple") {
-            vendor = "Apple".to_string();
-        } else if vendor.to_lowercase().contains("intel") {
-            vendor = "Intel".to_string();
-        } else if vendor.to_lowercase().contains("amd") {
-            vendor = "AMD".to_string();
-        }
+        let vendor = normalize_vendor(
+            gpu.get("spdisplays_vendor")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown"),
+        );
+        let name = normalize_gpu_name(&vendor, raw_name);
 
         let mut vram_mb = 0;
         let vram_keys = [
@@ -113,13 +95,10 @@ fn get_macos_gpu_stats() -> Option<GpuData> {
         let mut vram_used_mb = -1;
         let temp_c = -1.0;
 
-        let ioreg_output = Command::new("ioreg")
-            .args(["-rw0", "-c", "IOAccelerator"])
-            .output()
-            .ok();
+        let ioreg_output = run(Command::new("ioreg").args(["-rw0", "-c", "IOAccelerator"]));
 
-        if let Some(out) = ioreg_output {
-            let s = String::from_utf8_lossy(&out.stdout);
+        if let Ok(out) = ioreg_output {
+            let s = String::from_utf8_lossy(&out);
             // Search for "Device Utilization" = 5
             if let Some(idx) = s.find("\"Device Utilization\"=") {
                 let start = idx + "\"Device Utilization\"=".len();
@@ -173,13 +152,9 @@ fn get_nvidia_stats() -> Option<GpuData> {
     #[cfg(target_os = "windows")]
     cmd.creation_flags(CREATE_NO_WINDOW);
 
-    let output = cmd.output().ok()?;
-
-    if !output.status.success() {
-        return None;
-    }
+    let output = run(&mut cmd).ok()?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = String::from_utf8_lossy(&output);
     let line = stdout.trim().lines().next()?;
     let parts: Vec<&str> = line.split(", ").collect();
 
@@ -195,7 +170,7 @@ fn get_nvidia_stats() -> Option<GpuData> {
 
     Some(GpuData {
         vendor: "NVIDIA".to_string(),
-        model: name,
+        model: normalize_gpu_name("NVIDIA", &name),
         vram_total_mb: total_mem,
         vram_used_mb: used_mem,
         temp_c: temp,
@@ -203,3 +178,199 @@ fn get_nvidia_stats() -> Option<GpuData> {
         last_updated: Instant::now(),
     })
 }
+
+/// Consistent vendor names: "sppci_vendor_Apple" -> "Apple",
+/// "Intel Corporation" -> "Intel", "ATI" -> "AMD".
+pub fn normalize_vendor(raw: &str) -> String {
+    let raw = raw.trim().trim_start_matches("sppci_vendor_");
+    let lower = raw.to_lowercase();
+    let vendor = if lower.contains("nvidia") {
+        "NVIDIA"
+    } else if lower.contains("apple") {
+        "Apple"
+    } else if lower.contains("intel") {
+        "Intel"
+    } else if lower.contains("amd")
+        || lower.contains("advanced micro devices")
+        || lower == "ati"
+        || lower.starts_with("ati ")
+    {
+        "AMD"
+    } else if raw.is_empty() {
+        "Unknown"
+    } else {
+        raw
+    };
+    vendor.to_string()
+}
+
+/// Tidy a GPU name from any backend: drop trademark marks, expand macOS
+/// chip ids and lead with the vendor. "Intel(R) UHD Graphics 620" ->
+/// "Intel UHD Graphics 620", "kHW_AppleM1ProItem" -> "Apple M1 Pro",
+/// "GeForce GTX 1080" -> "NVIDIA GeForce GTX 1080".
+pub fn normalize_gpu_name(vendor: &str, raw: &str) -> String {
+    let mut name = raw.trim().to_string();
+
+    // "kHW_AppleM1ProItem" -> "AppleM1Pro" -> "Apple M1 Pro"
+    if let Some(chip) = name
+        .strip_prefix("kHW_")
+        .and_then(|n| n.strip_suffix("Item"))
+    {
+        let mut spaced = String::with_capacity(chip.len() + 4);
+        for (i, c) in chip.char_indices() {
+            if i > 0 && c.is_ascii_uppercase() {
+                spaced.push(' ');
+            }
+            spaced.push(c);
+        }
+        name = spaced;
+    }
+
+    for mark in ["(R)", "(r)", "(TM)", "(tm)", "®", "™"] {
+        name = name.replace(mark, " ");
+    }
+    let mut name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let known_vendor = matches!(vendor, "NVIDIA" | "AMD" | "Intel" | "Apple");
+    if known_vendor && !name.to_lowercase().starts_with(&vendor.to_lowercase()) {
+        name = format!("{} {}", vendor, name);
+    }
+    name
+}
+
+/// PIDs of processes using the NVIDIA GPU with their VRAM use in MB.
+/// Memory is `None` where the driver reports `[N/A]` (e.g. WDDM on Windows).
+/// Empty when `nvidia-smi` is missing or nothing is running on the GPU.
+pub fn get_nvidia_compute_apps() -> Vec<(u32, Option<u64>)> {
+    let mut cmd = Command::new("nvidia-smi");
+    cmd.args(&[
+        "--query-compute-apps=pid,used_memory",
+        "--format=csv,noheader,nounits",
+    ]);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    let Ok(output) = run(&mut cmd) else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output)
+        .lines()
+        .filter_map(|line| {
+            let (pid, mem) = line.split_once(',')?;
+            Some((pid.trim().parse().ok()?, mem.trim().parse().ok()))
+        })
+        .collect()
+}
+
+/// Stdout of `cmd` if it exits successfully within `COMMAND_TIMEOUT`, or
+/// why it didn't. Killed on timeout.
+pub fn run(cmd: &mut Command) -> Result<Vec<u8>, String> {
+    let (status, output) = run_with_status(cmd)?;
+    if !status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            cmd.get_program().to_string_lossy(),
+            status
+        ));
+    }
+    Ok(output)
+}
+
+/// Like `run`, but returns stdout whatever the exit status, for tools like
+/// `smartctl` whose exit code is more than a success flag.
+pub fn run_with_status(cmd: &mut Command) -> Result<(ExitStatus, Vec<u8>), String> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                format!("{} is not installed", program)
+            } else {
+                format!("{} failed: {}", program, e)
+            }
+        })?;
+
+    // Drain stdout as it comes so a long `system_profiler` report can't fill
+    // the pipe and stall the tool
+    let Some(mut stdout) = child.stdout.take() else {
+        return Err(format!("{} failed: no stdout", program));
+    };
+    let reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + COMMAND_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            result => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(match result {
+                    Ok(_) => {
+                        let message =
+                            format!("{} timed out after {}s", program, COMMAND_TIMEOUT.as_secs());
+                        tracing::warn!("{}", message);
+                        message
+                    }
+                    Err(e) => format!("{} failed: {}", program, e),
+                });
+            }
+        }
+    };
+    let output = reader
+        .join()
+        .map_err(|_| format!("{} failed: reading its output panicked", program))?;
+    Ok((status, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_gpu_names() {
+        let cases = [
+            (
+                "NVIDIA",
+                "NVIDIA GeForce RTX 4070 Laptop GPU",
+                "NVIDIA GeForce RTX 4070 Laptop GPU",
+            ),
+            (
+                "Intel",
+                "Intel(R) UHD Graphics 620",
+                "Intel UHD Graphics 620",
+            ),
+            ("Apple", "kHW_AppleM1ProItem", "Apple M1 Pro"),
+            ("NVIDIA", "GeForce GTX 1080", "NVIDIA GeForce GTX 1080"),
+            ("AMD", "Radeon™ RX 7900 XTX", "AMD Radeon RX 7900 XTX"),
+            ("Unknown", "Some  Virtual GPU", "Some Virtual GPU"),
+        ];
+        for (vendor, raw, expected) in cases {
+            assert_eq!(normalize_gpu_name(vendor, raw), expected, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn normalizes_vendors() {
+        let cases = [
+            ("sppci_vendor_Apple", "Apple"),
+            ("Intel Corporation", "Intel"),
+            ("ATI", "AMD"),
+            ("Advanced Micro Devices, Inc.", "AMD"),
+            ("NVIDIA Corporation", "NVIDIA"),
+            ("", "Unknown"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(normalize_vendor(raw), expected, "{}", raw);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
-    response::{sse::Event, sse::KeepAlive, Sse},
+    http::header,
+    response::{sse::Event, sse::KeepAlive, IntoResponse, Response, Sse},
     Json,
 };
 use futures::stream::Stream;
@@ -11,6 +11,7 @@ use std::convert::Infallible;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 use sysinfo::{Disks, Networks, Pid, System};
+use tracing::{error, info, warn};
 
 #[cfg(target_os = "windows")]
 use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
@@ -19,9 +20,14 @@ use windows::Win32::UI::WindowsAndMessaging::{
     EnumWindows, IsWindowVisible, SetForegroundWindow, ShowWindow, SwitchToThisWindow, SW_RESTORE,
 };
 
-use crate::config::AppConfig;
 use crate::auth_store::AuthState;
+use crate::config::{AppConfig, DisplayConfig, FeaturesConfig};
+use crate::logging;
+use crate::server::error::ApiError;
+use crate::server::humanize::{self, HumanizeParams};
+use crate::server::refresh::Part;
 use crate::server::types::*;
+use crate::server::usage_history;
 
 /// Subscribe to topics - increments ref counts and starts loops if needed
 pub fn subscribe_topics(state: &Arc<AppState>, topics: &[&str]) {
@@ -68,38 +74,123 @@ pub fn unsubscribe_topics(state: &Arc<AppState>, topics: &[&str]) {
     }
 }
 
+/// Shared server state.
+///
+/// Every lock here is a `std::sync::Mutex`, and no guard may be held across
+/// an `.await` (clippy's `await_holding_lock` flags it). sysinfo needs `&mut`
+/// to refresh, so an `RwLock` would only ever be write-locked, and a tokio
+/// lock only pays off when held across awaits. Instead:
+/// - hold a guard just long enough to copy out what's needed, one lock at a
+///   time, so no lock order has to be kept;
+/// - do slow work (sysinfo refreshes, `nvidia-smi`, window enumeration) in
+///   `run_refresh` or `spawn_blocking`, never on an async worker.
 pub struct AppState {
+    /// CPU and memory, refreshed by the stats loop and `/api/usage`.
     pub system: Arc<Mutex<System>>,
+    /// Process table, kept apart from `system` so a slow process scan never
+    /// holds up the stats loop.
+    pub processes: Arc<Mutex<System>>,
     pub networks: Arc<Mutex<Networks>>,
     pub disks: Arc<Mutex<Disks>>,
     pub gpu_cache: Arc<Mutex<Option<crate::server::gpu::GpuData>>>,
+    /// When CPU, memory and disks were last refreshed.
+    pub refresh: crate::server::refresh::RefreshScheduler,
+    /// Moving average of CPU load kept by the stats loop when
+    /// `stats.cpu_smoothing` is on.
+    pub cpu_load_average: Mutex<Option<f64>>,
+    /// Recent stats loop readings for `GET /api/usage/stats`.
+    pub usage_history: Mutex<crate::server::usage_history::UsageHistory>,
+    /// Last `EnumWindows` snapshot and when it was taken.
+    #[cfg(target_os = "windows")]
+    pub window_cache: Mutex<Option<(std::time::Instant, Arc<WindowMap>)>>,
+    /// Last `GET /api/system` body, its ETag and when it was built.
+    pub system_info_cache: Mutex<Option<(std::time::Instant, String, String)>>,
+    /// Opened on first use and kept, since on Linux the text we set is only
+    /// served while the `Clipboard` that set it is alive.
+    pub clipboard: Mutex<Option<arboard::Clipboard>>,
     pub broadcast_tx: tokio::sync::broadcast::Sender<crate::server::types::BroadcastEvent>,
     pub active_topics: Arc<Mutex<std::collections::HashMap<String, usize>>>,
+    /// Subscriptions of current and recently closed WebSocket connections.
+    pub resume_tokens: Mutex<crate::server::resume::ResumeTokens>,
     pub config: Arc<Mutex<AppConfig>>,
     pub auth_state: Arc<Mutex<AuthState>>,
+    /// Per-IP request history, recorded by the auth middleware.
+    pub client_history: Arc<Mutex<crate::server::client_history::ClientHistory>>,
     pub loop_manager: Arc<crate::server::LoopManager>,
+    pub status: tokio::sync::watch::Receiver<ServerStatus>,
+    /// Delayed power actions keyed by id, so they can be listed and cancelled.
+    pub scheduled_power: Arc<Mutex<std::collections::HashMap<u64, ScheduledPower>>>,
+    pub next_schedule_id: std::sync::atomic::AtomicU64,
     /// Notifies all WS connections to close when the server is shutting down.
     pub shutdown: tokio::sync::watch::Receiver<bool>,
 }
 
+/// Lock a sysinfo `System`. If a refresh panicked while holding the lock the
+/// data is at worst stale, so recover it instead of failing every request.
+pub fn lock_system(system: &Mutex<System>) -> std::sync::MutexGuard<'_, System> {
+    system.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Longest a request waits on a sysinfo refresh before answering 503.
+const REFRESH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Run a sysinfo refresh on the blocking pool so it can't stall the async
+/// workers, giving up with 503 `REFRESH_TIMEOUT` after `REFRESH_TIMEOUT`.
+/// A timed-out refresh still finishes in the background.
+pub async fn run_refresh<T: Send + 'static>(
+    refresh: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, ApiError> {
+    match tokio::time::timeout(REFRESH_TIMEOUT, tokio::task::spawn_blocking(refresh)).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => {
+            error!("System refresh failed: {}", e);
+            Err(ApiError::internal(
+                "REFRESH_FAILED",
+                "Reading system information failed",
+            ))
+        }
+        Err(_) => {
+            warn!("System refresh took longer than {:?}", REFRESH_TIMEOUT);
+            Err(ApiError::new(
+                axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                "REFRESH_TIMEOUT",
+                "Reading system information is taking too long, try again shortly",
+            ))
+        }
+    }
+}
+
+/// GPU usage as reported to clients, from possibly cached `data`.
+pub fn gpu_usage(data: &crate::server::gpu::GpuData, display: &DisplayConfig) -> GpuUsage {
+    GpuUsage {
+        current_load: data.load_percent,
+        current_temp: display.temperature(data.temp_c),
+        temp_unit: display.temp_unit.clone(),
+        current_memory: data.vram_used_mb,
+        age_ms: data.last_updated.elapsed().as_millis() as u64,
+    }
+}
+
 pub fn get_or_update_gpu_stats(state: &Arc<AppState>) -> Option<crate::server::gpu::GpuData> {
     let cache_seconds = {
         let config = state.config.lock().unwrap();
         if !config.stats.gpu_enabled {
             return None;
         }
-        config.stats.disk_cache_seconds // Reusing disk_cache for GPU too
+        config.stats.gpu_cache_seconds
     };
 
-    let mut cache = state.gpu_cache.lock().unwrap();
-    if let Some(stats) = &*cache {
+    if let Some(stats) = &*state.gpu_cache.lock().unwrap() {
         if stats.last_updated.elapsed() < std::time::Duration::from_secs(cache_seconds) {
             return Some(stats.clone());
         }
     }
 
-    // Refresh
-    if let Some(stats) = crate::server::gpu::get_gpu_stats() {
+    // Refresh without holding the cache, so `cached_gpu_stats` never waits
+    // on the GPU tools
+    let fresh = crate::server::gpu::get_gpu_stats();
+    let mut cache = state.gpu_cache.lock().unwrap();
+    if let Some(stats) = fresh {
         *cache = Some(stats.clone());
         return Some(stats);
     }
@@ -108,35 +199,324 @@ pub fn get_or_update_gpu_stats(state: &Arc<AppState>) -> Option<crate::server::g
     cache.clone()
 }
 
+/// The last GPU reading, without refreshing it.
+pub fn cached_gpu_stats(state: &AppState) -> Option<crate::server::gpu::GpuData> {
+    if !state.config.lock().unwrap().stats.gpu_enabled {
+        return None;
+    }
+    state.gpu_cache.lock().unwrap().clone()
+}
+
+/// How long a window map snapshot is reused before enumerating again.
+#[cfg(target_os = "windows")]
+const WINDOW_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[cfg(target_os = "windows")]
+fn get_or_update_window_map(state: &Arc<AppState>) -> Arc<WindowMap> {
+    // Holding the lock while enumerating means concurrent requests wait for
+    // one refresh instead of each walking every window.
+    let mut cache = state.window_cache.lock().unwrap();
+    if let Some((taken, map)) = &*cache {
+        if taken.elapsed() < WINDOW_CACHE_TTL {
+            return map.clone();
+        }
+    }
+
+    let map = Arc::new(unsafe { get_window_map() });
+    *cache = Some((std::time::Instant::now(), map.clone()));
+    map
+}
+
+/// API version served under `/api/...` and sent back in `X-API-Version`.
+pub const API_VERSION: u32 = 1;
+
+/// Versions mounted under `/api/v{n}/...`.
+pub const API_VERSIONS: [u32; 1] = [API_VERSION];
+
 pub async fn status_handler() -> Json<Value> {
     Json(json!({
         "status": "ok",
-        "version": env!("CARGO_PKG_VERSION")
+        "version": env!("CARGO_PKG_VERSION"),
+        "api_version": API_VERSION,
+        "api_versions": API_VERSIONS
+    }))
+}
+
+/// Feature flags, without the rest of the config, and whether each
+/// feature-gated route is currently served. Any valid key may read it, so
+/// clients can hide controls the bridge would refuse.
+pub async fn get_features(State(state): State<Arc<AppState>>) -> Json<Value> {
+    let features = state.config.lock().unwrap().features.clone();
+    let endpoints = live_endpoints(&features);
+    Json(json!({
+        "features": features,
+        "endpoints": endpoints
     }))
 }
 
+/// What this host supports, so clients don't have to guess from the
+/// platform. Unlike `/api/features` this ignores the config.
+pub async fn get_capabilities(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Capabilities>, ApiError> {
+    run_refresh(move || build_capabilities(&state))
+        .await
+        .map(Json)
+}
+
+fn build_capabilities(state: &Arc<AppState>) -> Capabilities {
+    let gpu = get_or_update_gpu_stats(state);
+    let desktop = cfg!(any(target_os = "windows", target_os = "macos"));
+    Capabilities {
+        platform: std::env::consts::OS.to_string(),
+        power: PowerCapabilities {
+            shutdown: desktop,
+            restart: desktop,
+            restart_firmware: check_firmware_restart().is_ok(),
+            sleep: desktop,
+            // macOS accepts hibernate but only sleeps
+            hibernate: cfg!(target_os = "windows"),
+        },
+        media: crate::server::media::capabilities(),
+        processes: ProcessCapabilities {
+            kill: true,
+            launch: true,
+            focus: focus_supported(),
+            priority: true,
+            suspend: true,
+        },
+        gpu: GpuCapabilities {
+            per_process: gpu.as_ref().is_some_and(|g| g.vendor == "NVIDIA"),
+            vendor: gpu.as_ref().map(|g| g.vendor.clone()),
+            model: gpu.map(|g| g.model),
+        },
+    }
+}
+
+/// `"METHOD /path"` of each route a feature flag can turn off, and whether
+/// `features` allows it. Must follow the checks in the handlers.
+fn live_endpoints(f: &FeaturesConfig) -> std::collections::BTreeMap<&'static str, bool> {
+    let kill = f.enable_processes && f.enable_process_kill;
+    let launch = f.enable_processes && f.enable_process_launch;
+    let priority = f.enable_processes && f.enable_process_priority;
+    let suspend = f.enable_processes && f.enable_process_suspend;
+    std::collections::BTreeMap::from([
+        ("GET /api/system", f.enable_system),
+        ("GET /api/disks/health", f.enable_system),
+        ("GET /api/disks/layout", f.enable_system),
+        ("GET /api/usage", f.enable_usage),
+        ("GET /api/usage/stats", f.enable_usage),
+        ("POST /api/system/refresh", f.enable_usage),
+        ("GET /api/stream", f.enable_stream),
+        ("GET /api/processes", f.enable_processes),
+        ("GET /api/processes/gpu", f.enable_processes),
+        ("GET /api/processes/:name", f.enable_processes),
+        ("GET /api/process/:pid", f.enable_processes),
+        ("POST /api/processes/focus", f.enable_processes),
+        ("POST /api/processes/kill", kill),
+        ("POST /api/processes/launch", launch),
+        ("POST /api/processes/priority", priority),
+        ("POST /api/processes/suspend", suspend),
+        ("POST /api/processes/resume", suspend),
+        ("GET /api/commands", f.enable_commands),
+        ("POST /api/commands/:id/run", f.enable_commands),
+        ("POST /api/open", f.enable_open),
+        ("GET /api/clipboard", f.enable_clipboard),
+        ("POST /api/clipboard", f.enable_clipboard),
+        ("GET /api/screenshot", f.enable_screenshot),
+        ("GET /api/displays", f.enable_display),
+        ("POST /api/displays/:id/brightness", f.enable_display),
+        ("POST /api/input", f.enable_input),
+        ("GET /api/autostart", f.enable_autostart_api),
+        ("POST /api/autostart", f.enable_autostart_api),
+        ("POST /api/pw/shutdown", f.enable_shutdown),
+        ("POST /api/pw/restart", f.enable_restart),
+        ("POST /api/pw/restart_firmware", f.enable_restart),
+        ("POST /api/pw/sleep", f.enable_sleep),
+        ("POST /api/pw/hibernate", f.enable_hibernate),
+        ("GET /api/media/status", f.enable_media),
+        ("POST /api/media/control", f.enable_media),
+        ("GET /api/media/devices", f.enable_media),
+        ("GET /api/media/capabilities", f.enable_media),
+        ("POST /api/media/device", f.enable_media),
+    ])
+}
+
 pub async fn get_client_count(State(state): State<Arc<AppState>>) -> Json<Value> {
     Json(json!({
         "count": state.broadcast_tx.receiver_count()
     }))
 }
 
+/// Client IPs seen recently, most recent first.
+pub async fn get_client_history(State(state): State<Arc<AppState>>) -> Json<Vec<ClientSeen>> {
+    Json(state.client_history.lock().unwrap().list())
+}
+
+/// Introspect topic ref counts and which monitoring loops are alive.
+pub async fn get_debug_topics(State(state): State<Arc<AppState>>) -> Json<Value> {
+    let topics = state.active_topics.lock().unwrap().clone();
+    let (stats, media, processes) = state.loop_manager.running_loops();
+    Json(json!({
+        "topics": topics,
+        "loops": {
+            "stats": stats,
+            "media": media,
+            "processes": processes
+        }
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct LogsParams {
+    /// Only lines at this level or more severe.
+    pub level: Option<String>,
+    /// Newest N lines (default 100).
+    pub limit: Option<usize>,
+}
+
+const DEFAULT_LOG_LIMIT: usize = 100;
+
+/// Recent log lines from the in-memory buffer, oldest first.
+pub async fn get_logs(Query(params): Query<LogsParams>) -> Result<Json<Vec<LogEntry>>, ApiError> {
+    if let Some(level) = &params.level {
+        if !logging::LOG_LEVELS.contains(&level.as_str()) {
+            return Err(ApiError::bad_request(
+                "INVALID_LEVEL",
+                format!(
+                    "level must be one of {} (got \"{}\")",
+                    logging::LOG_LEVELS.join(", "),
+                    level
+                ),
+            ));
+        }
+    }
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LOG_LIMIT)
+        .min(logging::RECENT_CAPACITY);
+    Ok(Json(logging::recent_logs(params.level.as_deref(), limit)))
+}
+
+/// How long `GET /api/system` reuses the last `SystemInfo` before rebuilding it.
+const SYSTEM_INFO_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Serves an `ETag` and answers a matching `If-None-Match` with 304, so
+/// polling clients only download the hardware info when it changes.
 pub async fn get_system_info(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<SystemInfo>, (StatusCode, Json<Value>)> {
-    if !state.config.lock().unwrap().features.enable_system {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "System info disabled"})),
-        ));
+    Query(params): Query<HumanizeParams>,
+    headers: axum::http::HeaderMap,
+) -> Result<Response, ApiError> {
+    let binary_units = {
+        let config = state.config.lock().unwrap();
+        if !config.features.enable_system {
+            return Err(ApiError::feature_disabled("System info disabled"));
+        }
+        config.display.binary_units
+    };
+
+    let (mut etag, mut body) = {
+        let state = state.clone();
+        run_refresh(move || cached_system_info(&state)).await??
+    };
+    if params.humanize {
+        let mut value: Value = serde_json::from_str(&body)
+            .map_err(|e| ApiError::internal("SERIALIZE_FAILED", e.to_string()))?;
+        humanize::add_human_fields(
+            &mut value,
+            humanize::SYSTEM_MEMORY_FIELDS,
+            humanize::SYSTEM_DISK_FIELDS,
+            binary_units,
+        );
+        body = value.to_string();
+        // Each variant gets its own tag so a cached plain body is never
+        // revalidated for a humanized request, or across unit bases
+        let suffix = if binary_units { "-hb" } else { "-hd" };
+        etag = format!("{}{}\"", etag.trim_end_matches('"'), suffix);
+    }
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| etag_matches(v, &etag));
+    if not_modified {
+        return Ok((axum::http::StatusCode::NOT_MODIFIED, cache_headers).into_response());
     }
+    Ok((
+        cache_headers,
+        [(header::CONTENT_TYPE, "application/json")],
+        body,
+    )
+        .into_response())
+}
+
+/// `(etag, json)` for the current `SystemInfo`, rebuilt at most every
+/// `SYSTEM_INFO_CACHE_TTL`.
+fn cached_system_info(state: &Arc<AppState>) -> Result<(String, String), ApiError> {
+    if let Some((built, etag, body)) = &*state.system_info_cache.lock().unwrap() {
+        if built.elapsed() < SYSTEM_INFO_CACHE_TTL {
+            return Ok((etag.clone(), body.clone()));
+        }
+    }
+
+    let body = serde_json::to_string(&build_system_info(state))
+        .map_err(|e| ApiError::internal("SERIALIZE_FAILED", e.to_string()))?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::hash::Hash::hash(&body, &mut hasher);
+    // Weak, since the compression layer may re-encode the body
+    let etag = format!("W/\"{:016x}\"", std::hash::Hasher::finish(&hasher));
+
+    *state.system_info_cache.lock().unwrap() =
+        Some((std::time::Instant::now(), etag.clone(), body.clone()));
+    Ok((etag, body))
+}
 
-    let mut sys = state.system.lock().unwrap();
-    sys.refresh_all();
+/// Weak comparison of an `If-None-Match` list against our ETag.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+/// Static system info, shared by `GET /api/system` and the WS `get_system_info` op.
+/// Never refreshes the shared `System`: brands, core counts and total memory
+/// were read when it was created and don't change.
+pub fn build_system_info(state: &Arc<AppState>) -> SystemInfo {
+    let (hostname, stats) = {
+        let config = state.config.lock().unwrap();
+        (config.display.host_name(), config.stats.clone())
+    };
+    let (total_memory, brand, mut vendor, cores, physical_cores) = {
+        let sys = lock_system(&state.system);
+        let global = sys.global_cpu_info();
+        let first = sys.cpus().first();
+        let brand = match global.brand() {
+            "" | "Unknown" => first.map_or("", |c| c.brand()),
+            brand => brand,
+        };
+        let vendor = match global.vendor_id() {
+            "" | "Unknown" => first.map_or("", |c| c.vendor_id()),
+            vendor => vendor,
+        };
+        (
+            sys.total_memory(),
+            brand.to_string(),
+            vendor.to_string(),
+            sys.cpus().len(),
+            sys.physical_core_count().unwrap_or(0),
+        )
+    };
 
+    let modules = crate::server::hardware::get_memory_modules();
     let memory = MemoryInfo {
-        total: sys.total_memory(),
-        slots: crate::server::hardware::get_memory_slots(),
+        total: total_memory,
+        slots: crate::server::hardware::memory_slots(&modules),
+        modules,
     };
 
     let os_info = OsInfo {
@@ -146,35 +526,26 @@ pub async fn get_system_info(
         arch: std::env::consts::ARCH.to_string(),
     };
 
-    let global = sys.global_cpu_info();
-    let mut brand = global.brand().to_string();
-    let mut vendor = global.vendor_id().to_string();
-
-    if (brand.is_empty() || brand == "Unknown") && !sys.cpus().is_empty() {
-        brand = sys.cpus()[0].brand().to_string();
-    }
-    if (vendor.is_empty() || vendor == "Unknown") && !sys.cpus().is_empty() {
-        vendor = sys.cpus()[0].vendor_id().to_string();
-    }
-
     if vendor == "GenuineIntel" {
         vendor = "Intel".to_string();
     } else if vendor == "AuthenticAMD" {
         vendor = "AMD".to_string();
     }
 
+    let speeds = crate::server::hardware::get_cpu_speeds();
     let cpu_info = CpuInfo {
         manufacturer: vendor,
         brand: brand,
-        cores: sys.cpus().len(),
-        physical_cores: sys.physical_core_count().unwrap_or(0),
-        base_speed: crate::server::hardware::get_cpu_speed_ghz(),
+        cores,
+        physical_cores,
+        base_speed: speeds.base,
+        max_speed: speeds.max,
+        speed_approximate: speeds.approximate,
     };
 
     // Network
     let mut net_info = None;
-    let networks = state.networks.lock().unwrap();
-    for (name, data) in networks.iter() {
+    for (name, data) in state.networks.lock().unwrap().iter() {
         // Simple heuristic for primary interface
         if !name.contains("Loopback") && !name.contains("vEthernet") {
             let mut ipv4 = String::new();
@@ -193,98 +564,248 @@ pub async fn get_system_info(
         }
     }
 
-    let disks_lock = state.disks.lock().unwrap();
-    let disks = disks_lock
-        .iter()
+    let disks = crate::server::hardware::listed_disks(&state.disks.lock().unwrap(), &stats)
+        .into_iter()
         .map(|d| DiskInfo {
-            fs: d
-                .mount_point()
-                .to_string_lossy()
-                .trim_end_matches('\\')
-                .into(),
+            fs: crate::server::hardware::disk_mount(d),
             disk_type: d.file_system().to_string_lossy().into(),
             size: d.total_space(),
             mount: d.mount_point().to_string_lossy().into(),
         })
         .collect();
 
-    let gpu_data = get_or_update_gpu_stats(&state);
+    let gpu_data = get_or_update_gpu_stats(state);
     let gpu = gpu_data.map(|g| GpuInfo {
         manufacturer: g.vendor,
         brand: g.model,
         memory_total: g.vram_total_mb,
     });
 
-    Ok(Json(SystemInfo {
-        hostname: System::host_name().unwrap_or("Unknown".into()),
+    SystemInfo {
+        hostname: hostname.unwrap_or("Unknown".into()),
         platform: std::env::consts::OS.to_string(),
+        boot_time: System::boot_time(),
         os: os_info,
         cpu: cpu_info,
         gpu,
         memory,
         disks,
         network: net_info,
-    }))
+    }
+}
+
+/// SMART health per physical drive. Drives that can't be queried are reported
+/// as unavailable rather than failing the request. smartctl runs once per
+/// drive, so this takes the blocking pool rather than `run_refresh` and its
+/// single-refresh timeout.
+pub async fn get_disk_health(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DiskHealth>>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_system {
+        return Err(ApiError::feature_disabled("System info disabled"));
+    }
+
+    let fallback_devices: Vec<String> = state
+        .disks
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|d| d.name().to_string_lossy().into())
+        .collect();
+
+    let display = state.config.lock().unwrap().display.clone();
+    let mut health = tokio::task::spawn_blocking(move || {
+        crate::server::smart::get_disk_health(&fallback_devices)
+    })
+    .await
+    .map_err(|e| ApiError::internal("DISK_HEALTH_FAILED", e.to_string()))?;
+    for drive in &mut health {
+        drive.temperature = drive.temperature.map(|c| display.temperature(c));
+        drive.temp_unit = display.temp_unit.clone();
+    }
+    Ok(Json(health))
+}
+
+pub async fn get_disk_layout(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<DiskLayout>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_system {
+        return Err(ApiError::feature_disabled("System info disabled"));
+    }
+
+    // PowerShell alone takes around a second to start
+    tokio::task::spawn_blocking(crate::server::disk_layout::get_disk_layout)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::internal("DISK_LAYOUT_FAILED", e.to_string()))
 }
 
 pub async fn get_system_usage(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<SystemUsage>, (StatusCode, Json<Value>)> {
+    Query(params): Query<HumanizeParams>,
+) -> Result<Json<Value>, ApiError> {
+    let binary_units = {
+        let config = state.config.lock().unwrap();
+        if !config.features.enable_usage {
+            return Err(ApiError::feature_disabled("Usage data disabled"));
+        }
+        config.display.binary_units
+    };
+
+    let usage = run_refresh(move || collect_usage(&state)).await?;
+    let mut value = serde_json::to_value(usage)
+        .map_err(|e| ApiError::internal("SERIALIZE_FAILED", e.to_string()))?;
+    if params.humanize {
+        humanize::add_human_fields(
+            &mut value,
+            humanize::USAGE_MEMORY_FIELDS,
+            humanize::USAGE_DISK_FIELDS,
+            binary_units,
+        );
+    }
+    Ok(Json(value))
+}
+
+/// Default `window_secs` for `GET /api/usage/stats`.
+const DEFAULT_USAGE_WINDOW_SECS: u64 = 60;
+
+#[derive(Deserialize)]
+pub struct UsageStatsParams {
+    pub window_secs: Option<u64>,
+}
+
+/// Min/max/avg of CPU, memory and GPU load over the last `window_secs`,
+/// from what the stats loop sampled. Empty while no one subscribes to stats.
+pub async fn get_usage_stats(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<UsageStatsParams>,
+) -> Result<Json<UsageSummary>, ApiError> {
     if !state.config.lock().unwrap().features.enable_usage {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Usage data disabled"})),
+        return Err(ApiError::feature_disabled("Usage data disabled"));
+    }
+    let max = usage_history::MAX_WINDOW.as_secs();
+    let window = params.window_secs.unwrap_or(DEFAULT_USAGE_WINDOW_SECS);
+    if !(1..=max).contains(&window) {
+        return Err(ApiError::bad_request(
+            "INVALID_WINDOW",
+            format!("window_secs must be between 1 and {} (got {})", max, window),
         ));
     }
+    let summary = state
+        .usage_history
+        .lock()
+        .unwrap()
+        .summary(std::time::Duration::from_secs(window));
+    Ok(Json(summary))
+}
 
-    let mut sys = state.system.lock().unwrap();
-    sys.refresh_cpu();
-    sys.refresh_memory();
-
-    let mut disks_lock = state.disks.lock().unwrap();
-    disks_lock.refresh_list();
+/// sysinfo reports CPU load averaged since the previous refresh. When that was
+/// longer ago than this, `/api/usage` takes two samples a moment apart
+/// instead, so a one-off call after an idle stretch gets the current load.
+const CPU_SAMPLE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(2);
 
-    let cpu = CpuUsage {
-        current_load: sys.global_cpu_info().cpu_usage() as f64,
-        current_temp: 0.0,
-        current_speed: crate::server::hardware::get_cpu_speed_ghz(),
+fn collect_usage(state: &Arc<AppState>) -> SystemUsage {
+    let (display, stats) = {
+        let config = state.config.lock().unwrap();
+        (config.display.clone(), config.stats.clone())
     };
+    let min_interval = stats.refresh_min_interval();
+    let (cpu, memory) = {
+        let mut sys = lock_system(&state.system);
+        // A running stats loop keeps the last refresh recent, so this only
+        // waits when nothing else is sampling the CPU
+        let previous = state.refresh.last_refresh(Part::Cpu);
+        if state.refresh.due(Part::Cpu, min_interval) {
+            sys.refresh_cpu();
+            if previous.map_or(true, |at| at.elapsed() > CPU_SAMPLE_MAX_AGE) {
+                // Wait unlocked so the stats loop and other callers aren't
+                // held up, then take the second sample
+                drop(sys);
+                std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+                sys = lock_system(&state.system);
+                sys.refresh_cpu();
+            }
+        }
+        if state.refresh.due(Part::Memory, min_interval) {
+            sys.refresh_memory();
+        }
+        let cpu = CpuUsage {
+            current_load: sys.global_cpu_info().cpu_usage() as f64,
+            current_temp: 0.0,
+            temp_unit: display.temp_unit.clone(),
+            current_speed: crate::server::hardware::get_current_cpu_speed_ghz(&sys),
+            load_avg: crate::server::hardware::get_load_average(),
+        };
 
-    let memory = MemoryUsage {
-        used: sys.used_memory(),
-        free: sys.free_memory(),
-        used_percent: (sys.used_memory() as f64 / sys.total_memory() as f64) * 100.0,
+        // refresh_memory() above covers swap as well
+        (cpu, crate::server::hardware::get_memory_usage(&sys))
     };
 
-    let disks = disks_lock
-        .iter()
-        .map(|d| DiskUsage {
-            fs: d
-                .mount_point()
-                .to_string_lossy()
-                .trim_end_matches('\\')
-                .into(),
-            used: d.total_space() - d.available_space(),
-            available: d.available_space(),
-            used_percent: ((d.total_space() - d.available_space()) as f64 / d.total_space() as f64)
-                * 100.0,
-        })
-        .collect();
+    let disks = {
+        let mut disks_lock = state.disks.lock().unwrap();
+        if state.refresh.due(Part::Disks, min_interval) {
+            disks_lock.refresh_list();
+        }
+        crate::server::hardware::listed_disks(&disks_lock, &stats)
+            .into_iter()
+            .map(crate::server::hardware::get_disk_usage)
+            .collect()
+    };
 
-    let gpu_data = get_or_update_gpu_stats(&state);
-    let gpu = gpu_data.map(|g| GpuUsage {
-        current_load: g.load_percent,
-        current_temp: g.temp_c,
-        current_memory: g.vram_used_mb,
-    });
+    let gpu_data = get_or_update_gpu_stats(state);
+    let gpu = gpu_data.map(|g| gpu_usage(&g, &display));
 
-    Ok(Json(SystemUsage {
+    SystemUsage {
         uptime: System::uptime(),
         cpu,
         memory,
         gpu,
         disks,
-    }))
+        fans: if stats.sensors_enabled {
+            crate::server::hardware::get_fan_speeds()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RefreshParams {
+    pub target: Option<String>,
+}
+
+/// Drop a cached reading and fetch it again, e.g. after plugging in an eGPU.
+pub async fn refresh_cache(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RefreshParams>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_usage {
+        return Err(ApiError::feature_disabled("Usage data disabled"));
+    }
+
+    match params.target.as_deref() {
+        Some("gpu") => {
+            *state.gpu_cache.lock().unwrap() = None;
+            *state.system_info_cache.lock().unwrap() = None;
+            let display = state.config.lock().unwrap().display.clone();
+            let gpu = run_refresh(move || get_or_update_gpu_stats(&state)).await?;
+            let info = gpu.as_ref().map(|g| GpuInfo {
+                manufacturer: g.vendor.clone(),
+                brand: g.model.clone(),
+                memory_total: g.vram_total_mb,
+            });
+            let usage = gpu.map(|g| gpu_usage(&g, &display));
+            Ok(Json(json!({"target": "gpu", "info": info, "usage": usage})))
+        }
+        Some(other) => Err(ApiError::bad_request(
+            "UNSUPPORTED_TARGET",
+            format!("Unsupported refresh target: {}", other),
+        )),
+        None => Err(ApiError::bad_request(
+            "MISSING_VALUE",
+            "target query parameter required",
+        )),
+    }
 }
 
 #[derive(Deserialize)]
@@ -292,17 +813,25 @@ pub struct StreamParams {
     pub fields: Option<String>,
 }
 
+/// `Some(max_clients)` when that many WebSocket/SSE clients are already
+/// connected. Each one holds a receiver on the broadcast channel.
+pub fn client_limit_reached(state: &AppState) -> Option<usize> {
+    let max = state.config.lock().unwrap().server.max_clients;
+    (state.broadcast_tx.receiver_count() >= max).then_some(max)
+}
+
 pub async fn handle_stream(
     State(state): State<Arc<AppState>>,
     Query(params): Query<StreamParams>,
-) -> Result<
-    Sse<Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>>,
-    (StatusCode, Json<Value>),
-> {
+) -> Result<Sse<Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>>, ApiError> {
     if !state.config.lock().unwrap().features.enable_stream {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Stream disabled"})),
+        return Err(ApiError::feature_disabled("Stream disabled"));
+    }
+    if let Some(max) = client_limit_reached(&state) {
+        return Err(ApiError::new(
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            "TOO_MANY_CLIENTS",
+            format!("Too many clients connected (max {})", max),
         ));
     }
 
@@ -344,13 +873,19 @@ pub async fn handle_stream(
                         BroadcastEvent::MediaUpdate(_) |
                         BroadcastEvent::ProcessList(_) |
                         BroadcastEvent::MediaFeedback(_) |
-                        BroadcastEvent::ProcessFeedback(_) => {
+                        BroadcastEvent::ProcessFeedback(_) |
+                        BroadcastEvent::ServerStatus(_) |
+                        BroadcastEvent::SystemInfo(_) |
+                        BroadcastEvent::ProcessOutput(_) |
+                        BroadcastEvent::ProcessExit(_) => {
                             continue;
                         }
                     }
                 }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
-                    continue;
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    yield Ok::<Event, Infallible>(
+                        Event::default().event("lagged").json_data(json!({ "skipped": skipped })).unwrap()
+                    );
                 }
                 Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                     break;
@@ -367,43 +902,109 @@ pub async fn handle_stream(
 
 pub async fn list_processes(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Vec<ProcessInfo>>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Vec<ProcessInfo>>, ApiError> {
     if !state.config.lock().unwrap().features.enable_processes {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Process control disabled"})),
-        ));
+        return Err(ApiError::feature_disabled("Process control disabled"));
     }
-    Ok(Json(crate::server::process::get_processes_list(&state)))
+    let processes = run_refresh(move || crate::server::process::get_processes_list(&state)).await?;
+    Ok(Json(processes))
+}
+
+/// Processes using the GPU (NVIDIA only). Empty on other GPUs.
+pub async fn list_gpu_processes(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<GpuProcessInfo>>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_processes {
+        return Err(ApiError::feature_disabled("Process control disabled"));
+    }
+
+    let result = run_refresh(move || {
+        let apps = crate::server::gpu::get_nvidia_compute_apps();
+        if apps.is_empty() {
+            return Vec::new();
+        }
+
+        let mut sys = lock_system(&state.processes);
+        sys.refresh_processes();
+
+        // Skip PIDs that exited between the two reads
+        let mut result: Vec<GpuProcessInfo> = apps
+            .into_iter()
+            .filter_map(|(pid, gpu_memory_mb)| {
+                let proc = sys.process(Pid::from(pid as usize))?;
+                Some(GpuProcessInfo {
+                    pid,
+                    name: proc.name().to_string(),
+                    gpu_memory_mb,
+                })
+            })
+            .collect();
+        result.sort_by(|a, b| b.gpu_memory_mb.cmp(&a.gpu_memory_mb));
+        result
+    })
+    .await?;
+
+    Ok(Json(result))
 }
 
 pub async fn get_process_details(
     State(state): State<Arc<AppState>>,
     axum::extract::Path(name): axum::extract::Path<String>,
-) -> Result<Json<Vec<ProcessDetail>>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Vec<ProcessDetail>>, ApiError> {
     if !state.config.lock().unwrap().features.enable_processes {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Process control disabled"})),
-        ));
+        return Err(ApiError::feature_disabled("Process control disabled"));
+    }
+
+    let name_lower = name.to_lowercase();
+    let details = run_refresh(move || {
+        find_processes(&state, |_, proc| {
+            proc.name().to_lowercase().contains(&name_lower)
+        })
+    })
+    .await?;
+    Ok(Json(details))
+}
+
+/// The one process with this PID, unlike `/api/processes/:name` which
+/// matches by substring.
+pub async fn get_process_by_pid(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(pid): axum::extract::Path<u32>,
+) -> Result<Json<ProcessDetail>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_processes {
+        return Err(ApiError::feature_disabled("Process control disabled"));
     }
 
-    let mut sys = state.system.lock().unwrap();
+    run_refresh(move || find_processes(&state, |pid_val, _| pid_val == pid))
+        .await?
+        .pop()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("PROCESS_NOT_FOUND", "Process not found"))
+}
+
+/// Processes for which `matches(pid, process)` holds, with their window
+/// titles.
+fn find_processes(
+    state: &Arc<AppState>,
+    matches: impl Fn(u32, &sysinfo::Process) -> bool,
+) -> Vec<ProcessDetail> {
+    let mut sys = lock_system(&state.processes);
     sys.refresh_processes();
 
+    let users = sysinfo::Users::new_with_refreshed_list();
     let mut result = Vec::new();
-    let name_lower = name.to_lowercase();
 
     #[cfg(target_os = "windows")]
-    let window_map = unsafe { get_window_map() };
+    let window_map = get_or_update_window_map(state);
 
     #[cfg(target_os = "macos")]
     let window_map = crate::server::process::get_macos_window_map();
+    #[cfg(target_os = "macos")]
+    let window_titles = crate::server::process::get_macos_window_titles();
 
     for (pid, proc) in sys.processes() {
-        if proc.name().to_lowercase().contains(&name_lower) {
-            let pid_val = pid.to_string().parse::<u32>().unwrap_or(0);
-
+        let pid_val = pid.to_string().parse::<u32>().unwrap_or(0);
+        if matches(pid_val, proc) {
             #[cfg(target_os = "windows")]
             let (title, has_window) = window_map
                 .get(&pid_val)
@@ -411,7 +1012,10 @@ pub async fn get_process_details(
                 .unwrap_or((None, false));
 
             #[cfg(target_os = "macos")]
-            let (title, has_window) = (None, window_map.contains(&pid_val));
+            let (title, has_window) = (
+                window_titles.get(&pid_val).cloned(),
+                window_map.contains(&pid_val),
+            );
 
             #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
             let (title, has_window) = (None, false);
@@ -423,22 +1027,49 @@ pub async fn get_process_details(
                 cpu: proc.cpu_usage() as f64,
                 title,
                 has_window,
+                start_time: proc.start_time(),
+                user: proc
+                    .user_id()
+                    .and_then(|uid| users.get_user_by_id(uid))
+                    .map(|u| u.name().to_string()),
             });
         }
     }
 
-    Ok(Json(result))
+    result
+}
+
+#[derive(Deserialize)]
+pub struct DryRunParams {
+    /// Validate the request but skip the side effect.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Whether a destructive action should stop after validation, because the
+/// request asked for a dry run or `safe_mode` is on.
+fn is_dry_run(state: &AppState, requested: bool) -> bool {
+    requested || state.config.lock().unwrap().safe_mode
+}
+
+fn dry_run_response(would: &str) -> Json<Value> {
+    Json(json!({"status": "dry_run", "would": would}))
 }
 
 pub async fn launch_process(
     State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
     Json(payload): Json<LaunchRequest>,
-) -> (StatusCode, Json<Value>) {
-    if !state.config.lock().unwrap().features.enable_processes {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Process control disabled"})),
-        );
+) -> Result<Json<Value>, ApiError> {
+    let features = state.config.lock().unwrap().features.clone();
+    if !features.enable_processes {
+        return Err(ApiError::feature_disabled("Process control disabled"));
+    }
+    if !features.enable_process_launch {
+        return Err(ApiError::feature_disabled("Launching processes disabled"));
+    }
+    if is_dry_run(&state, params.dry_run) {
+        return Ok(dry_run_response("launch"));
     }
 
     let mut cmd = std::process::Command::new(&payload.path);
@@ -447,88 +1078,565 @@ pub async fn launch_process(
     }
 
     match cmd.spawn() {
-        Ok(_) => (StatusCode::OK, Json(json!({"status": "success"}))),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": format!("Failed to launch: {}", e)})),
-        ),
+        Ok(_) => Ok(Json(json!({"status": "success"}))),
+        Err(e) => Err(ApiError::internal(
+            "LAUNCH_FAILED",
+            format!("Failed to launch: {}", e),
+        )),
     }
 }
 
-pub async fn kill_process(
+pub async fn list_commands(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<KillRequest>,
-) -> (StatusCode, Json<Value>) {
-    if !state.config.lock().unwrap().features.enable_processes {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Process control disabled"})),
-        );
+) -> Result<Json<Vec<CommandInfo>>, ApiError> {
+    let config = state.config.lock().unwrap();
+    if !config.features.enable_commands {
+        return Err(ApiError::feature_disabled("Commands disabled"));
     }
+    Ok(Json(
+        config
+            .commands
+            .iter()
+            .map(|c| CommandInfo {
+                id: c.id.clone(),
+                label: c.label.clone(),
+            })
+            .collect(),
+    ))
+}
 
-    let mut sys = state.system.lock().unwrap();
-    sys.refresh_processes();
+/// Run a command registered in config. Only the id comes from the request.
+pub async fn run_command(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let command = {
+        let config = state.config.lock().unwrap();
+        if !config.features.enable_commands {
+            return Err(ApiError::feature_disabled("Commands disabled"));
+        }
+        config.commands.iter().find(|c| c.id == id).cloned()
+    };
+    let Some(command) = command else {
+        return Err(ApiError::not_found(
+            "COMMAND_NOT_FOUND",
+            format!("No command registered with id \"{}\"", id),
+        ));
+    };
 
-    let mut killed = false;
-    let mut count = 0;
+    match std::process::Command::new(&command.program)
+        .args(&command.args)
+        .spawn()
+    {
+        Ok(_) => Ok(Json(json!({"status": "success"}))),
+        Err(e) => Err(ApiError::internal(
+            "LAUNCH_FAILED",
+            format!("Failed to run {}: {}", command.label, e),
+        )),
+    }
+}
 
-    if let Some(pid) = payload.pid {
-        if let Some(process) = sys.process(Pid::from(pid as usize)) {
-            if process.kill() {
-                killed = true;
-                count = 1;
-            }
-        }
-    } else if let Some(name) = payload.name {
-        for process in sys.processes().values() {
-            if process.name() == name {
-                if process.kill() {
-                    killed = true;
-                    count += 1;
-                }
-            }
-        }
+/// Open an http(s) URL in the default browser. Other schemes (`file://`,
+/// app protocols) are rejected since they can launch arbitrary programs.
+pub async fn open_url(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<OpenUrlRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_open {
+        return Err(ApiError::feature_disabled("Opening URLs disabled"));
     }
 
-    if killed || count > 0 {
-        (
-            StatusCode::OK,
-            Json(json!({"status": "success", "count": count})),
-        )
-    } else {
-        (
-            StatusCode::NOT_FOUND,
-            Json(json!({"error": "Process not found or could not be killed"})),
-        )
+    let url = url::Url::parse(payload.url.trim())
+        .map_err(|e| ApiError::bad_request("INVALID_URL", format!("Invalid URL: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(ApiError::bad_request(
+            "INVALID_URL",
+            "Only http and https URLs can be opened",
+        ));
+    }
+
+    match tauri_plugin_opener::open_url(url.as_str(), None::<&str>) {
+        Ok(()) => Ok(Json(json!({"status": "success"}))),
+        Err(e) => Err(ApiError::internal(
+            "OPEN_FAILED",
+            format!("Failed to open URL: {}", e),
+        )),
     }
 }
 
-pub async fn focus_process(
+fn with_clipboard<T>(
+    state: &AppState,
+    f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+) -> Result<T, ApiError> {
+    let mut slot = state.clipboard.lock().unwrap();
+    if slot.is_none() {
+        *slot = Some(arboard::Clipboard::new().map_err(|e| {
+            ApiError::internal(
+                "CLIPBOARD_FAILED",
+                format!("Failed to open clipboard: {}", e),
+            )
+        })?);
+    }
+    f(slot.as_mut().unwrap())
+        .map_err(|e| ApiError::internal("CLIPBOARD_FAILED", format!("Clipboard error: {}", e)))
+}
+
+pub async fn get_clipboard(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<FocusRequest>,
-) -> (StatusCode, Json<Value>) {
-    if !state.config.lock().unwrap().features.enable_processes {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Process control disabled"})),
-        );
+) -> Result<Json<ClipboardContent>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_clipboard {
+        return Err(ApiError::feature_disabled("Clipboard disabled"));
     }
-    #[cfg(target_os = "windows")]
-    {
-        let pid = payload.pid;
-        unsafe {
-            let hwnd = find_window_for_pid(pid);
-            if hwnd.0 != 0 {
-                let _ = ShowWindow(hwnd, SW_RESTORE);
-                let _ = SetForegroundWindow(hwnd);
-                let _ = SwitchToThisWindow(hwnd, BOOL(1));
-                return (StatusCode::OK, Json(json!({"status": "success"})));
-            }
-        }
+
+    let text = with_clipboard(&state, |clipboard| match clipboard.get_text() {
+        Ok(text) => Ok(Some(text)),
+        // Empty, or an image/files rather than text
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e),
+    })?;
+    Ok(Json(ClipboardContent { text }))
+}
+
+pub async fn set_clipboard(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<SetClipboardRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_clipboard {
+        return Err(ApiError::feature_disabled("Clipboard disabled"));
     }
 
-    #[cfg(target_os = "macos")]
-    {
+    with_clipboard(&state, |clipboard| clipboard.set_text(payload.text))?;
+    Ok(Json(json!({"status": "success"})))
+}
+
+#[derive(Deserialize)]
+pub struct ScreenshotParams {
+    /// Display index; defaults to the primary display.
+    pub display: Option<usize>,
+    /// `png` (default) or `jpeg`.
+    pub format: Option<String>,
+    /// JPEG quality, 1-100.
+    pub quality: Option<u8>,
+    /// Scale down to this width, keeping the aspect ratio.
+    pub width: Option<u32>,
+}
+
+pub async fn get_screenshot(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ScreenshotParams>,
+) -> Result<Response, ApiError> {
+    use crate::server::screenshot::{self, Format};
+
+    if !state.config.lock().unwrap().features.enable_screenshot {
+        return Err(ApiError::feature_disabled("Screenshots disabled"));
+    }
+
+    let format = match params.format.as_deref().unwrap_or("png") {
+        "png" => Format::Png,
+        "jpeg" | "jpg" => Format::Jpeg(params.quality.unwrap_or(80).clamp(1, 100)),
+        other => {
+            return Err(ApiError::bad_request(
+                "UNSUPPORTED_FORMAT",
+                format!("Unsupported format \"{}\" (use png or jpeg)", other),
+            ))
+        }
+    };
+    let content_type = format.content_type();
+    let (display, width) = (params.display, params.width);
+
+    // Capturing and encoding a full display takes long enough to stall the
+    // async runtime.
+    let image = tokio::task::spawn_blocking(move || {
+        let Some(image) = screenshot::capture(display)? else {
+            return Ok(None);
+        };
+        screenshot::encode(image, width, format).map(Some)
+    })
+    .await
+    .map_err(|e| ApiError::internal("SCREENSHOT_FAILED", e.to_string()))?
+    .map_err(|e| ApiError::internal("SCREENSHOT_FAILED", e))?;
+
+    match image {
+        Some(bytes) => Ok(([(header::CONTENT_TYPE, content_type)], bytes).into_response()),
+        None => Err(ApiError::not_found(
+            "DISPLAY_NOT_FOUND",
+            match display {
+                Some(i) => format!("No display with index {}", i),
+                None => "No display found".to_string(),
+            },
+        )),
+    }
+}
+
+pub async fn list_displays(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DisplayInfo>>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_display {
+        return Err(ApiError::feature_disabled("Display control disabled"));
+    }
+
+    // DDC/CI reads take tens of milliseconds per monitor
+    tokio::task::spawn_blocking(crate::server::display::list_displays)
+        .await
+        .map_err(|e| ApiError::internal("DISPLAY_FAILED", e.to_string()))?
+        .map(Json)
+        .map_err(|e| ApiError::internal("DISPLAY_FAILED", e))
+}
+
+pub async fn set_display_brightness(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<u32>,
+    Json(payload): Json<SetBrightnessRequest>,
+) -> Result<Json<Value>, ApiError> {
+    use crate::server::display::{self, BrightnessError};
+
+    if !state.config.lock().unwrap().features.enable_display {
+        return Err(ApiError::feature_disabled("Display control disabled"));
+    }
+
+    let result = tokio::task::spawn_blocking(move || display::set_brightness(id, payload.value))
+        .await
+        .map_err(|e| ApiError::internal("BRIGHTNESS_FAILED", e.to_string()))?;
+
+    match result {
+        Ok(()) => Ok(Json(json!({"status": "success"}))),
+        Err(BrightnessError::NotFound) => Err(ApiError::not_found(
+            "DISPLAY_NOT_FOUND",
+            format!("No display with id {}", id),
+        )),
+        Err(BrightnessError::Unsupported(e)) => {
+            Err(ApiError::bad_request("BRIGHTNESS_UNSUPPORTED", e))
+        }
+        Err(BrightnessError::Failed(e)) => Err(ApiError::internal("BRIGHTNESS_FAILED", e)),
+    }
+}
+
+/// Inject a key press, typed text, mouse move or click.
+pub async fn send_input(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<InputRequest>,
+) -> Result<Json<Value>, ApiError> {
+    use crate::server::input::{self, InputError};
+
+    if !state.config.lock().unwrap().features.enable_input {
+        return Err(ApiError::feature_disabled("Input injection disabled"));
+    }
+
+    let result = match payload {
+        InputRequest::Key(KeyInput {
+            key,
+            text,
+            modifiers,
+        }) => match (key, text) {
+            (Some(key), _) => input::key_tap(&key, &modifiers),
+            (None, Some(text)) => input::type_text(&text),
+            (None, None) => {
+                return Err(ApiError::bad_request(
+                    "MISSING_VALUE",
+                    "Key input needs `key` or `text`",
+                ))
+            }
+        },
+        InputRequest::Mouse(MouseInput {
+            action,
+            x,
+            y,
+            button,
+        }) => {
+            let position = x.zip(y);
+            if action == MouseAction::Move && position.is_none() {
+                return Err(ApiError::bad_request(
+                    "MISSING_VALUE",
+                    "Mouse move needs `x` and `y`",
+                ));
+            }
+            let moved = match position {
+                Some((x, y)) => input::mouse_move(x, y),
+                None => Ok(()),
+            };
+            match action {
+                MouseAction::Move => moved,
+                MouseAction::Click => moved.and_then(|()| input::click(button)),
+            }
+        }
+    };
+
+    match result {
+        Ok(()) => Ok(Json(json!({"status": "success"}))),
+        Err(InputError::UnknownKey(key)) => Err(ApiError::bad_request(
+            "UNKNOWN_KEY",
+            format!("Unknown key: {}", key),
+        )),
+        Err(InputError::Failed(e)) => Err(ApiError::internal("INPUT_FAILED", e)),
+    }
+}
+
+pub async fn get_autostart(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<AutostartState>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_autostart_api {
+        return Err(ApiError::feature_disabled("Autostart API disabled"));
+    }
+
+    // Reads the registry, a launch agent or a .desktop file
+    let enabled = tokio::task::spawn_blocking(crate::server::autostart::is_enabled)
+        .await
+        .map_err(|e| ApiError::internal("AUTOSTART_FAILED", e.to_string()))?
+        .map_err(|e| ApiError::internal("AUTOSTART_FAILED", e))?;
+    Ok(Json(AutostartState { enabled }))
+}
+
+/// Same as toggling autostart in the dashboard: registers or removes the
+/// login item and saves `features.enable_autostart`.
+pub async fn set_autostart(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<AutostartState>,
+) -> Result<Json<AutostartState>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_autostart_api {
+        return Err(ApiError::feature_disabled("Autostart API disabled"));
+    }
+
+    let config = state.config.clone();
+    let enabled = payload.enabled;
+    tokio::task::spawn_blocking(move || crate::server::autostart::set_enabled(&config, enabled))
+        .await
+        .map_err(|e| ApiError::internal("AUTOSTART_FAILED", e.to_string()))?
+        .map_err(|e| ApiError::internal("AUTOSTART_FAILED", e))?;
+    info!(
+        "Autostart {} via API",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(Json(payload))
+}
+
+pub async fn kill_process(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    Json(payload): Json<KillRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let features = state.config.lock().unwrap().features.clone();
+    if !features.enable_processes {
+        return Err(ApiError::feature_disabled("Process control disabled"));
+    }
+    if !features.enable_process_kill {
+        return Err(ApiError::feature_disabled("Killing processes disabled"));
+    }
+    let dry_run = is_dry_run(&state, params.dry_run);
+
+    run_refresh(move || kill_matching(&state, payload, dry_run)).await?
+}
+
+/// Kill the process `payload` names by pid or exact name, or only count the
+/// matches when `dry_run`.
+fn kill_matching(
+    state: &AppState,
+    payload: KillRequest,
+    dry_run: bool,
+) -> Result<Json<Value>, ApiError> {
+    let mut sys = lock_system(&state.processes);
+    sys.refresh_processes();
+
+    if dry_run {
+        // Still look the target up, so a dry run fails the same way a real one would
+        let count = if let Some(pid) = payload.pid {
+            sys.process(Pid::from(pid as usize)).map_or(0, |_| 1)
+        } else if let Some(name) = &payload.name {
+            sys.processes()
+                .values()
+                .filter(|p| p.name() == name)
+                .count()
+        } else {
+            0
+        };
+        if count == 0 {
+            return Err(ApiError::not_found(
+                "PROCESS_NOT_FOUND",
+                "Process not found or could not be killed",
+            ));
+        }
+        return Ok(Json(
+            json!({"status": "dry_run", "would": "kill", "count": count}),
+        ));
+    }
+
+    let mut killed = false;
+    let mut count = 0;
+
+    if let Some(pid) = payload.pid {
+        if let Some(process) = sys.process(Pid::from(pid as usize)) {
+            if process.kill() {
+                killed = true;
+                count = 1;
+            }
+        }
+    } else if let Some(name) = payload.name {
+        for process in sys.processes().values() {
+            if process.name() == name {
+                if process.kill() {
+                    killed = true;
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    if killed || count > 0 {
+        Ok(Json(json!({"status": "success", "count": count})))
+    } else {
+        Err(ApiError::not_found(
+            "PROCESS_NOT_FOUND",
+            "Process not found or could not be killed",
+        ))
+    }
+}
+
+/// Set a process's priority (nice value) to tame a CPU hog without killing it.
+pub async fn set_process_priority(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    Json(payload): Json<PriorityRequest>,
+) -> Result<Json<Value>, ApiError> {
+    use crate::server::process_control::{self, ProcessControlError, PRIORITY_RANGE};
+
+    let features = state.config.lock().unwrap().features.clone();
+    if !features.enable_processes {
+        return Err(ApiError::feature_disabled("Process control disabled"));
+    }
+    if !features.enable_process_priority {
+        return Err(ApiError::feature_disabled(
+            "Changing process priority disabled",
+        ));
+    }
+    if !PRIORITY_RANGE.contains(&payload.priority) {
+        return Err(ApiError::bad_request(
+            "INVALID_PRIORITY",
+            format!(
+                "priority must be between {} and {}",
+                PRIORITY_RANGE.start(),
+                PRIORITY_RANGE.end()
+            ),
+        ));
+    }
+    if is_dry_run(&state, params.dry_run) {
+        return Ok(dry_run_response("set_priority"));
+    }
+
+    let pid = payload.pid;
+    let result =
+        tokio::task::spawn_blocking(move || process_control::set_priority(pid, payload.priority))
+            .await
+            .map_err(|e| ApiError::internal("PRIORITY_FAILED", e.to_string()))?;
+
+    match result {
+        Ok(applied) => Ok(Json(
+            json!({"status": "success", "pid": pid, "priority": applied}),
+        )),
+        Err(ProcessControlError::InvalidPid(e)) => Err(ApiError::bad_request("INVALID_PID", e)),
+        Err(ProcessControlError::NotFound) => Err(ApiError::not_found(
+            "PROCESS_NOT_FOUND",
+            format!("No process with pid {}", pid),
+        )),
+        Err(ProcessControlError::Denied(e)) => Err(ApiError::new(
+            axum::http::StatusCode::FORBIDDEN,
+            "PRIORITY_DENIED",
+            e,
+        )),
+        Err(ProcessControlError::Failed(e)) => Err(ApiError::internal("PRIORITY_FAILED", e)),
+    }
+}
+
+/// Freeze a process without killing it.
+pub async fn suspend_process(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    Json(payload): Json<SuspendRequest>,
+) -> Result<Json<Value>, ApiError> {
+    set_process_suspended(&state, params.dry_run, payload.pid, true).await
+}
+
+/// Let a suspended process run again.
+pub async fn resume_process(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DryRunParams>,
+    Json(payload): Json<SuspendRequest>,
+) -> Result<Json<Value>, ApiError> {
+    set_process_suspended(&state, params.dry_run, payload.pid, false).await
+}
+
+async fn set_process_suspended(
+    state: &AppState,
+    dry_run: bool,
+    pid: u32,
+    suspend: bool,
+) -> Result<Json<Value>, ApiError> {
+    use crate::server::process_control::{self, ProcessControlError};
+
+    let features = state.config.lock().unwrap().features.clone();
+    if !features.enable_processes {
+        return Err(ApiError::feature_disabled("Process control disabled"));
+    }
+    if !features.enable_process_suspend {
+        return Err(ApiError::feature_disabled("Suspending processes disabled"));
+    }
+    let action = if suspend { "suspend" } else { "resume" };
+    if is_dry_run(state, dry_run) {
+        return Ok(dry_run_response(action));
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        if suspend {
+            process_control::suspend(pid)
+        } else {
+            process_control::resume(pid)
+        }
+    })
+    .await
+    .map_err(|e| ApiError::internal("SUSPEND_FAILED", e.to_string()))?;
+
+    match result {
+        Ok(was_suspended) => Ok(Json(
+            json!({"status": "success", "pid": pid, "was_suspended": was_suspended}),
+        )),
+        Err(ProcessControlError::InvalidPid(e)) => Err(ApiError::bad_request("INVALID_PID", e)),
+        Err(ProcessControlError::NotFound) => Err(ApiError::not_found(
+            "PROCESS_NOT_FOUND",
+            format!("No process with pid {}", pid),
+        )),
+        Err(ProcessControlError::Denied(e)) => Err(ApiError::new(
+            axum::http::StatusCode::FORBIDDEN,
+            "SUSPEND_DENIED",
+            e,
+        )),
+        Err(ProcessControlError::Failed(e)) => Err(ApiError::internal("SUSPEND_FAILED", e)),
+    }
+}
+
+pub async fn focus_process(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<FocusRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_processes {
+        return Err(ApiError::feature_disabled("Process control disabled"));
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let pid = payload.pid;
+        unsafe {
+            let hwnds = find_windows_for_pid(pid, payload.all_windows);
+            // Raise bottom-up so the window that was topmost ends up in front.
+            for &hwnd in hwnds.iter().rev() {
+                let _ = ShowWindow(hwnd, SW_RESTORE);
+                let _ = SetForegroundWindow(hwnd);
+                let _ = SwitchToThisWindow(hwnd, BOOL(1));
+            }
+            if !hwnds.is_empty() {
+                return Ok(Json(json!({"status": "success"})));
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
         let pid = payload.pid;
         let script = format!(
             "tell application \"System Events\" to set frontmost of the first process whose unix id is {} to true",
@@ -542,142 +1650,508 @@ pub async fn focus_process(
         {
             Ok(output) => {
                 if output.status.success() {
-                    return (StatusCode::OK, Json(json!({"status": "success"})));
+                    return Ok(Json(json!({"status": "success"})));
                 } else {
                     // Try to be helpful if it fails
                     let err = String::from_utf8_lossy(&output.stderr);
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(json!({"error": format!("Failed to focus: {}", err)})),
-                    );
+                    return Err(ApiError::internal(
+                        "FOCUS_FAILED",
+                        format!("Failed to focus: {}", err),
+                    ));
                 }
             }
             Err(e) => {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({"error": format!("Failed to execute osascript: {}", e)})),
-                )
+                return Err(ApiError::internal(
+                    "FOCUS_FAILED",
+                    format!("Failed to execute osascript: {}", e),
+                ))
             }
         }
     }
 
-    (
-        StatusCode::BAD_REQUEST,
-        Json(json!({"error": "Action not supported on this platform"})),
-    )
+    #[cfg(target_os = "linux")]
+    return focus_x11_window(payload.pid).map(|()| Json(json!({"status": "success"})));
+
+    #[cfg(not(target_os = "linux"))]
+    return Err(ApiError::bad_request(
+        "UNSUPPORTED_PLATFORM",
+        "Action not supported on this platform",
+    ));
+}
+
+/// Whether `focus_process` can work here. On Linux that takes an X11 display
+/// and `xdotool` or `wmctrl`.
+fn focus_supported() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        let on_path = |tool: &str| {
+            std::env::var_os("PATH").is_some_and(|paths| {
+                std::env::split_paths(&paths).any(|dir| dir.join(tool).is_file())
+            })
+        };
+        std::env::var_os("DISPLAY").is_some() && (on_path("xdotool") || on_path("wmctrl"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    cfg!(any(target_os = "windows", target_os = "macos"))
+}
+
+/// Activate a window owned by `pid` through `xdotool`, falling back to `wmctrl`.
+///
+/// Wayland doesn't let clients raise other apps' windows, so a Wayland-only
+/// session (no `DISPLAY`) is reported as unsupported. XWayland windows are
+/// still reachable whenever `DISPLAY` is set.
+#[cfg(target_os = "linux")]
+fn focus_x11_window(pid: u32) -> Result<(), ApiError> {
+    use std::process::Command;
+
+    if std::env::var_os("DISPLAY").is_none() {
+        let message = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            "Focusing windows is not supported on Wayland"
+        } else {
+            "No X11 display available"
+        };
+        return Err(ApiError::bad_request("UNSUPPORTED_PLATFORM", message));
+    }
+
+    let no_window = || ApiError::not_found("PROCESS_NOT_FOUND", "No window found for process");
+    let run = |program: &str, args: &[&str]| -> Result<(), ApiError> {
+        let output = Command::new(program).args(args).output().map_err(|e| {
+            ApiError::internal(
+                "FOCUS_FAILED",
+                format!("Failed to execute {}: {}", program, e),
+            )
+        })?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(ApiError::internal(
+                "FOCUS_FAILED",
+                format!(
+                    "Failed to focus: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            ))
+        }
+    };
+
+    // xdotool exits non-zero when nothing matches, so only the output matters.
+    match Command::new("xdotool")
+        .args(["search", "--onlyvisible", "--pid", &pid.to_string()])
+        .output()
+    {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let wid = stdout.lines().next().ok_or_else(no_window)?;
+            return run("xdotool", &["windowactivate", wid.trim()]);
+        }
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(ApiError::internal(
+                "FOCUS_FAILED",
+                format!("Failed to execute xdotool: {}", e),
+            ));
+        }
+        Err(_) => {}
+    }
+
+    // `wmctrl -lp` lines look like: <window id> <desktop> <pid> <host> <title>
+    let output = match Command::new("wmctrl").arg("-lp").output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ApiError::bad_request(
+                "UNSUPPORTED_PLATFORM",
+                "Install xdotool or wmctrl to focus windows",
+            ));
+        }
+        Err(e) => {
+            return Err(ApiError::internal(
+                "FOCUS_FAILED",
+                format!("Failed to execute wmctrl: {}", e),
+            ))
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let wid = stdout
+        .lines()
+        .find_map(|line| {
+            let mut cols = line.split_whitespace();
+            let wid = cols.next()?;
+            let owner: u32 = cols.nth(1)?.parse().ok()?;
+            (owner == pid).then_some(wid)
+        })
+        .ok_or_else(no_window)?;
+    run("wmctrl", &["-ia", wid])
 }
 
 // Power Handler
+#[derive(Deserialize)]
+pub struct PowerParams {
+    /// Run the action after this many seconds instead of immediately.
+    pub delay_secs: Option<u64>,
+    /// Validate the action but neither run nor schedule it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Longest `delay_secs` accepted: a week.
+const MAX_POWER_DELAY_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// A delayed power action waiting for its timer.
+pub struct ScheduledPower {
+    pub action: String,
+    pub fire_at: u64,
+    handle: tokio::task::JoinHandle<()>,
+}
+
 pub async fn power_action(
     State(state): State<Arc<AppState>>,
     axum::extract::Path(action): axum::extract::Path<String>,
-) -> (StatusCode, Json<Value>) {
+    Query(params): Query<PowerParams>,
+) -> Result<Json<Value>, ApiError> {
     let features = state.config.lock().unwrap().features.clone();
+    check_power_action(&features, &action)?;
+    let delay = params.delay_secs.unwrap_or(0);
+    let fire_at = unix_now()
+        .checked_add(delay)
+        .filter(|_| delay <= MAX_POWER_DELAY_SECS)
+        .ok_or_else(|| {
+            ApiError::bad_request(
+                "INVALID_DELAY",
+                format!("delay_secs must be at most {}", MAX_POWER_DELAY_SECS),
+            )
+        })?;
+    if is_dry_run(&state, params.dry_run) {
+        return Ok(dry_run_response(&action));
+    }
 
-    match action.as_str() {
-        "shutdown" => {
-            if !features.enable_shutdown {
-                return (
-                    StatusCode::FORBIDDEN,
-                    Json(json!({"error": "Shutdown disabled"})),
-                );
-            }
-            #[cfg(target_os = "windows")]
-            unsafe {
-                use windows::Win32::System::Shutdown::{
-                    ExitWindowsEx, EWX_SHUTDOWN, EXIT_WINDOWS_FLAGS, SHTDN_REASON_MAJOR_OTHER,
-                };
-                let _ = ExitWindowsEx(
-                    EXIT_WINDOWS_FLAGS(EWX_SHUTDOWN.0 | 0x00000004),
-                    SHTDN_REASON_MAJOR_OTHER,
-                );
-            }
-            #[cfg(target_os = "macos")]
-            {
-                std::process::Command::new("osascript")
-                    .args(["-e", "tell app \"System Events\" to shut down"])
-                    .spawn()
-                    .ok();
-            }
-            (StatusCode::OK, Json(json!({"status": "success"})))
-        }
-        "restart" => {
-            if !features.enable_restart {
-                return (
-                    StatusCode::FORBIDDEN,
-                    Json(json!({"error": "Restart disabled"})),
-                );
-            }
-            #[cfg(target_os = "windows")]
-            unsafe {
-                use windows::Win32::System::Shutdown::{
-                    ExitWindowsEx, EWX_REBOOT, EXIT_WINDOWS_FLAGS, SHTDN_REASON_MAJOR_OTHER,
-                };
-                let _ = ExitWindowsEx(
-                    EXIT_WINDOWS_FLAGS(EWX_REBOOT.0 | 0x00000004),
-                    SHTDN_REASON_MAJOR_OTHER,
-                );
-            }
-            #[cfg(target_os = "macos")]
-            {
-                std::process::Command::new("osascript")
-                    .args(["-e", "tell app \"System Events\" to restart"])
-                    .spawn()
-                    .ok();
-            }
-            (StatusCode::OK, Json(json!({"status": "success"})))
-        }
-        "sleep" => {
-            if !features.enable_sleep {
-                return (
-                    StatusCode::FORBIDDEN,
-                    Json(json!({"error": "Sleep disabled"})),
-                );
-            }
-            #[cfg(target_os = "windows")]
-            {
-                std::process::Command::new("rundll32.exe")
-                    .args(["powrprof.dll,SetSuspendState", "0", "1", "0"])
-                    .spawn()
-                    .ok();
-            }
-            #[cfg(target_os = "macos")]
-            {
-                std::process::Command::new("pmset")
-                    .args(["sleepnow"])
-                    .spawn()
-                    .ok();
-            }
-            (StatusCode::OK, Json(json!({"status": "success"})))
-        }
-        "hibernate" => {
-            if !features.enable_hibernate {
-                return (
-                    StatusCode::FORBIDDEN,
-                    Json(json!({"error": "Hibernate disabled"})),
-                );
-            }
-            #[cfg(target_os = "windows")]
-            {
-                std::process::Command::new("shutdown")
-                    .args(["/h"])
-                    .spawn()
-                    .ok();
-            }
-            #[cfg(target_os = "macos")]
-            {
-                // Same as sleep
-                std::process::Command::new("pmset")
-                    .args(["sleepnow"])
-                    .spawn()
-                    .ok();
+    match delay {
+        0 => tokio::task::spawn_blocking(move || run_power_action(&action))
+            .await
+            .map_err(|e| ApiError::internal("POWER_FAILED", e.to_string()))?
+            .map(|()| Json(json!({"status": "success"})))
+            .map_err(|e| ApiError::internal("POWER_FAILED", e)),
+        delay => {
+            let id = schedule_power_action(&state, action, delay, fire_at);
+            Ok(Json(
+                json!({"status": "scheduled", "id": id, "fire_at": fire_at}),
+            ))
+        }
+    }
+}
+
+pub async fn list_scheduled_power(
+    State(state): State<Arc<AppState>>,
+) -> Json<Vec<ScheduledPowerInfo>> {
+    let now = unix_now();
+    let scheduled = state.scheduled_power.lock().unwrap();
+    let mut list: Vec<ScheduledPowerInfo> = scheduled
+        .iter()
+        .map(|(id, s)| ScheduledPowerInfo {
+            id: *id,
+            action: s.action.clone(),
+            fire_at: s.fire_at,
+            remaining_secs: s.fire_at.saturating_sub(now),
+        })
+        .collect();
+    list.sort_by_key(|s| s.fire_at);
+    Json(list)
+}
+
+/// Cancel one scheduled action by id, or all of them when no id is given.
+pub async fn cancel_scheduled_power(
+    State(state): State<Arc<AppState>>,
+    payload: Option<Json<CancelPowerRequest>>,
+) -> Result<Json<Value>, ApiError> {
+    let id = payload.and_then(|Json(p)| p.id);
+    let cancelled = cancel_power_actions(&state, id);
+    if id.is_some() && cancelled == 0 {
+        return Err(ApiError::not_found(
+            "SCHEDULE_NOT_FOUND",
+            "No scheduled action with that id",
+        ));
+    }
+    Ok(Json(json!({"status": "success", "cancelled": cancelled})))
+}
+
+/// Abort pending power actions (`None` = all). Returns how many were cancelled.
+pub fn cancel_power_actions(state: &AppState, id: Option<u64>) -> usize {
+    let mut scheduled = state.scheduled_power.lock().unwrap();
+    let ids: Vec<u64> = match id {
+        Some(id) => scheduled
+            .contains_key(&id)
+            .then_some(id)
+            .into_iter()
+            .collect(),
+        None => scheduled.keys().copied().collect(),
+    };
+    for id in &ids {
+        if let Some(s) = scheduled.remove(id) {
+            info!("Cancelled scheduled {} (id {})", s.action, id);
+            s.handle.abort();
+        }
+    }
+    ids.len()
+}
+
+/// Run `action` in `delay_secs`, at Unix time `fire_at`. Returns its id.
+fn schedule_power_action(
+    state: &Arc<AppState>,
+    action: String,
+    delay_secs: u64,
+    fire_at: u64,
+) -> u64 {
+    let id = state
+        .next_schedule_id
+        .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+    // Hold the lock while spawning so the task can't fire and remove its
+    // entry before it has been inserted.
+    let mut scheduled = state.scheduled_power.lock().unwrap();
+    let task_state = state.clone();
+    let task_action = action.clone();
+    let handle = tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(delay_secs)).await;
+        task_state.scheduled_power.lock().unwrap().remove(&id);
+
+        // Features may have been turned off while the timer was running
+        let features = task_state.config.lock().unwrap().features.clone();
+        if let Err(e) = check_power_action(&features, &task_action) {
+            warn!("Skipping scheduled {}: {}", task_action, e.message);
+            return;
+        }
+        info!("Running scheduled {} (id {})", task_action, id);
+        let action = task_action.clone();
+        match tokio::task::spawn_blocking(move || run_power_action(&action)).await {
+            Ok(Err(e)) => error!("Scheduled {} failed: {}", task_action, e),
+            Err(e) => error!("Scheduled {} failed: {}", task_action, e),
+            Ok(Ok(())) => {}
+        }
+    });
+    info!("Scheduled {} in {}s (id {})", action, delay_secs, id);
+    scheduled.insert(
+        id,
+        ScheduledPower {
+            action,
+            fire_at,
+            handle,
+        },
+    );
+    id
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Reject unknown actions and ones turned off in config.
+fn check_power_action(features: &FeaturesConfig, action: &str) -> Result<(), ApiError> {
+    let (enabled, message) = match action {
+        "shutdown" => (features.enable_shutdown, "Shutdown disabled"),
+        "restart" | "restart_firmware" => (features.enable_restart, "Restart disabled"),
+        "sleep" => (features.enable_sleep, "Sleep disabled"),
+        "hibernate" => (features.enable_hibernate, "Hibernate disabled"),
+        _ => {
+            return Err(ApiError::bad_request(
+                "UNSUPPORTED_ACTION",
+                "Unsupported action",
+            ))
+        }
+    };
+    if !enabled {
+        return Err(ApiError::feature_disabled(message));
+    }
+    // Only rebooting into firmware setup is implemented on Linux so far
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    {
+        if action != "restart_firmware" {
+            return Err(ApiError::bad_request(
+                "UNSUPPORTED_PLATFORM",
+                format!("{} is not supported on this platform", action),
+            ));
+        }
+    }
+    if action == "restart_firmware" {
+        check_firmware_restart()?;
+    }
+    Ok(())
+}
+
+/// Rebooting into firmware setup needs OS support and, on Windows, an
+/// elevated bridge; check up front rather than fail silently later.
+fn check_firmware_restart() -> Result<(), ApiError> {
+    #[cfg(target_os = "windows")]
+    {
+        if !is_elevated() {
+            return Err(ApiError::new(
+                axum::http::StatusCode::FORBIDDEN,
+                "ELEVATION_REQUIRED",
+                "Restarting into firmware setup requires running Cntrl as administrator",
+            ));
+        }
+    }
+
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    {
+        // systemd reports whether the firmware exposes the OsIndications flag
+        let supported = std::process::Command::new("busctl")
+            .args([
+                "get-property",
+                "org.freedesktop.login1",
+                "/org/freedesktop/login1",
+                "org.freedesktop.login1.Manager",
+                "CanRebootToFirmwareSetup",
+            ])
+            .output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).contains("yes"))
+            .unwrap_or(false);
+        if !supported {
+            return Err(ApiError::bad_request(
+                "UNSUPPORTED_PLATFORM",
+                "This system can't reboot into firmware setup (needs UEFI and systemd-logind)",
+            ));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    return Err(ApiError::bad_request(
+        "UNSUPPORTED_PLATFORM",
+        "macOS has no command to restart into firmware setup",
+    ));
+
+    #[cfg(not(target_os = "macos"))]
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn is_elevated() -> bool {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token = HANDLE::default();
+        if !OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).as_bool() {
+            return false;
+        }
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut size = 0u32;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut std::ffi::c_void),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut size,
+        )
+        .as_bool();
+        let _ = CloseHandle(token);
+        ok && elevation.TokenIsElevated != 0
+    }
+}
+
+/// How long to wait for a power command to fail before assuming it worked.
+/// Sleep and hibernate commands may not return until the machine wakes up.
+const POWER_COMMAND_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Start a power command and report it if it fails right away (e.g. missing
+/// privileges). One still running after the grace period is under way.
+fn run_power_command(program: &str, args: &[&str]) -> Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+
+    let deadline = std::time::Instant::now() + POWER_COMMAND_GRACE;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                let output = child.wait_with_output().ok();
+                let detail = output
+                    .map(|o| {
+                        let stderr = String::from_utf8_lossy(&o.stderr).trim().to_string();
+                        if stderr.is_empty() {
+                            String::from_utf8_lossy(&o.stdout).trim().to_string()
+                        } else {
+                            stderr
+                        }
+                    })
+                    .unwrap_or_default();
+                return Err(format!("{} failed ({}): {}", program, status, detail));
             }
-            (StatusCode::OK, Json(json!({"status": "success"})))
+            Ok(None) if std::time::Instant::now() >= deadline => return Ok(()),
+            Ok(None) => std::thread::sleep(std::time::Duration::from_millis(50)),
+            Err(e) => return Err(format!("Failed to wait for {}: {}", program, e)),
         }
-        _ => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({"error": "Unsupported action"})),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn exit_windows(flags: u32) -> Result<(), String> {
+    use windows::Win32::System::Shutdown::{
+        ExitWindowsEx, EXIT_WINDOWS_FLAGS, SHTDN_REASON_MAJOR_OTHER,
+    };
+    // 0x4 = EWX_FORCE, so unsaved apps can't block it
+    unsafe {
+        ExitWindowsEx(
+            EXIT_WINDOWS_FLAGS(flags | 0x00000004),
+            SHTDN_REASON_MAJOR_OTHER,
+        )
+    }
+    .ok()
+    .map_err(|e| format!("ExitWindowsEx failed: {}", e.message()))
+}
+
+/// Execute a power action that has already passed `check_power_action`.
+/// Blocks for up to `POWER_COMMAND_GRACE`.
+#[cfg(target_os = "windows")]
+fn run_power_action(action: &str) -> Result<(), String> {
+    use windows::Win32::System::Shutdown::{EWX_REBOOT, EWX_SHUTDOWN};
+    match action {
+        "shutdown" => exit_windows(EWX_SHUTDOWN.0),
+        "restart" => exit_windows(EWX_REBOOT.0),
+        // rundll32 exits 0 even when SetSuspendState fails, so only a
+        // failure to start it is caught here.
+        "sleep" => run_power_command(
+            "rundll32.exe",
+            &["powrprof.dll,SetSuspendState", "0", "1", "0"],
+        ),
+        "restart_firmware" => run_power_command("shutdown", &["/r", "/fw", "/t", "0"]),
+        "hibernate" => run_power_command("shutdown", &["/h"]),
+        _ => Err(format!("Unsupported action: {}", action)),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn run_power_action(action: &str) -> Result<(), String> {
+    match action {
+        "shutdown" => run_power_command(
+            "osascript",
+            &["-e", "tell app \"System Events\" to shut down"],
+        ),
+        "restart" => run_power_command(
+            "osascript",
+            &["-e", "tell app \"System Events\" to restart"],
         ),
+        // Hibernate is the same as sleep
+        "sleep" | "hibernate" => run_power_command("pmset", &["sleepnow"]),
+        _ => Err(format!("Unsupported action: {}", action)),
+    }
+}
+
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+fn run_power_action(action: &str) -> Result<(), String> {
+    match action {
+        "restart_firmware" => run_power_command("systemctl", &["reboot", "--firmware-setup"]),
+        _ => Err(format!("{} is not supported on this platform", action)),
     }
 }
 
@@ -685,12 +2159,9 @@ pub async fn power_action(
 
 pub async fn get_media_status(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<MediaStatus>, (StatusCode, Json<Value>)> {
+) -> Result<Json<MediaStatus>, ApiError> {
     if !state.config.lock().unwrap().features.enable_media {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Media control disabled"})),
-        ));
+        return Err(ApiError::feature_disabled("Media control disabled"));
     }
 
     if let Some(status) = crate::server::media::get_media_status().await {
@@ -705,18 +2176,64 @@ pub async fn get_media_status(
         title: None,
         artist: None,
         supports_ctrl: true,
+        output_device: None,
+        input_volume: None,
+        input_muted: None,
+        app_id: None,
+        sessions: Vec::new(),
+        supported_players: crate::server::media::supported_players(),
     }))
 }
 
+pub async fn list_audio_devices(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AudioDevice>>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_media {
+        return Err(ApiError::feature_disabled("Media control disabled"));
+    }
+
+    crate::server::audio::list_output_devices()
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::bad_request(
+                "UNSUPPORTED_PLATFORM",
+                "Audio devices can't be listed on this platform",
+            )
+        })
+}
+
+pub async fn set_audio_device(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<SetAudioDeviceRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_media {
+        return Err(ApiError::feature_disabled("Media control disabled"));
+    }
+
+    let devices = crate::server::audio::list_output_devices().ok_or_else(|| {
+        ApiError::bad_request(
+            "UNSUPPORTED_PLATFORM",
+            "Audio devices can't be changed on this platform",
+        )
+    })?;
+    if !devices.iter().any(|d| d.id == payload.id) {
+        return Err(ApiError::not_found(
+            "DEVICE_NOT_FOUND",
+            format!("No output device with id {}", payload.id),
+        ));
+    }
+
+    crate::server::audio::set_default_output_device(&payload.id)
+        .map_err(|e| ApiError::internal("DEVICE_SWITCH_FAILED", e))?;
+    Ok(Json(json!({"status": "success"})))
+}
+
 pub async fn media_control(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<MediaControlRequest>,
-) -> (StatusCode, Json<Value>) {
+) -> Result<Json<Value>, ApiError> {
     if !state.config.lock().unwrap().features.enable_media {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(json!({"error": "Media control disabled"})),
-        );
+        return Err(ApiError::feature_disabled("Media control disabled"));
     }
 
     let action = payload.action.as_str();
@@ -724,61 +2241,128 @@ pub async fn media_control(
     // 1. Common Volume/Mute logic
     if action == "set_volume" {
         if let Some(vol) = payload.value {
-            if unsafe { crate::server::media::set_volume(vol) }.is_some() {
-                return (StatusCode::OK, Json(json!({"status": "success"})));
-            }
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": "Failed to set volume"})),
-            );
+            return match unsafe { crate::server::media::set_volume(vol) } {
+                Ok(()) => Ok(Json(json!({"status": "success"}))),
+                Err(e) => Err(ApiError::internal(
+                    "VOLUME_FAILED",
+                    format!("Failed to set volume: {}", e),
+                )),
+            };
         }
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(json!({"error": "Value required for set_volume"})),
-        );
+        return Err(ApiError::bad_request(
+            "MISSING_VALUE",
+            "Value required for set_volume",
+        ));
+    }
+
+    if action == "volume_up" || action == "volume_down" {
+        let step = state.config.lock().unwrap().media.volume_step as i32;
+        let delta = if action == "volume_up" { step } else { -step };
+        return match unsafe { crate::server::media::step_volume(delta) } {
+            Ok(()) => Ok(Json(json!({"status": "success"}))),
+            Err(e) => Err(ApiError::internal(
+                "VOLUME_FAILED",
+                format!("Failed to change volume: {}", e),
+            )),
+        };
+    }
+
+    if action == "set_input_volume" {
+        if let Some(vol) = payload.value {
+            return match unsafe { crate::server::media::set_input_volume(vol) } {
+                Ok(()) => Ok(Json(json!({"status": "success"}))),
+                Err(e) => Err(ApiError::internal(
+                    "VOLUME_FAILED",
+                    format!("Failed to set input volume: {}", e),
+                )),
+            };
+        }
+        return Err(ApiError::bad_request(
+            "MISSING_VALUE",
+            "Value required for set_input_volume",
+        ));
+    }
+
+    if action == "mute_input" || action == "unmute_input" {
+        return match unsafe { crate::server::media::set_input_mute(action == "mute_input") } {
+            Ok(()) => Ok(Json(json!({"status": "success"}))),
+            Err(e) => Err(ApiError::internal(
+                "MUTE_FAILED",
+                format!("Failed to set input mute state: {}", e),
+            )),
+        };
     }
 
     if action == "mute" || action == "unmute" || action == "toggle_mute" {
         #[cfg(target_os = "macos")]
         {
-            if action == "toggle_mute" {
-                if crate::server::media::run_media_action("toggle_mute")
-                    .await
-                    .is_some()
-                {
-                    return (StatusCode::OK, Json(json!({"status": "success"})));
-                }
+            let result = if action == "toggle_mute" {
+                unsafe { crate::server::media::toggle_mute() }
             } else {
-                let mute = action == "mute";
-                if unsafe { crate::server::media::set_mute(mute) }.is_some() {
-                    return (StatusCode::OK, Json(json!({"status": "success"})));
-                }
-            }
+                unsafe { crate::server::media::set_mute(action == "mute") }
+            };
+            return match result {
+                Ok(()) => Ok(Json(json!({"status": "success"}))),
+                Err(e) => Err(ApiError::internal(
+                    "MUTE_FAILED",
+                    format!("Failed to set mute state: {}", e),
+                )),
+            };
         }
 
         #[cfg(target_os = "windows")]
         {
             if action != "toggle_mute" {
                 let mute = action == "mute";
-                if unsafe { crate::server::media::set_mute(mute) }.is_some() {
-                    return (StatusCode::OK, Json(json!({"status": "success"})));
-                }
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({"error": "Failed to set mute state"})),
-                );
+                return match unsafe { crate::server::media::set_mute(mute) } {
+                    Ok(()) => Ok(Json(json!({"status": "success"}))),
+                    Err(e) => Err(ApiError::internal(
+                        "MUTE_FAILED",
+                        format!("Failed to set mute state: {}", e),
+                    )),
+                };
             }
         }
     }
 
     // 2. Playback Control
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(app_id) = payload.app_id.as_deref() {
+            if crate::server::media::SESSION_ACTIONS.contains(&action) {
+                return match crate::server::media::control_session(app_id, action).await {
+                    Some(true) => Ok(Json(json!({"status": "success"}))),
+                    Some(false) => Err(ApiError::internal(
+                        "MEDIA_CONTROL_FAILED",
+                        format!("{} did not accept {}", app_id, action),
+                    )),
+                    None => Err(ApiError::not_found(
+                        "SESSION_NOT_FOUND",
+                        format!("No media session for {}", app_id),
+                    )),
+                };
+            }
+        }
+    }
+
     #[cfg(target_os = "macos")]
     {
-        if crate::server::media::run_media_action(action)
-            .await
-            .is_some()
-        {
-            return (StatusCode::OK, Json(json!({"status": "success"})));
+        use crate::server::media::PlayerError;
+
+        if crate::server::media::ACTIONS.contains(&action) {
+            return match crate::server::media::run_media_action(action).await {
+                Ok(()) => Ok(Json(json!({"status": "success"}))),
+                Err(PlayerError::NoPlayer) => Err(ApiError::not_found(
+                    "NO_PLAYER",
+                    "No supported media player is running",
+                )),
+                Err(PlayerError::Unsupported) => Err(ApiError::new(
+                    axum::http::StatusCode::CONFLICT,
+                    "PLAYER_UNSUPPORTED",
+                    format!("The current player can't {}", action),
+                )),
+                Err(PlayerError::Failed(e)) => Err(ApiError::internal("MEDIA_CONTROL_FAILED", e)),
+            };
         }
     }
 
@@ -786,12 +2370,10 @@ pub async fn media_control(
     unsafe {
         use windows::Win32::UI::Input::KeyboardAndMouse::{
             SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_MEDIA_NEXT_TRACK,
-            VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_VOLUME_DOWN, VK_VOLUME_MUTE, VK_VOLUME_UP,
+            VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_VOLUME_MUTE,
         };
 
         let vk = match action {
-            "volume_up" => Some(VK_VOLUME_UP),
-            "volume_down" => Some(VK_VOLUME_DOWN),
             "mute" | "toggle_mute" => Some(VK_VOLUME_MUTE),
             "next" => Some(VK_MEDIA_NEXT_TRACK),
             "prev" | "previous" => Some(VK_MEDIA_PREV_TRACK),
@@ -822,32 +2404,48 @@ pub async fn media_control(
                 },
             ];
             SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
-            return (StatusCode::OK, Json(json!({"status": "success"})));
+            return Ok(Json(json!({"status": "success"})));
         }
     }
 
-    (
-        StatusCode::BAD_REQUEST,
-        Json(json!({"error": format!("Unsupported action: {}", action)})),
+    Err(ApiError::bad_request(
+        "UNSUPPORTED_ACTION",
+        format!("Unsupported action: {}", action),
     )
+    .with_supported(crate::server::media::ACTIONS))
+}
+
+/// Media actions available on this platform, so clients can offer only the
+/// controls that work.
+pub async fn get_media_capabilities(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<MediaCapabilities>, ApiError> {
+    if !state.config.lock().unwrap().features.enable_media {
+        return Err(ApiError::feature_disabled("Media control disabled"));
+    }
+    Ok(Json(crate::server::media::capabilities()))
 }
 
 // WIN32 Helpers
 #[cfg(target_os = "windows")]
 struct EnumContext {
     pid: u32,
-    hwnd: HWND,
+    all: bool,
+    hwnds: Vec<HWND>,
 }
 
+/// Visible top-level windows owned by `pid`, in Z-order (topmost first).
+/// Stops at the first match unless `all` is set.
 #[cfg(target_os = "windows")]
-unsafe fn find_window_for_pid(pid: u32) -> HWND {
+unsafe fn find_windows_for_pid(pid: u32, all: bool) -> Vec<HWND> {
     let mut context = EnumContext {
         pid,
-        hwnd: HWND::default(),
+        all,
+        hwnds: Vec::new(),
     };
     let lparam = LPARAM(&mut context as *mut EnumContext as isize);
     let _ = EnumWindows(Some(enum_window_proc), lparam);
-    context.hwnd
+    context.hwnds
 }
 
 #[cfg(target_os = "windows")]
@@ -856,25 +2454,34 @@ unsafe extern "system" fn enum_window_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
     let mut pid = 0;
     let _ = windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId(hwnd, Some(&mut pid));
     if pid == context.pid && IsWindowVisible(hwnd).as_bool() {
-        context.hwnd = hwnd;
-        return BOOL(0);
+        context.hwnds.push(hwnd);
+        if !context.all {
+            return BOOL(0);
+        }
     }
     BOOL(1)
 }
 
 #[cfg(target_os = "windows")]
-struct WindowInfo {
+pub struct WindowInfo {
     title: Option<String>,
     visible: bool,
 }
 
+/// Visible top-level window info keyed by owner PID.
+#[cfg(target_os = "windows")]
+pub type WindowMap = std::collections::HashMap<u32, WindowInfo>;
+
 #[cfg(target_os = "windows")]
 struct WindowMapContext {
-    map: std::collections::HashMap<u32, WindowInfo>,
+    map: WindowMap,
 }
 
+/// The context pointer handed to `EnumWindows` only lives for this call,
+/// which runs the callback synchronously on the current thread, so the
+/// result is plain owned data that is safe to share afterwards.
 #[cfg(target_os = "windows")]
-unsafe fn get_window_map() -> std::collections::HashMap<u32, WindowInfo> {
+unsafe fn get_window_map() -> WindowMap {
     let mut context = WindowMapContext {
         map: std::collections::HashMap::new(),
     };
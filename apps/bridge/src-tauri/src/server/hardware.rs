@@ -1,6 +1,10 @@
+use crate::config::StatsConfig;
+use crate::server::types::{DiskUsage, FanSpeed, MemoryModule, MemoryUsage};
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 use std::process::Command;
+use std::sync::OnceLock;
+use sysinfo::{Disk, Disks, System};
 #[cfg(target_os = "windows")]
 use winreg::enums::*;
 #[cfg(target_os = "windows")]
@@ -9,94 +13,566 @@ use winreg::RegKey;
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-pub fn get_cpu_speed_ghz() -> f64 {
-    #[cfg(target_os = "windows")]
-    {
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        if let Ok(key) = hklm.open_subkey("HARDWARE\\DESCRIPTION\\System\\CentralProcessor\\0") {
-            if let Ok(mhz) = key.get_value::<u32, _>("~MHz") {
-                return mhz as f64 / 1000.0;
-            }
+/// Rated clock speeds of the CPU in GHz, 0.0 where unknown.
+#[derive(Clone, Copy)]
+pub struct CpuSpeeds {
+    pub base: f64,
+    pub max: f64,
+    /// Set when the speeds come from a table of known chips rather than the OS
+    pub approximate: bool,
+}
+
+/// Base and max clock speeds. These don't change while running, so they're
+/// read once.
+pub fn get_cpu_speeds() -> CpuSpeeds {
+    static SPEEDS: OnceLock<CpuSpeeds> = OnceLock::new();
+    *SPEEDS.get_or_init(read_cpu_speeds)
+}
+
+/// Current clock speed in GHz: the average of the per-core frequencies from
+/// an already-refreshed `System`, or the base speed where the OS doesn't
+/// report them (e.g. Apple Silicon).
+pub fn get_current_cpu_speed_ghz(sys: &System) -> f64 {
+    let (sum, count) = sys
+        .cpus()
+        .iter()
+        .map(|cpu| cpu.frequency())
+        .filter(|&mhz| mhz > 0)
+        .fold((0u64, 0u64), |(sum, count), mhz| (sum + mhz, count + 1));
+    if count == 0 {
+        return get_cpu_speeds().base;
+    }
+    sum as f64 / count as f64 / 1000.0
+}
+
+#[cfg(target_os = "windows")]
+fn read_cpu_speeds() -> CpuSpeeds {
+    let mut base = 0.0;
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    if let Ok(key) = hklm.open_subkey("HARDWARE\\DESCRIPTION\\System\\CentralProcessor\\0") {
+        if let Ok(mhz) = key.get_value::<u32, _>("~MHz") {
+            base = mhz as f64 / 1000.0;
         }
     }
 
-    #[cfg(any(target_os = "macos", unix))]
-    {
-        // Try hw.cpufrequency
-        let output = Command::new("sysctl")
-            .args(["-n", "hw.cpufrequency"])
+    let mut cmd = Command::new("powershell");
+    cmd.args([
+        "-NoProfile",
+        "-Command",
+        "(Get-CimInstance Win32_Processor | Select-Object -First 1).MaxClockSpeed",
+    ]);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let max = cmd
+        .output()
+        .ok()
+        .and_then(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .trim()
+                .parse::<u32>()
+                .ok()
+        })
+        .map_or(0.0, |mhz| mhz as f64 / 1000.0);
+
+    CpuSpeeds {
+        base,
+        max: max.max(base),
+        approximate: false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_speeds() -> CpuSpeeds {
+    // cpufreq reports kHz; base_frequency is only there with intel_pstate
+    let read_khz = |file: &str| {
+        std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu0/cpufreq/{}", file))
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map_or(0.0, |khz| khz as f64 / 1_000_000.0)
+    };
+    CpuSpeeds {
+        base: read_khz("base_frequency"),
+        max: read_khz("cpuinfo_max_freq"),
+        approximate: false,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_cpu_speeds() -> CpuSpeeds {
+    let sysctl = |name: &str| {
+        Command::new("sysctl")
+            .args(["-n", name])
             .output()
-            .ok();
+            .ok()
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+    };
+    let ghz = |name: &str| {
+        sysctl(name)
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|&hz| hz > 0)
+            .map_or(0.0, |hz| hz as f64 / 1_000_000_000.0)
+    };
 
-        if let Some(out) = output {
-            let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            if let Ok(hz) = s.parse::<u64>() {
-                if hz > 0 {
-                    return hz as f64 / 1_000_000_000.0;
-                }
-            }
+    // Intel Macs
+    let base = ghz("hw.cpufrequency");
+    if base > 0.0 {
+        return CpuSpeeds {
+            base,
+            max: ghz("hw.cpufrequency_max").max(base),
+            approximate: false,
+        };
+    }
+
+    // Apple Silicon doesn't report its clocks, so fall back to the peak
+    // performance-core clock of each generation
+    let brand = sysctl("machdep.cpu.brand_string")
+        .unwrap_or_default()
+        .to_lowercase();
+    let peak = [("m1", 3.2), ("m2", 3.5), ("m3", 4.0), ("m4", 4.4)]
+        .iter()
+        .find(|(chip, _)| brand.contains(chip))
+        .map(|&(_, ghz)| ghz);
+    match peak {
+        Some(ghz) => CpuSpeeds {
+            base: ghz,
+            max: ghz,
+            approximate: true,
+        },
+        None => CpuSpeeds {
+            base: 0.0,
+            max: 0.0,
+            approximate: false,
+        },
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn read_cpu_speeds() -> CpuSpeeds {
+    CpuSpeeds {
+        base: 0.0,
+        max: 0.0,
+        approximate: false,
+    }
+}
+
+/// Build a `MemoryUsage` from an already-refreshed `System`.
+/// Swap fields are zero on systems without swap.
+///
+/// `used_percent` is computed from total minus available (like `free -h`'s
+/// "available" column) rather than `used`, which on some platforms counts
+/// reclaimable cache as used.
+pub fn get_memory_usage(sys: &System) -> MemoryUsage {
+    let total = sys.total_memory();
+    let available = sys.available_memory();
+    let (cached, buffers) = get_cache_and_buffers();
+    MemoryUsage {
+        used: sys.used_memory(),
+        free: sys.free_memory(),
+        used_percent: (total.saturating_sub(available) as f64 / total as f64) * 100.0,
+        available,
+        cached,
+        buffers,
+        swap_used: sys.used_swap(),
+        swap_total: sys.total_swap(),
+    }
+}
+
+/// Page cache and buffer sizes in bytes, read from /proc/meminfo on Linux.
+/// Other platforms don't expose these separately.
+fn get_cache_and_buffers() -> (Option<u64>, Option<u64>) {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") {
+            let field = |name: &str| {
+                meminfo
+                    .lines()
+                    .find(|l| l.starts_with(name))
+                    .and_then(|l| {
+                        l[name.len()..]
+                            .trim()
+                            .trim_end_matches("kB")
+                            .trim()
+                            .parse::<u64>()
+                            .ok()
+                    })
+                    .map(|kb| kb * 1024)
+            };
+            return (field("Cached:"), field("Buffers:"));
         }
+    }
 
-        // Fallback for Apple Silicon (sysctl often returns 0 or fails for cpufrequency)
-        let brand_out = Command::new("sysctl")
-            .args(["-n", "machdep.cpu.brand_string"])
-            .output()
-            .ok();
+    (None, None)
+}
 
-        if let Some(out) = brand_out {
-            let brand = String::from_utf8_lossy(&out.stdout).to_lowercase();
-            if brand.contains("m1") {
-                return 3.2;
-            }
-            if brand.contains("m2") {
-                return 3.5;
-            }
-            if brand.contains("m3") {
-                return 4.0;
-            }
-            if brand.contains("m4") {
-                return 4.4;
-            }
+/// The disks `stats.disk_include`/`disk_exclude` let through, each device
+/// once. See `listed_entries`.
+pub fn listed_disks<'a>(disks: &'a Disks, stats: &StatsConfig) -> Vec<&'a Disk> {
+    let all: Vec<&Disk> = disks.iter().collect();
+    let entries: Vec<(String, String, String)> = all
+        .iter()
+        .map(|disk| {
+            (
+                disk.name().to_string_lossy().into_owned(),
+                disk.file_system().to_string_lossy().into_owned(),
+                disk.mount_point().to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+    listed_entries(&entries, stats, !cfg!(target_os = "windows"))
+        .into_iter()
+        .map(|i| all[i])
+        .collect()
+}
+
+/// Indexes of the `(name, file system, mount point)` entries that
+/// `stats.disk_include`/`disk_exclude` let through, in order.
+///
+/// With `merge_devices`, bind mounts and btrfs subvolumes, which show one
+/// device at several mount points, are listed once under the shortest mount
+/// point. Entries are the same device when name and file system match and
+/// the name is a device path: pseudo filesystems all share names like
+/// "tmpfs" or "overlay". Windows passes `false`, as its names are volume
+/// labels, which needn't be unique, and drive letters never repeat a volume.
+fn listed_entries(
+    entries: &[(String, String, String)],
+    stats: &StatsConfig,
+    merge_devices: bool,
+) -> Vec<usize> {
+    let mut listed: Vec<usize> = Vec::new();
+    for (i, (name, file_system, mount)) in entries.iter().enumerate() {
+        if !stats.shows_disk(mount, file_system) {
+            continue;
+        }
+        let same_device = if merge_devices && name.contains('/') {
+            listed.iter().position(|&j| {
+                let (other_name, other_file_system, _) = &entries[j];
+                other_name == name && other_file_system == file_system
+            })
+        } else {
+            None
+        };
+        match same_device {
+            Some(k) if mount.len() < entries[listed[k]].2.len() => listed[k] = i,
+            Some(_) => {}
+            None => listed.push(i),
         }
     }
+    listed
+}
 
-    0.0
+/// Where `disk` is mounted, without the trailing `\` of Windows drives.
+pub fn disk_mount(disk: &Disk) -> String {
+    disk.mount_point()
+        .to_string_lossy()
+        .trim_end_matches('\\')
+        .into()
 }
 
-pub fn get_memory_slots() -> usize {
-    #[cfg(target_os = "windows")]
-    {
-        // wmic memorychip get Capacity
-        let mut cmd = Command::new("wmic");
-        cmd.args(&["memorychip", "get", "Capacity"]);
+pub fn get_disk_usage(disk: &Disk) -> DiskUsage {
+    let used = disk.total_space() - disk.available_space();
+    DiskUsage {
+        fs: disk_mount(disk),
+        used,
+        available: disk.available_space(),
+        used_percent: (used as f64 / disk.total_space() as f64) * 100.0,
+    }
+}
 
-        cmd.creation_flags(CREATE_NO_WINDOW);
+/// 1, 5 and 15 minute load averages. Windows has no equivalent, so `None` there.
+pub fn get_load_average() -> Option<(f64, f64, f64)> {
+    if cfg!(target_os = "windows") {
+        return None;
+    }
+    let load = System::load_average();
+    Some((load.one, load.five, load.fifteen))
+}
+
+/// Fan speeds from hardware sensors, or an empty list when none are exposed.
+///
+/// - Linux: `/sys/class/hwmon/*/fan*_input`
+/// - Windows: LibreHardwareMonitor / OpenHardwareMonitor WMI sensors, when one
+///   of them is running (Windows has no built-in fan RPM source)
+/// - macOS: not available without privileged SMC access
+pub fn get_fan_speeds() -> Vec<FanSpeed> {
+    read_fan_speeds()
+}
+
+#[cfg(target_os = "linux")]
+fn read_fan_speeds() -> Vec<FanSpeed> {
+    let mut fans = Vec::new();
+    let Ok(hwmons) = std::fs::read_dir("/sys/class/hwmon") else {
+        return fans;
+    };
+    let read = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+
+    for hwmon in hwmons.flatten() {
+        let dir = hwmon.path();
+        let chip = read(dir.join("name")).unwrap_or_default();
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut inputs: Vec<String> = entries
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|n| n.starts_with("fan") && n.ends_with("_input"))
+            .collect();
+        inputs.sort();
 
-        let output = cmd.output().ok();
+        for input in inputs {
+            let Some(rpm) = read(dir.join(&input)).and_then(|s| s.parse::<u64>().ok()) else {
+                continue;
+            };
+            let fan = input.trim_end_matches("_input");
+            let label = read(dir.join(format!("{}_label", fan)))
+                .unwrap_or_else(|| format!("{} {}", chip, fan).trim().to_string());
+            fans.push(FanSpeed { label, rpm });
+        }
+    }
+    fans
+}
 
-        if let Some(out) = output {
-            let stdout = String::from_utf8_lossy(&out.stdout);
-            let lines: Vec<&str> = stdout.trim().lines().collect();
+#[cfg(target_os = "windows")]
+fn read_fan_speeds() -> Vec<FanSpeed> {
+    let script = "foreach ($ns in 'root/LibreHardwareMonitor','root/OpenHardwareMonitor') { \
+        Get-CimInstance -Namespace $ns -ClassName Sensor -Filter \"SensorType='Fan'\" -ErrorAction SilentlyContinue | \
+        ForEach-Object { \"$($_.Name)|$([int]$_.Value)\" } }";
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", script]);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    let Ok(out) = cmd.output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (label, rpm) = line.trim().rsplit_once('|')?;
+            Some(FanSpeed {
+                label: label.to_string(),
+                rpm: rpm.parse().ok()?,
+            })
+        })
+        .collect()
+}
 
-            let mut count = 0;
-            for (i, line) in lines.iter().enumerate() {
-                if i == 0 {
-                    continue;
-                } // Skip header
-                if !line.trim().is_empty() {
-                    count += 1;
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn read_fan_speeds() -> Vec<FanSpeed> {
+    Vec::new()
+}
+
+/// Populated RAM modules, or an empty list when they can't be detected
+/// (e.g. `dmidecode` needs root on Linux).
+pub fn get_memory_modules() -> Vec<MemoryModule> {
+    read_memory_modules().unwrap_or_default()
+}
+
+/// Number of populated RAM slots, or 1 when they can't be detected.
+pub fn memory_slots(modules: &[MemoryModule]) -> usize {
+    modules.len().max(1)
+}
+
+/// "8 GB", "8192 MB" -> bytes.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn parse_memory_size(text: &str) -> Option<u64> {
+    let mut parts = text.split_whitespace();
+    let value: u64 = parts.next()?.parse().ok()?;
+    let unit = match parts.next()?.to_ascii_uppercase().as_str() {
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        "TB" => 1 << 40,
+        _ => return None,
+    };
+    Some(value * unit)
+}
+
+/// `None` for the placeholders tools print for unknown values.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn known_value(text: &str) -> Option<String> {
+    let text = text.trim();
+    let unknown = [
+        "",
+        "Unknown",
+        "Not Specified",
+        "Empty",
+        "NO DIMM",
+        "Undefined",
+    ];
+    (!unknown.iter().any(|u| text.eq_ignore_ascii_case(u))).then(|| text.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn read_memory_modules() -> Option<Vec<MemoryModule>> {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct PhysicalMemory {
+        capacity: Option<u64>,
+        speed: Option<u32>,
+        manufacturer: Option<String>,
+        part_number: Option<String>,
+    }
+
+    // @() so a single module still comes back as a JSON array
+    let mut cmd = Command::new("powershell");
+    cmd.args([
+        "-NoProfile",
+        "-Command",
+        "ConvertTo-Json -Compress -InputObject @(Get-CimInstance Win32_PhysicalMemory | \
+            Select-Object Capacity, Speed, Manufacturer, PartNumber)",
+    ]);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    let out = cmd.output().ok()?;
+    let modules: Vec<PhysicalMemory> = serde_json::from_slice(&out.stdout).ok()?;
+    let text = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    Some(
+        modules
+            .into_iter()
+            .map(|m| MemoryModule {
+                size_bytes: m.capacity.unwrap_or(0),
+                speed_mhz: m.speed.filter(|&s| s > 0),
+                manufacturer: text(m.manufacturer),
+                part_number: text(m.part_number),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn read_memory_modules() -> Option<Vec<MemoryModule>> {
+    let out = Command::new("dmidecode")
+        .args(["--type", "17"])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+
+    // One "Memory Device" block per slot; empty ones have
+    // "Size: No Module Installed"
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let modules = stdout
+        .split("Memory Device")
+        .skip(1)
+        .filter_map(|block| {
+            let field = |name: &str| {
+                block.lines().find_map(|line| {
+                    let (key, value) = line.trim().split_once(':')?;
+                    (key == name).then(|| value.trim())
+                })
+            };
+            Some(MemoryModule {
+                size_bytes: parse_memory_size(field("Size")?)?,
+                // "3200 MT/s" on newer dmidecode, "3200 MHz" on older
+                speed_mhz: field("Speed").and_then(|s| s.split_whitespace().next()?.parse().ok()),
+                manufacturer: field("Manufacturer").and_then(known_value),
+                part_number: field("Part Number").and_then(known_value),
+            })
+        })
+        .collect();
+    Some(modules)
+}
+
+#[cfg(target_os = "macos")]
+fn read_memory_modules() -> Option<Vec<MemoryModule>> {
+    use serde_json::Value;
+
+    let out = Command::new("system_profiler")
+        .args(["SPMemoryDataType", "-json"])
+        .output()
+        .ok()?;
+    let json: Value = serde_json::from_slice(&out.stdout).ok()?;
+    let text = |entry: &Value, key: &str| entry.get(key)?.as_str().and_then(known_value);
+
+    let mut modules = Vec::new();
+    for bank in json.get("SPMemoryDataType")?.as_array()? {
+        match bank.get("_items").and_then(|i| i.as_array()) {
+            // Intel Macs list each slot, with "empty" for free ones
+            Some(slots) => {
+                for slot in slots {
+                    let Some(size) = text(slot, "dimm_size").and_then(|s| parse_memory_size(&s))
+                    else {
+                        continue;
+                    };
+                    modules.push(MemoryModule {
+                        size_bytes: size,
+                        speed_mhz: text(slot, "dimm_speed")
+                            .and_then(|s| s.split_whitespace().next()?.parse().ok()),
+                        manufacturer: text(slot, "dimm_manufacturer"),
+                        part_number: text(slot, "dimm_part_number"),
+                    });
                 }
             }
-            if count > 0 {
-                return count;
+            // Apple Silicon has one unified memory package
+            None => {
+                if let Some(size) =
+                    text(bank, "SPMemoryDataType").and_then(|s| parse_memory_size(&s))
+                {
+                    modules.push(MemoryModule {
+                        size_bytes: size,
+                        speed_mhz: None,
+                        manufacturer: text(bank, "dimm_manufacturer"),
+                        part_number: None,
+                    });
+                }
             }
         }
     }
+    Some(modules)
+}
 
-    #[cfg(any(target_os = "macos", unix))]
-    {
-        return 1;
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn read_memory_modules() -> Option<Vec<MemoryModule>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(list: &[(&str, &str, &str)]) -> Vec<(String, String, String)> {
+        list.iter()
+            .map(|(n, f, m)| (n.to_string(), f.to_string(), m.to_string()))
+            .collect()
     }
 
-    0
+    #[test]
+    fn lists_each_device_once() {
+        let mut stats = crate::config::AppConfig::default().stats;
+        stats.disk_exclude.clear();
+        let disks = entries(&[
+            ("/dev/nvme0n1p2", "btrfs", "/home"),
+            ("/dev/nvme0n1p2", "btrfs", "/"),
+            ("tmpfs", "tmpfs", "/run"),
+            ("tmpfs", "tmpfs", "/tmp"),
+            ("/dev/sda1", "ext4", "/mnt/data"),
+            ("/dev/sda1", "ext4", "/srv/data-bind"),
+            ("none", "overlay", "/var/lib/docker/a"),
+            ("none", "fuse.sshfs", "/mnt/remote"),
+        ]);
+
+        // Subvolumes and bind mounts collapse to the shortest mount point,
+        // pseudo filesystems sharing a name stay apart
+        assert_eq!(listed_entries(&disks, &stats, true), vec![1, 2, 3, 4, 6, 7]);
+        assert_eq!(
+            listed_entries(&disks, &stats, false),
+            (0..disks.len()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn applies_disk_filters_before_merging() {
+        let mut stats = crate::config::AppConfig::default().stats;
+        stats.disk_exclude = vec!["/".to_string()];
+        let disks = entries(&[
+            ("/dev/nvme0n1p2", "btrfs", "/"),
+            ("/dev/nvme0n1p2", "btrfs", "/home"),
+        ]);
+        assert_eq!(listed_entries(&disks, &stats, true), vec![1]);
+    }
 }
@@ -0,0 +1,79 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize)]
+pub struct HumanizeParams {
+    /// Add a `*_human` string next to each byte count.
+    #[serde(default)]
+    pub humanize: bool,
+}
+
+/// Byte counts in `memory` of `/api/system`.
+pub const SYSTEM_MEMORY_FIELDS: &[&str] = &["total"];
+/// Byte counts in each of `disks` of `/api/system`.
+pub const SYSTEM_DISK_FIELDS: &[&str] = &["size"];
+/// Byte counts in `memory` of `/api/usage`.
+pub const USAGE_MEMORY_FIELDS: &[&str] = &[
+    "used",
+    "free",
+    "available",
+    "cached",
+    "buffers",
+    "swap_used",
+    "swap_total",
+];
+/// Byte counts in each of `disks` of `/api/usage`.
+pub const USAGE_DISK_FIELDS: &[&str] = &["used", "available"];
+
+/// `bytes` with one decimal, e.g. "15.6 GB", or "14.5 GiB" when `binary`.
+pub fn format_bytes(bytes: u64, binary: bool) -> String {
+    let (base, units) = if binary {
+        (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"])
+    } else {
+        (1000.0, ["B", "KB", "MB", "GB", "TB", "PB"])
+    };
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= base && unit < units.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+/// Add `<field>_human` next to each of `memory_fields` in `body.memory` and
+/// `disk_fields` in every `body.disks` entry. Null fields (e.g. `cached`
+/// off Linux) get no human field.
+pub fn add_human_fields(
+    body: &mut Value,
+    memory_fields: &[&str],
+    disk_fields: &[&str],
+    binary: bool,
+) {
+    if let Some(memory) = body.get_mut("memory") {
+        add_to(memory, memory_fields, binary);
+    }
+    if let Some(Value::Array(disks)) = body.get_mut("disks") {
+        for disk in disks {
+            add_to(disk, disk_fields, binary);
+        }
+    }
+}
+
+fn add_to(object: &mut Value, fields: &[&str], binary: bool) {
+    let Value::Object(map) = object else {
+        return;
+    };
+    for field in fields {
+        if let Some(bytes) = map.get(*field).and_then(Value::as_u64) {
+            map.insert(
+                format!("{}_human", field),
+                Value::String(format_bytes(bytes, binary)),
+            );
+        }
+    }
+}
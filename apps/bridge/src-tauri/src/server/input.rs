@@ -0,0 +1,339 @@
+use crate::server::types::MouseButton;
+
+pub enum InputError {
+    UnknownKey(String),
+    Failed(String),
+}
+
+/// Keys that can be named in a request, besides single characters.
+#[derive(Clone, Copy)]
+enum NamedKey {
+    Enter,
+    Tab,
+    Escape,
+    Backspace,
+    Space,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// F1-F12
+    F(u8),
+    Ctrl,
+    Shift,
+    Alt,
+    Meta,
+}
+
+fn named_key(name: &str) -> Option<NamedKey> {
+    let name = name.to_lowercase();
+    Some(match name.as_str() {
+        "enter" | "return" => NamedKey::Enter,
+        "tab" => NamedKey::Tab,
+        "escape" | "esc" => NamedKey::Escape,
+        "backspace" => NamedKey::Backspace,
+        "space" => NamedKey::Space,
+        "delete" | "del" => NamedKey::Delete,
+        "home" => NamedKey::Home,
+        "end" => NamedKey::End,
+        "page_up" | "pageup" => NamedKey::PageUp,
+        "page_down" | "pagedown" => NamedKey::PageDown,
+        "up" => NamedKey::Up,
+        "down" => NamedKey::Down,
+        "left" => NamedKey::Left,
+        "right" => NamedKey::Right,
+        "ctrl" | "control" => NamedKey::Ctrl,
+        "shift" => NamedKey::Shift,
+        "alt" | "option" => NamedKey::Alt,
+        "meta" | "win" | "cmd" | "command" | "super" => NamedKey::Meta,
+        _ => {
+            let n: u8 = name.strip_prefix('f')?.parse().ok()?;
+            if !(1..=12).contains(&n) {
+                return None;
+            }
+            NamedKey::F(n)
+        }
+    })
+}
+
+/// A key from a request: a named key, or a single character.
+enum KeySpec {
+    Named(NamedKey),
+    Char(char),
+}
+
+fn parse_key(name: &str) -> Result<KeySpec, InputError> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeySpec::Char(c));
+    }
+    named_key(name)
+        .map(KeySpec::Named)
+        .ok_or_else(|| InputError::UnknownKey(name.to_string()))
+}
+
+/// Press `key` with `modifiers` held.
+pub fn key_tap(key: &str, modifiers: &[String]) -> Result<(), InputError> {
+    let key = parse_key(key)?;
+    let modifiers = modifiers
+        .iter()
+        .map(|m| named_key(m).ok_or_else(|| InputError::UnknownKey(m.clone())))
+        .collect::<Result<Vec<_>, _>>()?;
+    platform::key_tap(key, &modifiers)
+}
+
+pub fn type_text(text: &str) -> Result<(), InputError> {
+    platform::type_text(text)
+}
+
+/// Move the cursor to an absolute screen position.
+pub fn mouse_move(x: i32, y: i32) -> Result<(), InputError> {
+    platform::mouse_move(x, y)
+}
+
+pub fn click(button: MouseButton) -> Result<(), InputError> {
+    platform::click(button)
+}
+
+// Windows goes through SendInput, like the media keys.
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{InputError, KeySpec, NamedKey};
+    use crate::server::types::MouseButton;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, VkKeyScanW, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT,
+        KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+        MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP,
+        MOUSEINPUT, MOUSE_EVENT_FLAGS, VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN,
+        VK_END, VK_ESCAPE, VK_F1, VK_HOME, VK_LEFT, VK_LWIN, VK_MENU, VK_NEXT, VK_PRIOR, VK_RETURN,
+        VK_RIGHT, VK_SHIFT, VK_SPACE, VK_TAB, VK_UP,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::SetCursorPos;
+
+    fn vk(key: NamedKey) -> VIRTUAL_KEY {
+        match key {
+            NamedKey::Enter => VK_RETURN,
+            NamedKey::Tab => VK_TAB,
+            NamedKey::Escape => VK_ESCAPE,
+            NamedKey::Backspace => VK_BACK,
+            NamedKey::Space => VK_SPACE,
+            NamedKey::Delete => VK_DELETE,
+            NamedKey::Home => VK_HOME,
+            NamedKey::End => VK_END,
+            NamedKey::PageUp => VK_PRIOR,
+            NamedKey::PageDown => VK_NEXT,
+            NamedKey::Up => VK_UP,
+            NamedKey::Down => VK_DOWN,
+            NamedKey::Left => VK_LEFT,
+            NamedKey::Right => VK_RIGHT,
+            NamedKey::F(n) => VIRTUAL_KEY(VK_F1.0 + n as u16 - 1),
+            NamedKey::Ctrl => VK_CONTROL,
+            NamedKey::Shift => VK_SHIFT,
+            NamedKey::Alt => VK_MENU,
+            NamedKey::Meta => VK_LWIN,
+        }
+    }
+
+    fn keyboard(ki: KEYBDINPUT) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 { ki },
+        }
+    }
+
+    fn key_event(key: VIRTUAL_KEY, up: bool) -> INPUT {
+        keyboard(KEYBDINPUT {
+            wVk: key,
+            dwFlags: if up {
+                KEYEVENTF_KEYUP
+            } else {
+                Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    fn mouse_event(flags: MOUSE_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dwFlags: flags,
+                    ..Default::default()
+                },
+            },
+        }
+    }
+
+    fn send(inputs: &[INPUT]) -> Result<(), InputError> {
+        let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize != inputs.len() {
+            // UIPI drops input aimed at elevated windows without an error code
+            return Err(InputError::Failed(
+                "SendInput was blocked (is an elevated window focused?)".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn key_tap(key: KeySpec, modifiers: &[NamedKey]) -> Result<(), InputError> {
+        let mut held: Vec<VIRTUAL_KEY> = modifiers.iter().map(|&m| vk(m)).collect();
+        let key = match key {
+            KeySpec::Named(k) => vk(k),
+            KeySpec::Char(c) => {
+                // Low byte is the key, high byte the shift state it needs
+                let scan = unsafe { VkKeyScanW(c as u16) };
+                if scan == -1 {
+                    return Err(InputError::UnknownKey(format!(
+                        "{} (not on the current keyboard layout; send it as text)",
+                        c
+                    )));
+                }
+                let shift_state = (scan as u16) >> 8;
+                for (bit, modifier) in [(1, VK_SHIFT), (2, VK_CONTROL), (4, VK_MENU)] {
+                    if shift_state & bit != 0 && !held.contains(&modifier) {
+                        held.push(modifier);
+                    }
+                }
+                VIRTUAL_KEY(scan as u16 & 0xff)
+            }
+        };
+
+        let mut inputs: Vec<INPUT> = held.iter().map(|&m| key_event(m, false)).collect();
+        inputs.push(key_event(key, false));
+        inputs.push(key_event(key, true));
+        inputs.extend(held.iter().rev().map(|&m| key_event(m, true)));
+        send(&inputs)
+    }
+
+    pub fn type_text(text: &str) -> Result<(), InputError> {
+        let inputs: Vec<INPUT> = text
+            .encode_utf16()
+            .flat_map(|unit| {
+                [false, true].map(|up| {
+                    keyboard(KEYBDINPUT {
+                        wScan: unit,
+                        dwFlags: if up {
+                            KEYEVENTF_UNICODE | KEYEVENTF_KEYUP
+                        } else {
+                            KEYEVENTF_UNICODE
+                        },
+                        ..Default::default()
+                    })
+                })
+            })
+            .collect();
+        send(&inputs)
+    }
+
+    pub fn mouse_move(x: i32, y: i32) -> Result<(), InputError> {
+        if !unsafe { SetCursorPos(x, y) }.as_bool() {
+            return Err(InputError::Failed("SetCursorPos failed".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn click(button: MouseButton) -> Result<(), InputError> {
+        let (down, up) = match button {
+            MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
+            MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
+            MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
+        };
+        send(&[mouse_event(down), mouse_event(up)])
+    }
+}
+
+// enigo posts CGEvents on macOS and uses XTest (or libei) on Linux.
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::{InputError, KeySpec, NamedKey};
+    use crate::server::types::MouseButton;
+    use enigo::{Button, Coordinate, Direction, Enigo, Key, Keyboard, Mouse, Settings};
+
+    fn key(key: NamedKey) -> Key {
+        match key {
+            NamedKey::Enter => Key::Return,
+            NamedKey::Tab => Key::Tab,
+            NamedKey::Escape => Key::Escape,
+            NamedKey::Backspace => Key::Backspace,
+            NamedKey::Space => Key::Space,
+            NamedKey::Delete => Key::Delete,
+            NamedKey::Home => Key::Home,
+            NamedKey::End => Key::End,
+            NamedKey::PageUp => Key::PageUp,
+            NamedKey::PageDown => Key::PageDown,
+            NamedKey::Up => Key::UpArrow,
+            NamedKey::Down => Key::DownArrow,
+            NamedKey::Left => Key::LeftArrow,
+            NamedKey::Right => Key::RightArrow,
+            NamedKey::F(n) => [
+                Key::F1,
+                Key::F2,
+                Key::F3,
+                Key::F4,
+                Key::F5,
+                Key::F6,
+                Key::F7,
+                Key::F8,
+                Key::F9,
+                Key::F10,
+                Key::F11,
+                Key::F12,
+            ][n as usize - 1],
+            NamedKey::Ctrl => Key::Control,
+            NamedKey::Shift => Key::Shift,
+            NamedKey::Alt => Key::Alt,
+            NamedKey::Meta => Key::Meta,
+        }
+    }
+
+    fn enigo() -> Result<Enigo, InputError> {
+        // On macOS this fails until Cntrl has the Accessibility permission
+        Enigo::new(&Settings::default())
+            .map_err(|e| InputError::Failed(format!("Input injection unavailable: {}", e)))
+    }
+
+    fn failed(e: enigo::InputError) -> InputError {
+        InputError::Failed(format!("Input injection failed: {}", e))
+    }
+
+    pub fn key_tap(spec: KeySpec, modifiers: &[NamedKey]) -> Result<(), InputError> {
+        let mut enigo = enigo()?;
+        let target = match spec {
+            KeySpec::Named(k) => key(k),
+            KeySpec::Char(c) => Key::Unicode(c),
+        };
+
+        for &m in modifiers {
+            enigo.key(key(m), Direction::Press).map_err(failed)?;
+        }
+        let result = enigo.key(target, Direction::Click).map_err(failed);
+        // Always release, so a failed tap can't leave a modifier stuck down
+        for &m in modifiers.iter().rev() {
+            let _ = enigo.key(key(m), Direction::Release);
+        }
+        result
+    }
+
+    pub fn type_text(text: &str) -> Result<(), InputError> {
+        enigo()?.text(text).map_err(failed)
+    }
+
+    pub fn mouse_move(x: i32, y: i32) -> Result<(), InputError> {
+        enigo()?.move_mouse(x, y, Coordinate::Abs).map_err(failed)
+    }
+
+    pub fn click(button: MouseButton) -> Result<(), InputError> {
+        let button = match button {
+            MouseButton::Left => Button::Left,
+            MouseButton::Right => Button::Right,
+            MouseButton::Middle => Button::Middle,
+        };
+        enigo()?.button(button, Direction::Click).map_err(failed)
+    }
+}
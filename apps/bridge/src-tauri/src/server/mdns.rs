@@ -0,0 +1,62 @@
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+use std::net::IpAddr;
+use std::time::Duration;
+
+const SERVICE_TYPE: &str = "_cntrl._tcp.local.";
+
+/// How long `stop` waits for the goodbye packet to go out.
+const UNREGISTER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A registered `_cntrl._tcp` service. Call `stop` to withdraw it.
+pub struct Advertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+/// Advertise the bridge as `name` on `port`. An unspecified `ip` (0.0.0.0
+/// or ::) advertises every interface address.
+pub fn advertise(name: &str, ip: IpAddr, port: u16) -> Result<Advertisement, String> {
+    let daemon = ServiceDaemon::new().map_err(|e| format!("mDNS unavailable: {}", e))?;
+    let host_name = format!("{}.local.", host_label(name));
+    let properties = [("version", env!("CARGO_PKG_VERSION")), ("path", "/api")];
+
+    let info = if ip.is_unspecified() {
+        ServiceInfo::new(SERVICE_TYPE, name, &host_name, "", port, &properties[..])
+            .map(|info| info.enable_addr_auto())
+    } else {
+        ServiceInfo::new(SERVICE_TYPE, name, &host_name, ip, port, &properties[..])
+    }
+    .map_err(|e| format!("Invalid mDNS service info: {}", e))?;
+
+    let fullname = info.get_fullname().to_string();
+    daemon
+        .register(info)
+        .map_err(|e| format!("Failed to register mDNS service: {}", e))?;
+    Ok(Advertisement { daemon, fullname })
+}
+
+impl Advertisement {
+    /// Unregister the service (browsers drop it right away instead of
+    /// waiting for the record to expire) and stop the daemon. Blocks for up
+    /// to `UNREGISTER_TIMEOUT`.
+    pub fn stop(self) {
+        if let Ok(done) = self.daemon.unregister(&self.fullname) {
+            let _ = done.recv_timeout(UNREGISTER_TIMEOUT);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// A DNS label for the host name: letters, digits and hyphens only.
+fn host_label(name: &str) -> String {
+    let label: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    if label.is_empty() {
+        "cntrl".to_string()
+    } else {
+        label.to_string()
+    }
+}
@@ -1,167 +1,276 @@
 #[cfg(target_os = "windows")]
 use windows::Media::Control::{
-    GlobalSystemMediaTransportControlsSessionManager,
+    GlobalSystemMediaTransportControlsSession, GlobalSystemMediaTransportControlsSessionManager,
     GlobalSystemMediaTransportControlsSessionPlaybackStatus,
 };
 #[cfg(target_os = "windows")]
 use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
 #[cfg(target_os = "windows")]
 use windows::Win32::Media::Audio::{
-    eConsole, eRender, IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator,
+    eCapture, eConsole, eRender, EDataFlow, IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator,
 };
 #[cfg(target_os = "windows")]
 use windows::Win32::System::Com::{
     CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
 };
 
-use crate::server::types::MediaStatus;
+use crate::server::types::{MediaCapabilities, MediaSession, MediaStatus};
 
+/// Volume control of the default output (`eRender`) or input (`eCapture`) device.
 #[cfg(target_os = "windows")]
-unsafe fn get_volume_info() -> Option<(i32, bool)> {
+unsafe fn default_endpoint_volume(flow: EDataFlow) -> Result<IAudioEndpointVolume, String> {
+    // S_FALSE / RPC_E_CHANGED_MODE just mean COM is already initialized on
+    // this thread, which is fine; a real failure shows up on the next call.
     let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
     let enumerator: IMMDeviceEnumerator =
-        CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
-    let device: IMMDevice = enumerator.GetDefaultAudioEndpoint(eRender, eConsole).ok()?;
-    let volume_endpoint: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None).ok()?;
-    let vol_scalar = volume_endpoint.GetMasterVolumeLevelScalar().ok()?;
-    let muted = volume_endpoint.GetMute().ok()?.as_bool();
-    Some(((vol_scalar * 100.0) as i32, muted))
+        CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+            .map_err(com_error("CoCreateInstance(MMDeviceEnumerator)"))?;
+    let device: IMMDevice = enumerator
+        .GetDefaultAudioEndpoint(flow, eConsole)
+        .map_err(com_error("GetDefaultAudioEndpoint"))?;
+    device
+        .Activate(CLSCTX_ALL, None)
+        .map_err(com_error("Activate(IAudioEndpointVolume)"))
+}
+
+/// Formats a COM failure as `"<call> failed: <message> (HRESULT 0x...)"`.
+#[cfg(target_os = "windows")]
+fn com_error(call: &'static str) -> impl Fn(windows::core::Error) -> String {
+    move |e| {
+        format!(
+            "{} failed: {} (HRESULT {:#010x})",
+            call,
+            e.message(),
+            e.code().0 as u32
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn get_volume_info(flow: EDataFlow) -> Result<(i32, bool), String> {
+    let volume_endpoint = default_endpoint_volume(flow)?;
+    let vol_scalar = volume_endpoint
+        .GetMasterVolumeLevelScalar()
+        .map_err(com_error("GetMasterVolumeLevelScalar"))?;
+    let muted = volume_endpoint
+        .GetMute()
+        .map_err(com_error("GetMute"))?
+        .as_bool();
+    Ok(((vol_scalar * 100.0) as i32, muted))
+}
+
+#[cfg(target_os = "windows")]
+fn current_session_app_id(
+    manager: &GlobalSystemMediaTransportControlsSessionManager,
+) -> Option<String> {
+    manager
+        .GetCurrentSession()
+        .and_then(|s| s.SourceAppUserModelId())
+        .ok()
+        .map(|id| id.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn read_session(
+    session: &GlobalSystemMediaTransportControlsSession,
+    title: Option<String>,
+    artist: Option<String>,
+) -> MediaSession {
+    let status = match session.GetPlaybackInfo().and_then(|i| i.PlaybackStatus()) {
+        Ok(GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing) => "playing",
+        Ok(GlobalSystemMediaTransportControlsSessionPlaybackStatus::Paused) => "paused",
+        _ => "stopped",
+    };
+    MediaSession {
+        app_id: session
+            .SourceAppUserModelId()
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+        status: status.to_string(),
+        title: title.filter(|t| !t.is_empty()),
+        artist: artist.filter(|a| !a.is_empty()),
+    }
+}
+
+/// The session reported at the top level of `MediaStatus`: a playing one
+/// (preferring the one Windows calls current), else the current one, else
+/// the first. Windows' own pick alone is arbitrary when several apps play.
+#[cfg(target_os = "windows")]
+fn pick_active_session<'a>(
+    sessions: &'a [MediaSession],
+    current_app_id: Option<&str>,
+) -> Option<&'a MediaSession> {
+    let playing = |s: &&MediaSession| s.status == "playing";
+    let current = |s: &&MediaSession| current_app_id == Some(s.app_id.as_str());
+    sessions
+        .iter()
+        .find(|s| playing(s) && current(s))
+        .or_else(|| sessions.iter().find(playing))
+        .or_else(|| sessions.iter().find(current))
+        .or_else(|| sessions.first())
+}
+
+#[cfg(target_os = "windows")]
+fn windows_media_status(sessions: Vec<MediaSession>, current_app_id: Option<&str>) -> MediaStatus {
+    let (volume, muted) = unsafe { get_volume_info(eRender) }.ok().unzip();
+    let (input_volume, input_muted) = unsafe { get_volume_info(eCapture) }.ok().unzip();
+    let active = pick_active_session(&sessions, current_app_id).cloned();
+
+    MediaStatus {
+        status: active
+            .as_ref()
+            .map_or_else(|| "stopped".to_string(), |s| s.status.clone()),
+        volume,
+        muted,
+        playing: Some(active.as_ref().is_some_and(|s| s.status == "playing")),
+        title: active.as_ref().and_then(|s| s.title.clone()),
+        artist: active.as_ref().and_then(|s| s.artist.clone()),
+        supports_ctrl: true,
+        output_device: crate::server::audio::default_output_device_name(),
+        input_volume,
+        input_muted,
+        app_id: active.map(|s| s.app_id),
+        sessions,
+        supported_players: supported_players(),
+    }
+}
+
+/// Actions `POST /api/media/control` accepts on this platform.
+#[cfg(target_os = "windows")]
+pub const ACTIONS: &[&str] = &[
+    "play",
+    "pause",
+    "play_pause",
+    "next",
+    "prev",
+    "previous",
+    "mute",
+    "unmute",
+    "toggle_mute",
+    "set_volume",
+    "volume_up",
+    "volume_down",
+    "set_input_volume",
+    "mute_input",
+    "unmute_input",
+];
+
+/// Actions `POST /api/media/control` accepts on this platform.
+#[cfg(target_os = "macos")]
+pub const ACTIONS: &[&str] = &[
+    "play",
+    "pause",
+    "play_pause",
+    "next",
+    "prev",
+    "previous",
+    "mute",
+    "unmute",
+    "toggle_mute",
+    "set_volume",
+    "volume_up",
+    "volume_down",
+    "set_input_volume",
+    "mute_input",
+    "unmute_input",
+];
+
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+pub const ACTIONS: &[&str] = &[];
+
+/// Playback actions that can be sent to one specific session.
+#[cfg(target_os = "windows")]
+pub const SESSION_ACTIONS: &[&str] = &["play", "pause", "play_pause", "next", "prev", "previous"];
+
+pub fn capabilities() -> MediaCapabilities {
+    #[cfg(target_os = "windows")]
+    let session_actions = SESSION_ACTIONS;
+    #[cfg(not(target_os = "windows"))]
+    let session_actions: &[&str] = &[];
+
+    MediaCapabilities {
+        actions: ACTIONS.iter().map(|a| a.to_string()).collect(),
+        session_actions: session_actions.iter().map(|a| a.to_string()).collect(),
+        seek: false,
+        output_devices: crate::server::audio::list_output_devices().is_some(),
+    }
+}
+
+/// Run a playback action on the session of `app_id` rather than whichever
+/// one the media keys would reach. `None` if no session has that app id,
+/// otherwise whether the app accepted the command.
+#[cfg(target_os = "windows")]
+pub async fn control_session(app_id: &str, action: &str) -> Option<bool> {
+    let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+        .ok()?
+        .await
+        .ok()?;
+    let list = manager.GetSessions().ok()?;
+    let session = (0..list.Size().ok()?)
+        .filter_map(|i| list.GetAt(i).ok())
+        .find(|s| {
+            s.SourceAppUserModelId()
+                .map(|id| id.to_string() == app_id)
+                .unwrap_or(false)
+        })?;
+
+    let op = match action {
+        "play" => session.TryPlayAsync(),
+        "pause" => session.TryPauseAsync(),
+        "play_pause" => session.TryTogglePlayPauseAsync(),
+        "next" => session.TrySkipNextAsync(),
+        "prev" | "previous" => session.TrySkipPreviousAsync(),
+        _ => return Some(false),
+    };
+    Some(match op {
+        Ok(op) => op.await.unwrap_or(false),
+        Err(_) => false,
+    })
+}
+
+/// Names of the players media info is read from. Empty on Windows, where
+/// any app with a media session is picked up.
+pub fn supported_players() -> Vec<String> {
+    #[cfg(target_os = "macos")]
+    return MAC_PLAYERS.iter().map(|p| p.name.to_string()).collect();
+
+    #[cfg(not(target_os = "macos"))]
+    Vec::new()
 }
 
 pub async fn get_media_status() -> Option<MediaStatus> {
     #[cfg(target_os = "windows")]
     {
-        let (volume, muted) = unsafe { get_volume_info() }.unzip();
-        let mut status = "stopped".to_string();
-        let mut playing = Some(false);
-        let mut title = None;
-        let mut artist = None;
+        let mut sessions = Vec::new();
+        let mut current_app_id = None;
 
         if let Ok(manager_res) = GlobalSystemMediaTransportControlsSessionManager::RequestAsync() {
             if let Ok(manager) = manager_res.await {
-                if let Ok(session) = manager.GetCurrentSession() {
-                    if let Ok(info) = session.GetPlaybackInfo() {
-                        if let Ok(s) = info.PlaybackStatus() {
-                            status = match s {
-                                GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing => "playing".to_string(),
-                                GlobalSystemMediaTransportControlsSessionPlaybackStatus::Paused => "paused".to_string(),
-                                _ => "stopped".to_string(),
-                            };
-                        }
-                    }
-                    playing = Some(status == "playing");
-                    if let Ok(props_res) = session.TryGetMediaPropertiesAsync() {
-                        if let Ok(props) = props_res.await {
-                            if let Ok(t) = props.Title() {
-                                title = Some(t.to_string());
-                            }
-                            if let Ok(a) = props.Artist() {
-                                artist = Some(a.to_string());
+                current_app_id = current_session_app_id(&manager);
+                if let Ok(list) = manager.GetSessions() {
+                    for i in 0..list.Size().unwrap_or(0) {
+                        let Ok(session) = list.GetAt(i) else {
+                            continue;
+                        };
+                        let mut title = None;
+                        let mut artist = None;
+                        if let Ok(props_res) = session.TryGetMediaPropertiesAsync() {
+                            if let Ok(props) = props_res.await {
+                                title = props.Title().ok().map(|t| t.to_string());
+                                artist = props.Artist().ok().map(|a| a.to_string());
                             }
                         }
+                        sessions.push(read_session(&session, title, artist));
                     }
                 }
             }
         }
 
-        return Some(MediaStatus {
-            status,
-            volume,
-            muted,
-            playing,
-            title: if title.as_deref() == Some("") {
-                None
-            } else {
-                title
-            },
-            artist: if artist.as_deref() == Some("") {
-                None
-            } else {
-                artist
-            },
-            supports_ctrl: true,
-        });
+        return Some(windows_media_status(sessions, current_app_id.as_deref()));
     }
 
     #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-
-        // 1. Get Volume info
-        let vol_script = "get volume settings";
-        let vol_out = Command::new("osascript")
-            .args(["-e", vol_script])
-            .output()
-            .ok();
-        let mut volume = None;
-        let mut muted = None;
-
-        if let Some(out) = vol_out {
-            let s = String::from_utf8_lossy(&out.stdout);
-            // output volume:50, input volume:50, alert volume:50, output muted:false
-            for part in s.split(',') {
-                if part.contains("output volume:") {
-                    if let Ok(v) = part.split(':').last().unwrap_or("").trim().parse::<i32>() {
-                        volume = Some(v);
-                    }
-                }
-                if part.contains("output muted:") {
-                    muted = Some(part.contains("true"));
-                }
-            }
-        }
-
-        // 2. Get Media player info
-        let media_script = r#"
-            tell application "System Events"
-                set spotifyRunning to (name of processes) contains "Spotify"
-                set musicRunning to (name of processes) contains "Music"
-            end tell
-
-            if spotifyRunning then
-                tell application "Spotify"
-                    return "Spotify" & "||" & (player state as string) & "||" & (name of current track) & "||" & (artist of current track)
-                end tell
-            else if musicRunning then
-                tell application "Music"
-                    return "Music" & "||" & (player state as string) & "||" & (name of current track) & "||" & (artist of current track)
-                end tell
-            end if
-            return "None"
-        "#;
-
-        let media_out = Command::new("osascript")
-            .args(["-e", media_script])
-            .output()
-            .ok();
-        let mut status = "stopped".to_string();
-        let mut title = None;
-        let mut artist = None;
-        let mut playing = Some(false);
-
-        if let Some(out) = media_out {
-            let res = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            if res != "None" && !res.is_empty() {
-                let parts: Vec<&str> = res.split("||").collect();
-                if parts.len() >= 4 {
-                    status = parts[1].to_lowercase(); // playing/paused
-                    playing = Some(status == "playing");
-                    title = Some(parts[2].to_string());
-                    artist = Some(parts[3].to_string());
-                }
-            }
-        }
-
-        return Some(MediaStatus {
-            status,
-            volume,
-            muted,
-            playing,
-            title,
-            artist,
-            supports_ctrl: true,
-        });
-    }
+    return Some(mac_media_status());
 
     #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
     None
@@ -171,260 +280,427 @@ pub async fn get_media_status() -> Option<MediaStatus> {
 pub fn get_media_status_sync() -> Option<MediaStatus> {
     #[cfg(target_os = "windows")]
     {
-        let (volume, muted) = unsafe { get_volume_info() }.unzip();
-        let mut status = "stopped".to_string();
-        let mut playing = Some(false);
-        let mut title = None;
-        let mut artist = None;
+        let mut sessions = Vec::new();
+        let mut current_app_id = None;
 
         // Synchronous equivalent using .get().ok()
         if let Ok(manager) =
             GlobalSystemMediaTransportControlsSessionManager::RequestAsync().and_then(|op| op.get())
         {
-            if let Ok(session) = manager.GetCurrentSession() {
-                if let Ok(info) = session.GetPlaybackInfo() {
-                    if let Ok(s) = info.PlaybackStatus() {
-                        status = match s {
-                            GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing => {
-                                "playing".to_string()
-                            }
-                            GlobalSystemMediaTransportControlsSessionPlaybackStatus::Paused => {
-                                "paused".to_string()
-                            }
-                            _ => "stopped".to_string(),
-                        };
-                    }
-                }
-                playing = Some(status == "playing");
-
-                if let Ok(props) = session.TryGetMediaPropertiesAsync().and_then(|op| op.get()) {
-                    if let Ok(t) = props.Title() {
-                        title = Some(t.to_string());
-                    }
-                    if let Ok(a) = props.Artist() {
-                        artist = Some(a.to_string());
-                    }
+            current_app_id = current_session_app_id(&manager);
+            if let Ok(list) = manager.GetSessions() {
+                for i in 0..list.Size().unwrap_or(0) {
+                    let Ok(session) = list.GetAt(i) else {
+                        continue;
+                    };
+                    let props = session.TryGetMediaPropertiesAsync().and_then(|op| op.get());
+                    let title = props.as_ref().ok().and_then(|p| p.Title().ok());
+                    let artist = props.as_ref().ok().and_then(|p| p.Artist().ok());
+                    sessions.push(read_session(
+                        &session,
+                        title.map(|t| t.to_string()),
+                        artist.map(|a| a.to_string()),
+                    ));
                 }
             }
         }
 
-        return Some(MediaStatus {
-            status,
-            volume,
-            muted,
-            playing,
-            title: if title.as_deref() == Some("") {
-                None
-            } else {
-                title
-            },
-            artist: if artist.as_deref() == Some("") {
-                None
-            } else {
-                artist
-            },
-            supports_ctrl: true,
-        });
+        return Some(windows_media_status(sessions, current_app_id.as_deref()));
     }
 
-    // Reuse async implementation for MacOS since it uses Command (which is sync-ish anyway)
-    // Actually our macos impl uses std::process::Command which IS synchronous.
-    // The "async" wrapper was just for API consistency.
     #[cfg(target_os = "macos")]
-    {
-        // We can't call the async function from sync easily without a runtime,
-        // but the macos impl logic is completely synchronous (std::process::Command).
-        // So we can just copy-paste or extract the logic.
-        // For brevity, let's just duplicate the logic since it's short.
-
-        use std::process::Command;
-
-        // 1. Get Volume info
-        let vol_script = "get volume settings";
-        let vol_out = Command::new("osascript")
-            .args(["-e", vol_script])
-            .output()
-            .ok();
-        let mut volume = None;
-        let mut muted = None;
-
-        if let Some(out) = vol_out {
-            let s = String::from_utf8_lossy(&out.stdout);
-            for part in s.split(',') {
-                if part.contains("output volume:") {
-                    if let Ok(v) = part.split(':').last().unwrap_or("").trim().parse::<i32>() {
-                        volume = Some(v);
-                    }
-                }
-                if part.contains("output muted:") {
-                    muted = Some(part.contains("true"));
-                }
-            }
+    return Some(mac_media_status());
+
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    None
+}
+
+/// How a macOS player is reached over AppleScript.
+#[cfg(target_os = "macos")]
+enum Scripting {
+    /// The app has its own dictionary. Each script runs inside
+    /// `tell application "<name>"`; `now_playing` returns
+    /// `state||title||artist`.
+    App {
+        now_playing: &'static str,
+        play_pause: &'static str,
+        next: &'static str,
+        previous: &'static str,
+    },
+    /// A browser whose tabs are searched for a `<video>`/`<audio>` element.
+    /// `run_js` is the command that runs the JavaScript `{js}` in tab `t`.
+    /// Needs "Allow JavaScript from Apple Events" enabled in the browser.
+    Browser { run_js: &'static str },
+}
+
+#[cfg(target_os = "macos")]
+pub struct MacPlayer {
+    /// Application name, as it appears in System Events' process list.
+    pub name: &'static str,
+    scripting: Scripting,
+}
+
+/// Players media info is read from on macOS. When several are running, a
+/// playing one wins, otherwise the first in this order.
+#[cfg(target_os = "macos")]
+pub const MAC_PLAYERS: &[MacPlayer] = &[
+    MacPlayer {
+        name: "Spotify",
+        scripting: Scripting::App {
+            now_playing: r#"return (player state as string) & "||" & (name of current track) & "||" & (artist of current track)"#,
+            play_pause: "playpause",
+            next: "next track",
+            previous: "previous track",
+        },
+    },
+    MacPlayer {
+        name: "Music",
+        scripting: Scripting::App {
+            now_playing: r#"return (player state as string) & "||" & (name of current track) & "||" & (artist of current track)"#,
+            play_pause: "playpause",
+            next: "next track",
+            previous: "previous track",
+        },
+    },
+    MacPlayer {
+        name: "VLC",
+        scripting: Scripting::App {
+            now_playing: r#"return (if playing then "playing" else "paused") & "||" & (name of current item) & "||""#,
+            play_pause: "play",
+            next: "next",
+            previous: "previous",
+        },
+    },
+    MacPlayer {
+        name: "Safari",
+        scripting: Scripting::Browser {
+            run_js: "do JavaScript {js} in t",
+        },
+    },
+    MacPlayer {
+        name: "Google Chrome",
+        scripting: Scripting::Browser {
+            run_js: "execute t javascript {js}",
+        },
+    },
+];
+
+/// Finds the first media element in the page that has something loaded.
+#[cfg(target_os = "macos")]
+const BROWSER_MEDIA_JS: &str =
+    "var m = Array.from(document.querySelectorAll('video, audio')).find(function (e) { return e.currentSrc; });";
+
+#[cfg(target_os = "macos")]
+const BROWSER_NOW_PLAYING_JS: &str = "if (!m) return ''; \
+     var d = navigator.mediaSession && navigator.mediaSession.metadata; \
+     return (m.paused ? 'paused' : 'playing') + '||' + ((d && d.title) || document.title) + '||' + ((d && d.artist) || '');";
+
+#[cfg(target_os = "macos")]
+const BROWSER_PLAY_PAUSE_JS: &str =
+    "if (!m) return ''; if (m.paused) { m.play(); } else { m.pause(); } return 'ok';";
+
+/// Runs `js` in each tab of the browser until one returns something, and
+/// returns that.
+#[cfg(target_os = "macos")]
+fn browser_script(run_js: &str, js: &str) -> String {
+    let js = format!("(function () {{ {} {} }})()", BROWSER_MEDIA_JS, js);
+    format!(
+        r#"repeat with w in windows
+    repeat with t in tabs of w
+        set r to {}
+        if r is not missing value and r is not "" then return r
+    end repeat
+end repeat
+return """#,
+        run_js.replace("{js}", &format!("{:?}", js))
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn tell(player: &MacPlayer, script: &str) -> Result<String, String> {
+    osascript(&format!(
+        "tell application \"{}\"\n{}\nend tell",
+        player.name, script
+    ))
+}
+
+/// Supported players that are currently running, without launching any.
+#[cfg(target_os = "macos")]
+fn running_players() -> Vec<&'static MacPlayer> {
+    let processes = osascript(r#"tell application "System Events" to get name of every process"#)
+        .unwrap_or_default();
+    let processes: Vec<&str> = processes.split(", ").collect();
+    MAC_PLAYERS
+        .iter()
+        .filter(|p| processes.contains(&p.name))
+        .collect()
+}
+
+/// What `player` has loaded, or `None` if nothing (or it can't be scripted).
+#[cfg(target_os = "macos")]
+fn read_player(player: &MacPlayer) -> Option<MediaSession> {
+    let out = match &player.scripting {
+        Scripting::App { now_playing, .. } => tell(player, now_playing),
+        Scripting::Browser { run_js } => {
+            tell(player, &browser_script(run_js, BROWSER_NOW_PLAYING_JS))
         }
+    }
+    .ok()?;
+    let mut parts = out.splitn(3, "||");
+    let status = parts.next().filter(|s| !s.is_empty())?.to_lowercase();
+    let title = parts.next().map(str::to_string);
+    let artist = parts.next().map(str::to_string);
+    Some(MediaSession {
+        app_id: player.name.to_string(),
+        status,
+        title: title.filter(|t| !t.is_empty()),
+        artist: artist.filter(|a| !a.is_empty()),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn mac_sessions() -> Vec<MediaSession> {
+    running_players()
+        .into_iter()
+        .filter_map(read_player)
+        .collect()
+}
 
-        // 2. Get Media player info
-        let media_script = r#"
-            tell application "System Events"
-                set spotifyRunning to (name of processes) contains "Spotify"
-                set musicRunning to (name of processes) contains "Music"
-            end tell
-            if spotifyRunning then
-                tell application "Spotify"
-                    return "Spotify" & "||" & (player state as string) & "||" & (name of current track) & "||" & (artist of current track)
-                end tell
-            else if musicRunning then
-                tell application "Music"
-                    return "Music" & "||" & (player state as string) & "||" & (name of current track) & "||" & (artist of current track)
-                end tell
-            end if
-            return "None"
-        "#;
-
-        let media_out = Command::new("osascript")
-            .args(["-e", media_script])
-            .output()
-            .ok();
-        let mut status = "stopped".to_string();
-        let mut title = None;
-        let mut artist = None;
-        let mut playing = Some(false);
-
-        if let Some(out) = media_out {
-            let res = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            if res != "None" && !res.is_empty() {
-                let parts: Vec<&str> = res.split("||").collect();
-                if parts.len() >= 4 {
-                    status = parts[1].to_lowercase();
-                    playing = Some(status == "playing");
-                    title = Some(parts[2].to_string());
-                    artist = Some(parts[3].to_string());
+/// A playing session, else the first one (in `MAC_PLAYERS` order).
+#[cfg(target_os = "macos")]
+fn pick_mac_session(sessions: &[MediaSession]) -> Option<&MediaSession> {
+    sessions
+        .iter()
+        .find(|s| s.status == "playing")
+        .or_else(|| sessions.first())
+}
+
+#[cfg(target_os = "macos")]
+fn mac_media_status() -> MediaStatus {
+    let mut volume = None;
+    let mut muted = None;
+    let mut input_volume = None;
+
+    // output volume:50, input volume:50, alert volume:50, output muted:false
+    if let Ok(s) = osascript("get volume settings") {
+        for part in s.split(',') {
+            if part.contains("output volume:") {
+                if let Ok(v) = part.split(':').last().unwrap_or("").trim().parse::<i32>() {
+                    volume = Some(v);
+                }
+            }
+            if part.contains("output muted:") {
+                muted = Some(part.contains("true"));
+            }
+            if part.contains("input volume:") {
+                if let Ok(v) = part.split(':').last().unwrap_or("").trim().parse::<i32>() {
+                    input_volume = Some(v);
                 }
             }
         }
+    }
 
-        Some(MediaStatus {
-            status,
-            volume,
-            muted,
-            playing,
-            title,
-            artist,
-            supports_ctrl: true,
-        })
+    // macOS has no input mute, so a muted mic is one at zero volume
+    let input_muted = input_volume.map(|v| v == 0);
+
+    let sessions = mac_sessions();
+    let active = pick_mac_session(&sessions).cloned();
+
+    MediaStatus {
+        status: active
+            .as_ref()
+            .map_or_else(|| "stopped".to_string(), |s| s.status.clone()),
+        volume,
+        muted,
+        playing: Some(active.as_ref().is_some_and(|s| s.status == "playing")),
+        title: active.as_ref().and_then(|s| s.title.clone()),
+        artist: active.as_ref().and_then(|s| s.artist.clone()),
+        supports_ctrl: true,
+        output_device: crate::server::audio::default_output_device_name(),
+        input_volume,
+        input_muted,
+        app_id: active.map(|s| s.app_id),
+        sessions,
+        supported_players: supported_players(),
     }
+}
 
-    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
-    None
+/// Why `run_media_action` didn't reach a player.
+#[cfg(target_os = "macos")]
+pub enum PlayerError {
+    /// None of the supported players is running
+    NoPlayer,
+    /// The player can't take this action, e.g. next track in a browser
+    Unsupported,
+    Failed(String),
 }
 
+/// Sends a playback action to the player `get_media_status` reports.
+/// Browsers only support play/pause.
 #[cfg(target_os = "macos")]
-pub async fn run_media_action(action: &str) -> Option<()> {
-    use std::process::Command;
-    let script = match action {
-        "play" | "pause" | "play_pause" | "toggle_mute" => {
-            r#"
-            tell application "System Events"
-                set spotifyRunning to (name of processes) contains "Spotify"
-                set musicRunning to (name of processes) contains "Music"
-            end tell
-            if spotifyRunning then
-                tell application "Spotify" to playpause
-            else if musicRunning then
-                tell application "Music" to playpause
-            end if
-            "#
+pub async fn run_media_action(action: &str) -> Result<(), PlayerError> {
+    let sessions = mac_sessions();
+    let player = pick_mac_session(&sessions)
+        .and_then(|active| MAC_PLAYERS.iter().find(|p| p.name == active.app_id))
+        .ok_or(PlayerError::NoPlayer)?;
+
+    let script = match (&player.scripting, action) {
+        (Scripting::App { play_pause, .. }, "play" | "pause" | "play_pause") => {
+            play_pause.to_string()
         }
-        "next" => {
-            r#"
-            tell application "System Events"
-                set spotifyRunning to (name of processes) contains "Spotify"
-                set musicRunning to (name of processes) contains "Music"
-            end tell
-            if spotifyRunning then
-                tell application "Spotify" to next track
-            else if musicRunning then
-                tell application "Music" to next track
-            end if
-            "#
+        (Scripting::App { next, .. }, "next") => next.to_string(),
+        (Scripting::App { previous, .. }, "prev" | "previous") => previous.to_string(),
+        (Scripting::Browser { run_js }, "play" | "pause" | "play_pause") => {
+            browser_script(run_js, BROWSER_PLAY_PAUSE_JS)
         }
-        "prev" | "previous" => {
-            r#"
-            tell application "System Events"
-                set spotifyRunning to (name of processes) contains "Spotify"
-                set musicRunning to (name of processes) contains "Music"
-            end tell
-            if spotifyRunning then
-                tell application "Spotify" to previous track
-            else if musicRunning then
-                tell application "Music" to previous track
-            end if
-            "#
-        }
-        "volume_up" => "set volume output volume ((output volume of (get volume settings)) + 5)",
-        "volume_down" => "set volume output volume ((output volume of (get volume settings)) - 5)",
-        _ => return None,
+        _ => return Err(PlayerError::Unsupported),
     };
 
-    Command::new("osascript")
-        .args(["-e", script])
-        .status()
-        .ok()?;
-    Some(())
+    tell(player, &script).map_err(PlayerError::Failed)?;
+    Ok(())
 }
 
 #[cfg(target_os = "windows")]
-pub unsafe fn set_volume(volume: i32) -> Option<()> {
-    let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
-    let enumerator: IMMDeviceEnumerator =
-        CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
-    let device: IMMDevice = enumerator.GetDefaultAudioEndpoint(eRender, eConsole).ok()?;
-    let volume_endpoint: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None).ok()?;
+pub unsafe fn set_volume(volume: i32) -> Result<(), String> {
+    set_endpoint_volume(eRender, volume)
+}
+
+#[cfg(target_os = "windows")]
+pub unsafe fn set_input_volume(volume: i32) -> Result<(), String> {
+    set_endpoint_volume(eCapture, volume)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn set_endpoint_volume(flow: EDataFlow, volume: i32) -> Result<(), String> {
+    let volume_endpoint = default_endpoint_volume(flow)?;
     let vol_scalar = (volume as f32) / 100.0;
     volume_endpoint
         .SetMasterVolumeLevelScalar(vol_scalar, std::ptr::null())
-        .ok()?;
-    Some(())
+        .map_err(com_error("SetMasterVolumeLevelScalar"))
 }
 
-#[cfg(target_os = "macos")]
-pub unsafe fn set_volume(volume: i32) -> Option<()> {
-    let script = format!("set volume output volume {}", volume);
-    std::process::Command::new("osascript")
-        .args(["-e", &script])
-        .status()
-        .ok()?;
-    Some(())
+/// Move the output volume by `delta` percentage points, clamped to 0-100.
+#[cfg(target_os = "windows")]
+pub unsafe fn step_volume(delta: i32) -> Result<(), String> {
+    let volume_endpoint = default_endpoint_volume(eRender)?;
+    let current = volume_endpoint
+        .GetMasterVolumeLevelScalar()
+        .map_err(com_error("GetMasterVolumeLevelScalar"))?;
+    let current = (current * 100.0).round() as i32;
+    set_volume((current + delta).clamp(0, 100))
 }
 
 #[cfg(target_os = "windows")]
-pub unsafe fn set_mute(mute: bool) -> Option<()> {
-    let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
-    let enumerator: IMMDeviceEnumerator =
-        CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
-    let device: IMMDevice = enumerator.GetDefaultAudioEndpoint(eRender, eConsole).ok()?;
-    let volume_endpoint: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None).ok()?;
-    volume_endpoint.SetMute(mute, std::ptr::null()).ok()?;
-    Some(())
+pub unsafe fn set_mute(mute: bool) -> Result<(), String> {
+    set_endpoint_mute(eRender, mute)
+}
+
+#[cfg(target_os = "windows")]
+pub unsafe fn set_input_mute(mute: bool) -> Result<(), String> {
+    set_endpoint_mute(eCapture, mute)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn set_endpoint_mute(flow: EDataFlow, mute: bool) -> Result<(), String> {
+    let volume_endpoint = default_endpoint_volume(flow)?;
+    volume_endpoint
+        .SetMute(mute, std::ptr::null())
+        .map_err(com_error("SetMute"))
+}
+
+/// Run an AppleScript snippet and return its trimmed output.
+#[cfg(target_os = "macos")]
+fn osascript(script: &str) -> Result<String, String> {
+    let out = std::process::Command::new("osascript")
+        .args(["-e", script])
+        .output()
+        .map_err(|e| format!("Failed to execute osascript: {}", e))?;
+    if !out.status.success() {
+        return Err(format!(
+            "osascript failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+pub unsafe fn set_volume(volume: i32) -> Result<(), String> {
+    osascript(&format!("set volume output volume {}", volume)).map(|_| ())
 }
 
 #[cfg(target_os = "macos")]
-pub unsafe fn set_mute(mute: bool) -> Option<()> {
-    let script = format!(
+pub unsafe fn set_mute(mute: bool) -> Result<(), String> {
+    osascript(&format!(
         "set volume output muted {}",
         if mute { "true" } else { "false" }
-    );
-    std::process::Command::new("osascript")
-        .args(["-e", &script])
-        .status()
-        .ok()?;
-    Some(())
+    ))
+    .map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+pub unsafe fn toggle_mute() -> Result<(), String> {
+    osascript("set volume output muted not (output muted of (get volume settings))").map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+pub unsafe fn step_volume(delta: i32) -> Result<(), String> {
+    let current = osascript("output volume of (get volume settings)")?;
+    let current = current
+        .parse::<i32>()
+        .map_err(|_| format!("Unexpected output volume \"{}\"", current))?;
+    set_volume((current + delta).clamp(0, 100))
+}
+
+#[cfg(target_os = "macos")]
+pub unsafe fn set_input_volume(volume: i32) -> Result<(), String> {
+    osascript(&format!("set volume input volume {}", volume)).map(|_| ())
+}
+
+/// Input level to restore on `unmute_input`, since macOS has no input mute.
+#[cfg(target_os = "macos")]
+static INPUT_VOLUME_BEFORE_MUTE: std::sync::atomic::AtomicI32 =
+    std::sync::atomic::AtomicI32::new(50);
+
+/// macOS has no input mute flag, so muting drops the input volume to zero
+/// and unmuting restores the level it had before.
+#[cfg(target_os = "macos")]
+pub unsafe fn set_input_mute(mute: bool) -> Result<(), String> {
+    use std::sync::atomic::Ordering;
+
+    if mute {
+        let current = osascript("input volume of (get volume settings)")?;
+        if let Ok(v) = current.parse::<i32>() {
+            if v > 0 {
+                INPUT_VOLUME_BEFORE_MUTE.store(v, Ordering::Relaxed);
+            }
+        }
+        set_input_volume(0)
+    } else {
+        set_input_volume(INPUT_VOLUME_BEFORE_MUTE.load(Ordering::Relaxed))
+    }
 }
 
 #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
-pub unsafe fn set_volume(_volume: i32) -> Option<()> {
-    None
+const UNSUPPORTED: &str = "Volume control is not supported on this platform";
+
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+pub unsafe fn set_volume(_volume: i32) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
 }
 #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
-pub unsafe fn set_mute(_mute: bool) -> Option<()> {
-    None
+pub unsafe fn step_volume(_delta: i32) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
+}
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+pub unsafe fn set_mute(_mute: bool) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
+}
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+pub unsafe fn set_input_volume(_volume: i32) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
+}
+#[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+pub unsafe fn set_input_mute(_mute: bool) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
 }
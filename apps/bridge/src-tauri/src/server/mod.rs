@@ -9,19 +9,42 @@ use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 use sysinfo::{Disks, Networks, System};
 use tokio::task::JoinHandle;
-use tower_http::cors::CorsLayer;
+use tower_http::compression::predicate::{DefaultPredicate, Predicate};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowHeaders, AllowOrigin, Any, CorsLayer};
 use tower_http::trace::TraceLayer;
+use tracing::{debug, error, info, warn};
 
 use crate::config::AppConfig;
 use crate::auth_scopes::{self, AuthContext, SCOPE_ADMIN};
-use crate::auth_store::{self, AuthMode};
-
+use crate::auth_store::{self, AuthMode, KeyLookup};
+
+pub mod audio;
+pub mod autostart;
+pub mod client_history;
+pub mod discovery;
+pub mod disk_layout;
+pub mod display;
+pub mod error;
+pub mod features;
 pub mod gpu;
 pub mod handlers;
 pub mod hardware;
+pub mod humanize;
+pub mod input;
+pub mod mdns;
 pub mod media;
 pub mod process;
+pub mod process_control;
+pub mod process_stream;
+pub mod refresh;
+pub mod resume;
+pub mod screenshot;
+pub mod send_queue;
+pub mod smart;
+pub mod stats_delta;
 pub mod types;
+pub mod usage_history;
 pub mod ws;
 
 /// Manages lazy-spawned monitoring loops.
@@ -65,36 +88,53 @@ impl LoopManager {
         if is_stats_topic {
             let mut handle = self.stats_handle.lock().unwrap();
             if handle.as_ref().map_or(true, |h| h.is_finished()) {
-                println!("[LoopManager] Starting stats loop");
-                *handle = Some(spawn_stats_loop(state));
+                debug!("Starting stats loop");
+                *handle = Some(supervise("stats", state, spawn_stats_loop));
             }
         } else if is_media_topic {
             let mut handle = self.media_handle.lock().unwrap();
             if handle.as_ref().map_or(true, |h| h.is_finished()) {
-                println!("[LoopManager] Starting media loop");
-                *handle = Some(spawn_media_loop(state));
+                debug!("Starting media loop");
+                *handle = Some(supervise("media", state, spawn_media_loop));
             }
         } else if is_processes_topic {
             let mut handle = self.processes_handle.lock().unwrap();
             if handle.as_ref().map_or(true, |h| h.is_finished()) {
-                println!("[LoopManager] Starting processes loop");
-                *handle = Some(spawn_processes_loop(state));
+                debug!("Starting processes loop");
+                *handle = Some(supervise("processes", state, spawn_processes_loop));
             }
         }
     }
 
-    /// Abort all running monitoring loops immediately.
-    pub fn abort_all(&self) {
+    /// Which monitoring loops currently have a live task, as
+    /// `(stats, media, processes)`.
+    pub fn running_loops(&self) -> (bool, bool, bool) {
+        let is_running = |h: &Mutex<Option<JoinHandle<()>>>| {
+            h.lock()
+                .unwrap()
+                .as_ref()
+                .map_or(false, |h| !h.is_finished())
+        };
+        (
+            is_running(&self.stats_handle),
+            is_running(&self.media_handle),
+            is_running(&self.processes_handle),
+        )
+    }
+
+    /// Abort all running monitoring loops immediately. Called on server
+    /// shutdown so loops never outlive the server that spawned them.
+    pub fn stop_all(&self) {
         if let Some(h) = self.stats_handle.lock().unwrap().take() {
-            println!("[LoopManager] Aborting stats loop (shutdown)");
+            debug!("Aborting stats loop (shutdown)");
             h.abort();
         }
         if let Some(h) = self.media_handle.lock().unwrap().take() {
-            println!("[LoopManager] Aborting media loop (shutdown)");
+            debug!("Aborting media loop (shutdown)");
             h.abort();
         }
         if let Some(h) = self.processes_handle.lock().unwrap().take() {
-            println!("[LoopManager] Aborting processes loop (shutdown)");
+            debug!("Aborting processes loop (shutdown)");
             h.abort();
         }
     }
@@ -144,7 +184,7 @@ impl LoopManager {
                 drop(topics); // Release lock before acquiring another
                 let mut handle = self.stats_handle.lock().unwrap();
                 if let Some(h) = handle.take() {
-                    println!("[LoopManager] Stopping stats loop");
+                    debug!("Stopping stats loop");
                     h.abort();
                 }
             }
@@ -157,7 +197,7 @@ impl LoopManager {
                 drop(topics);
                 let mut handle = self.media_handle.lock().unwrap();
                 if let Some(h) = handle.take() {
-                    println!("[LoopManager] Stopping media loop");
+                    debug!("Stopping media loop");
                     h.abort();
                 }
             }
@@ -170,7 +210,7 @@ impl LoopManager {
                 drop(topics);
                 let mut handle = self.processes_handle.lock().unwrap();
                 if let Some(h) = handle.take() {
-                    println!("[LoopManager] Stopping processes loop");
+                    debug!("Stopping processes loop");
                     h.abort();
                 }
             }
@@ -178,10 +218,103 @@ impl LoopManager {
     }
 }
 
+/// Shortest and longest wait before restarting a loop that panicked. The
+/// wait doubles on each consecutive panic.
+const LOOP_RESTART_MIN_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+const LOOP_RESTART_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Aborts the task when dropped.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Run the loop `spawn` starts and, if it panics, log why and start it
+/// again after a backoff. A loop that returns on its own is left stopped.
+/// Aborting the returned handle stops the loop too.
+fn supervise(
+    name: &'static str,
+    state: Arc<handlers::AppState>,
+    spawn: fn(Arc<handlers::AppState>) -> JoinHandle<()>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut delay = LOOP_RESTART_MIN_DELAY;
+        loop {
+            let started = std::time::Instant::now();
+            let mut task = AbortOnDrop(spawn(state.clone()));
+            let panic = match (&mut task.0).await {
+                Err(e) if e.is_panic() => e.into_panic(),
+                _ => break,
+            };
+            let cause = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+
+            // A loop that ran fine for a while before failing starts over
+            // with the short delay
+            if started.elapsed() > LOOP_RESTART_MAX_DELAY {
+                delay = LOOP_RESTART_MIN_DELAY;
+            }
+            error!(
+                "{} loop panicked ({}), restarting in {:?}",
+                name, cause, delay
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(LOOP_RESTART_MAX_DELAY);
+        }
+    })
+}
+
+/// How often a loop whose topic is disabled in `websocket` checks whether
+/// it's been turned back on. Loops idle rather than exit: the manager only
+/// starts one when a topic gains its first subscriber, so an exited loop
+/// would stay down for clients already subscribed when the topic returns.
+const DISABLED_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// What a monitoring loop does next.
+#[derive(Debug, PartialEq)]
+enum LoopStep {
+    /// The topic is disabled: check again after `DISABLED_POLL_INTERVAL`
+    Disabled,
+    /// Wait this long, then collect and broadcast
+    Run(std::time::Duration),
+}
+
+/// The enabled/idle decision shared by the monitoring loops.
+#[derive(Default)]
+struct LoopGate {
+    running: bool,
+}
+
+impl LoopGate {
+    /// The first tick after the loop starts, or after its topic is turned
+    /// back on, runs at once so subscribers get data straight away.
+    fn step(&mut self, enabled: bool, interval_ms: u64) -> LoopStep {
+        if !enabled {
+            self.running = false;
+            return LoopStep::Disabled;
+        }
+        let wait = if self.running {
+            std::time::Duration::from_millis(interval_ms)
+        } else {
+            std::time::Duration::ZERO
+        };
+        self.running = true;
+        LoopStep::Run(wait)
+    }
+}
+
 /// Spawn the stats monitoring loop. Returns a JoinHandle that can be aborted.
 fn spawn_stats_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
     tokio::spawn(async move {
-        let mut first_run = true;
+        // Lives and dies with this loop
+        let _gpu_loop = AbortOnDrop(spawn_gpu_loop(state.clone()));
+        let mut gate = LoopGate::default();
 
         loop {
             // Read interval from config each iteration (allows runtime changes)
@@ -193,16 +326,14 @@ fn spawn_stats_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
                 )
             };
 
-            if !enabled {
-                // Loop exits gracefully if disabled
-                break;
-            }
-
-            // Sleep AFTER first iteration so subscribers get instant data
-            if !first_run {
-                tokio::time::sleep(std::time::Duration::from_millis(interval_ms)).await;
+            match gate.step(enabled, interval_ms) {
+                LoopStep::Disabled => {
+                    tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+                    continue;
+                }
+                LoopStep::Run(wait) if !wait.is_zero() => tokio::time::sleep(wait).await,
+                LoopStep::Run(_) => {}
             }
-            first_run = false;
 
             // Check if still have subscribers
             let receiver_count = state.broadcast_tx.receiver_count();
@@ -238,95 +369,24 @@ fn spawn_stats_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
                 continue;
             }
 
+            // Refreshing sysinfo and running nvidia-smi can take a while, so
+            // keep it off the async workers
             let payload = {
-                let mut sys = state.system.lock().unwrap();
-                if need_cpu {
-                    sys.refresh_cpu();
-                }
-                if need_mem {
-                    sys.refresh_memory();
-                }
-
-                let mut networks = state.networks.lock().unwrap();
-                if need_net {
-                    networks.refresh();
-                }
-
-                types::StreamPayload {
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64,
-                    uptime: System::uptime(),
-                    cpu: if need_cpu {
-                        Some(types::CpuUsage {
-                            current_load: sys.global_cpu_info().cpu_usage() as f64,
-                            current_temp: 0.0,
-                            current_speed: crate::server::hardware::get_cpu_speed_ghz(),
-                        })
-                    } else {
-                        None
-                    },
-                    memory: if need_mem {
-                        Some(types::MemoryUsage {
-                            used: sys.used_memory(),
-                            free: sys.free_memory(),
-                            used_percent: (sys.used_memory() as f64 / sys.total_memory() as f64)
-                                * 100.0,
-                        })
-                    } else {
-                        None
-                    },
-                    gpu: if need_gpu {
-                        handlers::get_or_update_gpu_stats(&state).map(|g| types::GpuUsage {
-                            current_load: g.load_percent,
-                            current_temp: g.temp_c,
-                            current_memory: g.vram_used_mb,
-                        })
-                    } else {
-                        None
-                    },
-                    disks: if need_disks {
-                        let mut disks_lock = state.disks.lock().unwrap();
-                        disks_lock.refresh_list();
-                        Some(
-                            disks_lock
-                                .iter()
-                                .map(|d| types::DiskUsage {
-                                    fs: d
-                                        .mount_point()
-                                        .to_string_lossy()
-                                        .trim_end_matches('\\')
-                                        .into(),
-                                    used: d.total_space() - d.available_space(),
-                                    available: d.available_space(),
-                                    used_percent: ((d.total_space() - d.available_space()) as f64
-                                        / d.total_space() as f64)
-                                        * 100.0,
-                                })
-                                .collect(),
-                        )
-                    } else {
-                        None
-                    },
-                    network: if need_net {
-                        let mut sent: u64 = 0;
-                        let mut recv: u64 = 0;
-                        for (_, network) in networks.iter() {
-                            sent += network.total_transmitted();
-                            recv += network.total_received();
-                        }
-                        Some(types::NetworkUsage {
-                            bytes_sent: sent,
-                            bytes_recv: recv,
-                        })
-                    } else {
-                        None
-                    },
-                    media: None,
+                let state = state.clone();
+                tokio::task::spawn_blocking(move || {
+                    collect_stats(&state, need_cpu, need_mem, need_gpu, need_disks, need_net)
+                })
+                .await
+            };
+            let payload = match payload {
+                Ok(payload) => payload,
+                Err(e) => {
+                    error!("Collecting stats failed: {}", e);
+                    continue;
                 }
             };
 
+            state.usage_history.lock().unwrap().record(&payload);
             let _ = state
                 .broadcast_tx
                 .send(types::BroadcastEvent::SystemStats(payload));
@@ -334,11 +394,152 @@ fn spawn_stats_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
     })
 }
 
+/// How often the GPU loop checks whether the cached reading has expired.
+const GPU_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Keep `gpu_cache` fresh while anyone subscribes to GPU stats, refreshing it
+/// every `stats.gpu_cache_seconds`. The stats loop only reads the cache, so a
+/// slow `nvidia-smi` never delays CPU and memory samples.
+fn spawn_gpu_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let wanted = {
+                let topics = state.active_topics.lock().unwrap();
+                ["gpu", "stats.gpu", "stats"]
+                    .iter()
+                    .any(|t| topics.get(*t).is_some_and(|n| *n > 0))
+            };
+            if wanted {
+                let state = state.clone();
+                let _ =
+                    tokio::task::spawn_blocking(move || handlers::get_or_update_gpu_stats(&state))
+                        .await;
+            }
+            tokio::time::sleep(GPU_POLL_INTERVAL).await;
+        }
+    })
+}
+
+/// Fold `load` into the running average with weight `smoothing` and return
+/// the average, or `load` itself when smoothing is off.
+fn smooth_cpu_load(state: &handlers::AppState, load: f64, smoothing: f64) -> f64 {
+    let mut average = state.cpu_load_average.lock().unwrap();
+    if smoothing <= 0.0 {
+        *average = None;
+        return load;
+    }
+    let smoothed = match *average {
+        Some(previous) => smoothing * load + (1.0 - smoothing) * previous,
+        None => load,
+    };
+    *average = Some(smoothed);
+    smoothed
+}
+
+/// One stats tick, with only the requested parts filled in. Each lock is held
+/// just for its own refresh.
+fn collect_stats(
+    state: &Arc<handlers::AppState>,
+    need_cpu: bool,
+    need_mem: bool,
+    need_gpu: bool,
+    need_disks: bool,
+    need_net: bool,
+) -> types::StreamPayload {
+    let (display, stats) = {
+        let config = state.config.lock().unwrap();
+        (config.display.clone(), config.stats.clone())
+    };
+    let min_interval = stats.refresh_min_interval();
+    let (cpu, memory) = {
+        let mut sys = lock_system(&state.system);
+        if need_cpu && state.refresh.due(refresh::Part::Cpu, min_interval) {
+            sys.refresh_cpu();
+        }
+        if need_mem && state.refresh.due(refresh::Part::Memory, min_interval) {
+            sys.refresh_memory();
+        }
+        let cpu = if need_cpu {
+            Some(types::CpuUsage {
+                current_load: smooth_cpu_load(
+                    state,
+                    sys.global_cpu_info().cpu_usage() as f64,
+                    stats.cpu_smoothing,
+                ),
+                current_temp: 0.0,
+                temp_unit: display.temp_unit.clone(),
+                current_speed: crate::server::hardware::get_current_cpu_speed_ghz(&sys),
+                load_avg: crate::server::hardware::get_load_average(),
+            })
+        } else {
+            None
+        };
+        let memory = if need_mem {
+            Some(crate::server::hardware::get_memory_usage(&sys))
+        } else {
+            None
+        };
+        (cpu, memory)
+    };
+
+    let network = if need_net {
+        let mut networks = state.networks.lock().unwrap();
+        networks.refresh();
+        let mut sent: u64 = 0;
+        let mut recv: u64 = 0;
+        for (_, network) in networks.iter() {
+            sent += network.total_transmitted();
+            recv += network.total_received();
+        }
+        Some(types::NetworkUsage {
+            bytes_sent: sent,
+            bytes_recv: recv,
+        })
+    } else {
+        None
+    };
+
+    let disks = if need_disks {
+        let mut disks_lock = state.disks.lock().unwrap();
+        if state.refresh.due(refresh::Part::Disks, min_interval) {
+            disks_lock.refresh_list();
+        }
+        Some(
+            crate::server::hardware::listed_disks(&disks_lock, &stats)
+                .into_iter()
+                .map(crate::server::hardware::get_disk_usage)
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let gpu = if need_gpu {
+        handlers::cached_gpu_stats(state).map(|g| handlers::gpu_usage(&g, &display))
+    } else {
+        None
+    };
+
+    types::StreamPayload {
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64,
+        uptime: System::uptime(),
+        cpu,
+        memory,
+        gpu,
+        disks,
+        network,
+        media: None,
+    }
+}
+
 /// Spawn the media monitoring loop. Returns a JoinHandle that can be aborted.
 fn spawn_media_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
     tokio::spawn(async move {
         let mut last_status: Option<String> = None;
-        let mut first_run = true;
+        let mut gate = LoopGate::default();
 
         loop {
             // Read interval from config each iteration
@@ -350,15 +551,14 @@ fn spawn_media_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
                 )
             };
 
-            if !enabled {
-                break;
-            }
-
-            // Sleep AFTER first iteration so subscribers get instant data
-            if !first_run {
-                tokio::time::sleep(std::time::Duration::from_millis(interval_ms)).await;
+            match gate.step(enabled, interval_ms) {
+                LoopStep::Disabled => {
+                    tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+                    continue;
+                }
+                LoopStep::Run(wait) if !wait.is_zero() => tokio::time::sleep(wait).await,
+                LoopStep::Run(_) => {}
             }
-            first_run = false;
 
             if state.broadcast_tx.receiver_count() == 0 {
                 continue;
@@ -383,8 +583,15 @@ fn spawn_media_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
             if let Some(status) = crate::server::media::get_media_status().await {
                 // Include volume in signature so volume changes trigger updates
                 let current_sig = format!(
-                    "{:?}-{:?}-{:?}-{:?}",
-                    status.title, status.playing, status.muted, status.volume
+                    "{:?}-{:?}-{:?}-{:?}-{:?}-{:?}-{:?}-{:?}",
+                    status.title,
+                    status.playing,
+                    status.muted,
+                    status.volume,
+                    status.output_device,
+                    status.input_volume,
+                    status.input_muted,
+                    status.sessions
                 );
                 let changed = match &last_status {
                     Some(s) => s != &current_sig,
@@ -402,10 +609,32 @@ fn spawn_media_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
     })
 }
 
+/// With `coalesce` on, an unchanged process list is still re-sent every this
+/// many intervals so clients can tell the stream is alive.
+const PROCESS_KEEPALIVE_TICKS: u32 = 10;
+
+/// Cheap fingerprint of a process list: names, instance counts and memory
+/// rounded to whole MB. CPU time is left out since it changes every tick.
+fn process_list_signature(payload: &types::ProcessListPayload) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    payload.total_count.hash(&mut hasher);
+    for p in &payload.processes {
+        p.name.hash(&mut hasher);
+        p.count.hash(&mut hasher);
+        (p.memory_mb.round() as u64).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
 /// Spawn the processes monitoring loop. Returns a JoinHandle that can be aborted.
 fn spawn_processes_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
     tokio::spawn(async move {
-        let mut first_run = true;
+        let mut gate = LoopGate::default();
+        let mut last_signature: Option<u64> = None;
+        let mut last_subscribers = 0;
+        let mut unchanged_ticks = 0;
 
         loop {
             // Read interval from config each iteration
@@ -417,28 +646,27 @@ fn spawn_processes_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
                 )
             };
 
-            if !enabled {
-                break;
-            }
-
-            // Sleep AFTER first iteration so subscribers get instant data
-            if !first_run {
-                tokio::time::sleep(std::time::Duration::from_millis(interval_ms)).await;
+            match gate.step(enabled, interval_ms) {
+                LoopStep::Disabled => {
+                    tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+                    continue;
+                }
+                LoopStep::Run(wait) if !wait.is_zero() => tokio::time::sleep(wait).await,
+                LoopStep::Run(_) => {}
             }
-            first_run = false;
 
             if state.broadcast_tx.receiver_count() == 0 {
                 continue;
             }
 
             // Check demand
-            let need_processes = {
+            let subscribers = {
                 let topics = state.active_topics.lock().unwrap();
-                *topics.get("processes").unwrap_or(&0) > 0
-                    || *topics.get("process").unwrap_or(&0) > 0
+                *topics.get("processes").unwrap_or(&0) + *topics.get("process").unwrap_or(&0)
             };
 
-            if !need_processes {
+            if subscribers == 0 {
+                last_subscribers = 0;
                 continue;
             }
 
@@ -447,9 +675,25 @@ fn spawn_processes_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
                 continue;
             }
 
-            let processes = crate::server::process::get_processes_list(&state);
+            let mut processes = crate::server::process::get_processes_list(&state);
             let total_count = processes.len();
 
+            // List is already sorted by memory, so this keeps the top N
+            let (max_items, coalesce) = {
+                let config = state.config.lock().unwrap();
+                (
+                    config.websocket.processes.max_items,
+                    config.websocket.processes.coalesce,
+                )
+            };
+            let truncated = match max_items {
+                Some(max) if total_count > max => {
+                    processes.truncate(max);
+                    true
+                }
+                _ => false,
+            };
+
             let payload = types::ProcessListPayload {
                 timestamp: std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -457,8 +701,25 @@ fn spawn_processes_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
                     .as_secs() as i64,
                 processes,
                 total_count,
+                truncated,
             };
 
+            // Like the media loop's `last_status`, but new subscribers still
+            // get the current list straight away
+            let signature = process_list_signature(&payload);
+            let new_subscriber = subscribers > last_subscribers;
+            last_subscribers = subscribers;
+            if coalesce
+                && !new_subscriber
+                && last_signature == Some(signature)
+                && unchanged_ticks + 1 < PROCESS_KEEPALIVE_TICKS
+            {
+                unchanged_ticks += 1;
+                continue;
+            }
+            last_signature = Some(signature);
+            unchanged_ticks = 0;
+
             let _ = state
                 .broadcast_tx
                 .send(types::BroadcastEvent::ProcessList(payload));
@@ -466,22 +727,34 @@ fn spawn_processes_loop(state: Arc<handlers::AppState>) -> JoinHandle<()> {
     })
 }
 
+use error::ApiError;
 use handlers::*;
 use types::ServerStatus;
 use ws::ws_handler;
 
+/// Address the running server's main listener is bound to.
+static BOUND_ADDR: Mutex<Option<SocketAddr>> = Mutex::new(None);
+
+/// Where the server is actually listening, `None` while it is not running.
+pub fn bound_addr() -> Option<SocketAddr> {
+    *BOUND_ADDR.lock().unwrap()
+}
+
 pub async fn start_server(
     port: u16,
     config: Arc<Mutex<AppConfig>>,
     auth_state: Arc<Mutex<auth_store::AuthState>>,
     status_tx: Arc<tokio::sync::watch::Sender<ServerStatus>>,
     mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+    broadcast_holder: Arc<Mutex<Option<tokio::sync::broadcast::Sender<types::BroadcastEvent>>>>,
 ) {
     let loop_manager = Arc::new(LoopManager::new());
     let (ws_shutdown_tx, ws_shutdown_rx) = tokio::sync::watch::channel(false);
+    let channel_capacity = config.lock().unwrap().websocket.channel_capacity;
 
     let state = Arc::new(AppState {
         system: Arc::new(Mutex::new(System::new_all())),
+        processes: Arc::new(Mutex::new(System::new())),
         networks: Arc::new(Mutex::new({
             let mut n = Networks::new();
             n.refresh_list();
@@ -493,36 +766,97 @@ pub async fn start_server(
             d
         })),
         gpu_cache: Arc::new(Mutex::new(None)),
+        refresh: Default::default(),
+        cpu_load_average: Mutex::new(None),
+        usage_history: Mutex::new(Default::default()),
+        #[cfg(target_os = "windows")]
+        window_cache: Mutex::new(None),
+        system_info_cache: Mutex::new(None),
+        clipboard: Mutex::new(None),
         broadcast_tx: {
-            let (tx, _rx) = tokio::sync::broadcast::channel(100);
+            let (tx, _rx) = tokio::sync::broadcast::channel(channel_capacity);
             tx
         },
         active_topics: Arc::new(Mutex::new(std::collections::HashMap::new())),
+        resume_tokens: Mutex::new(Default::default()),
         config: config,
         auth_state: auth_state,
-        loop_manager: loop_manager,
+        client_history: client_history::shared(),
+        loop_manager: loop_manager.clone(),
+        status: status_tx.subscribe(),
+        scheduled_power: Arc::new(Mutex::new(std::collections::HashMap::new())),
+        next_schedule_id: std::sync::atomic::AtomicU64::new(1),
         shutdown: ws_shutdown_rx,
     });
 
+    // Forward status transitions to WS clients subscribed to "server"
+    let status_state = state.clone();
+    tokio::spawn(async move {
+        let mut status_rx = status_state.status.clone();
+        let mut shutdown = status_state.shutdown.clone();
+        loop {
+            tokio::select! {
+                changed = status_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    let status = status_rx.borrow_and_update().clone();
+                    let _ = status_state
+                        .broadcast_tx
+                        .send(types::BroadcastEvent::ServerStatus(status));
+                }
+                _ = shutdown.changed() => break,
+            }
+        }
+    });
+
     // No always-running loops! Loops are now lazy-spawned via LoopManager
     // when clients subscribe to topics.
-    println!("Server initialized with lazy loop spawning (zero CPU when idle)");
+    debug!("Server initialized with lazy loop spawning (zero CPU when idle)");
 
     // Authenticated routes
     let authed = Router::new()
         .route("/api/system", get(get_system_info))
         .route("/api/usage", get(get_system_usage))
+        .route("/api/usage/stats", get(get_usage_stats))
+        .route("/api/system/refresh", post(refresh_cache))
+        .route("/api/disks/health", get(get_disk_health))
+        .route("/api/disks/layout", get(get_disk_layout))
         .route("/api/processes", get(list_processes))
+        .route("/api/processes/gpu", get(list_gpu_processes))
         .route("/api/processes/:name", get(get_process_details))
+        .route("/api/process/:pid", get(get_process_by_pid))
         .route("/api/processes/kill", post(kill_process))
+        .route("/api/processes/priority", post(set_process_priority))
+        .route("/api/processes/suspend", post(suspend_process))
+        .route("/api/processes/resume", post(resume_process))
         .route("/api/processes/focus", post(focus_process))
         .route("/api/processes/launch", post(launch_process))
+        .route("/api/commands", get(list_commands))
+        .route("/api/commands/:id/run", post(run_command))
+        .route("/api/open", post(open_url))
+        .route("/api/clipboard", get(get_clipboard).post(set_clipboard))
+        .route("/api/screenshot", get(get_screenshot))
+        .route("/api/displays", get(list_displays))
+        .route("/api/displays/:id/brightness", post(set_display_brightness))
+        .route("/api/input", post(send_input))
+        .route("/api/autostart", get(get_autostart).post(set_autostart))
+        .route("/api/pw/scheduled", get(list_scheduled_power))
+        .route("/api/pw/cancel", post(cancel_scheduled_power))
         .route("/api/pw/:action", post(power_action))
         .route("/api/media/control", post(media_control))
         .route("/api/media/status", get(get_media_status))
+        .route("/api/media/devices", get(list_audio_devices))
+        .route("/api/media/capabilities", get(get_media_capabilities))
+        .route("/api/media/device", post(set_audio_device))
         .route("/api/stream", get(handle_stream))
         .route("/api/ws", get(ws_handler))
+        .route("/api/features", get(get_features))
+        .route("/api/capabilities", get(get_capabilities))
         .route("/api/clients", get(get_client_count))
+        .route("/api/clients/history", get(get_client_history))
+        .route("/api/debug/topics", get(get_debug_topics))
+        .route("/api/logs", get(get_logs))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -536,18 +870,25 @@ pub async fn start_server(
     let app = Router::new()
         .merge(authed)
         .merge(public)
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http());
+        .layer(axum::middleware::map_response(add_api_version_header))
+        .layer(compression_layer())
+        .layer(cors_layer(state.config.clone()))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span));
 
-    let host = {
+    let (host, local_only) = {
         let c = state.config.lock().unwrap();
-        c.server.host.clone()
+        (c.server.host.clone(), c.server.local_only)
     };
 
-    let ip_addr: std::net::IpAddr = host.parse().unwrap_or_else(|_| {
-        println!("Invalid host '{}', defaulting to 0.0.0.0", host);
-        std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0))
-    });
+    // local_only overrides host: 127.0.0.1 here, ::1 as the second listener
+    let ip_addr: std::net::IpAddr = if local_only {
+        std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST)
+    } else {
+        host.parse().unwrap_or_else(|_| {
+            warn!("Invalid host '{}', defaulting to 0.0.0.0", host);
+            std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0))
+        })
+    };
 
     // On Windows, :: is IPv6-only (IPV6_V6ONLY=true by default), so binding 0.0.0.0
     // won't accept IPv6 clients. We bind :: as a second listener to cover both.
@@ -556,38 +897,65 @@ pub async fn start_server(
     let dual_stack = cfg!(target_os = "windows")
         && ip_addr == std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0));
 
-    let addr_v4 = SocketAddr::from((ip_addr, port));
-    let listener = match tokio::net::TcpListener::bind(addr_v4).await {
+    // Binding :: directly always clears IPV6_V6ONLY so IPv4 clients can connect
+    // too, regardless of the platform default.
+    let v6_wildcard = ip_addr == std::net::IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED);
+
+    let addr = SocketAddr::from((ip_addr, port));
+    let listener = match bind_with_retry(addr, !v6_wildcard).await {
         Ok(l) => l,
         Err(e) => {
-            println!("Failed to bind port: {}", e);
+            error!("Failed to bind port: {}", e);
             status_tx.send_modify(|s| *s = ServerStatus::Error(format!("Failed to bind port {}: {}", port, e)));
             return;
         }
     };
 
-    let ipv6_listener = if dual_stack {
-        let addr_v6 = SocketAddr::from((std::net::Ipv6Addr::UNSPECIFIED, port));
-        match tokio::net::TcpListener::bind(addr_v6).await {
+    let ipv6_listener = if dual_stack || local_only {
+        let v6 = if local_only {
+            std::net::Ipv6Addr::LOCALHOST
+        } else {
+            std::net::Ipv6Addr::UNSPECIFIED
+        };
+        let addr_v6 = SocketAddr::from((v6, port));
+        match bind_with_retry(addr_v6, true).await {
             Ok(l) => {
-                println!("Server listening on {} (dual-stack)", addr_v4);
+                if local_only {
+                    info!("Server listening on {} and {} (local only)", addr, addr_v6);
+                } else {
+                    info!("Server listening on {} (dual-stack)", addr);
+                }
                 Some(l)
             }
             Err(e) => {
                 // IPv6 not available on this system — continue with IPv4 only
-                println!("IPv6 bind failed ({}), continuing with IPv4 only", e);
-                println!("Server listening on {}", addr_v4);
+                warn!("IPv6 bind failed ({}), continuing with IPv4 only", e);
+                info!("Server listening on {}", addr);
                 None
             }
         }
+    } else if v6_wildcard {
+        info!("Server listening on {} (dual-stack)", addr);
+        None
     } else {
-        println!("Server listening on {}", addr_v4);
+        info!("Server listening on {}", addr);
         None
     };
 
+    let config = state.config.lock().unwrap().clone();
+    let advertisement = start_advertising(&config, ip_addr, port);
+    let discovery = start_discovery(&config, ip_addr, port).await;
+
+    *broadcast_holder.lock().unwrap() = Some(state.broadcast_tx.clone());
+    *BOUND_ADDR.lock().unwrap() = Some(addr);
     status_tx.send_modify(|s| *s = ServerStatus::Running);
 
-    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+    // Rewrite versioned paths before routing; a Router::layer would only
+    // run after the route was already matched.
+    let app = tower::ServiceExt::<axum::extract::Request>::map_request(app, strip_api_version);
+    let app = axum::ServiceExt::<axum::extract::Request>::into_make_service_with_connect_info::<
+        SocketAddr,
+    >(app);
 
     // Broadcast shutdown to both servers
     let (inner_tx, inner_rx1) = tokio::sync::broadcast::channel::<()>(1);
@@ -598,9 +966,17 @@ pub async fn start_server(
     let shutdown_state = state.clone();
     tauri::async_runtime::spawn(async move {
         shutdown_rx.recv().await.ok();
-        println!("Server received shutdown signal");
+        info!("Server received shutdown signal");
         // Abort all monitoring loops so they stop feeding the broadcast channel
-        shutdown_state.loop_manager.abort_all();
+        shutdown_state.loop_manager.stop_all();
+        // Pending power actions belong to this server instance
+        cancel_power_actions(&shutdown_state, None);
+        // Tell "server" subscribers before their sockets close. The watch
+        // status only flips to Stopped once serve has fully returned, by which
+        // point no client is left to hear it.
+        let _ = shutdown_state
+            .broadcast_tx
+            .send(types::BroadcastEvent::ServerStatus(ServerStatus::Stopped));
         // Signal all WS connections to close
         let _ = ws_shutdown_tx.send(true);
         // Stop the HTTP listeners
@@ -608,7 +984,7 @@ pub async fn start_server(
     });
 
     let status_tx_clone = status_tx.clone();
-    if let Some(v6_listener) = ipv6_listener {
+    let serve_error = if let Some(v6_listener) = ipv6_listener {
         let app_v6 = app.clone();
         let v4_task = tauri::async_runtime::spawn(async move {
             axum::serve(listener, app)
@@ -622,23 +998,221 @@ pub async fn start_server(
         });
 
         let (r4, r6) = tokio::join!(v4_task, v6_task);
-        let err = r4.err().map(|e| e.to_string())
-            .or_else(|| r6.err().map(|e| e.to_string()));
-        if let Some(e) = err {
-            status_tx_clone.send_modify(|s| *s = ServerStatus::Error(format!("Server exited: {}", e)));
-        } else {
-            status_tx_clone.send_modify(|s| *s = ServerStatus::Stopped);
-        }
+        r4.err().map(|e| e.to_string())
+            .or_else(|| r6.err().map(|e| e.to_string()))
     } else {
-        let result = axum::serve(listener, app)
+        axum::serve(listener, app)
             .with_graceful_shutdown(async move { inner_rx1.resubscribe().recv().await.ok(); })
-            .await;
-        if let Err(e) = result {
-            status_tx_clone.send_modify(|s| *s = ServerStatus::Error(format!("Server exited: {}", e)));
-        } else {
-            status_tx_clone.send_modify(|s| *s = ServerStatus::Stopped);
+            .await
+            .err()
+            .map(|e| e.to_string())
+    };
+
+    // A subscribe that raced the shutdown signal may have respawned a loop while
+    // connections drained; make sure none survive before reporting Stopped.
+    loop_manager.stop_all();
+    *broadcast_holder.lock().unwrap() = None;
+    *BOUND_ADDR.lock().unwrap() = None;
+    if let Some(advertisement) = advertisement {
+        let _ = tokio::task::spawn_blocking(move || advertisement.stop()).await;
+    }
+    if let Some(discovery) = discovery {
+        discovery.abort();
+    }
+
+    if let Some(e) = serve_error {
+        status_tx_clone.send_modify(|s| *s = ServerStatus::Error(format!("Server exited: {}", e)));
+    } else {
+        status_tx_clone.send_modify(|s| *s = ServerStatus::Stopped);
+    }
+}
+
+/// Name the bridge is discovered under, the same one `/api/system` reports.
+fn advertised_name(config: &AppConfig) -> String {
+    config
+        .display
+        .host_name()
+        .unwrap_or_else(|| "Cntrl Bridge".to_string())
+}
+
+/// Advertise over mDNS when `server.advertise` is on and the server is
+/// reachable from other machines.
+fn start_advertising(
+    config: &AppConfig,
+    ip: std::net::IpAddr,
+    port: u16,
+) -> Option<mdns::Advertisement> {
+    if !config.server.advertise || ip.is_loopback() {
+        return None;
+    }
+    let name = advertised_name(config);
+    match mdns::advertise(&name, ip, port) {
+        Ok(advertisement) => {
+            info!("Advertising \"{}\" over mDNS", name);
+            Some(advertisement)
         }
+        Err(e) => {
+            warn!("{}", e);
+            None
+        }
+    }
+}
+
+/// Answer UDP discovery broadcasts under the same conditions as mDNS, for
+/// networks that block multicast.
+async fn start_discovery(
+    config: &AppConfig,
+    ip: std::net::IpAddr,
+    port: u16,
+) -> Option<JoinHandle<()>> {
+    if !config.server.advertise || ip.is_loopback() {
+        return None;
+    }
+    let discovery_port = config.server.discovery_port;
+    match discovery::spawn_responder(discovery_port, advertised_name(config), port).await {
+        Ok(handle) => {
+            info!(
+                "Answering discovery requests on UDP port {}",
+                discovery_port
+            );
+            Some(handle)
+        }
+        Err(e) => {
+            warn!(
+                "Failed to bind UDP discovery port {}: {}",
+                discovery_port, e
+            );
+            None
+        }
+    }
+}
+
+/// Backoff delays between bind attempts when the port is still held, e.g. by
+/// a server that is shutting down during a restart.
+const BIND_RETRY_DELAYS_MS: [u64; 3] = [100, 200, 400];
+
+/// Bind a listener, retrying with exponential backoff while the address is in
+/// use. Other errors (e.g. permission denied) fail immediately.
+async fn bind_with_retry(
+    addr: SocketAddr,
+    v6_only: bool,
+) -> std::io::Result<tokio::net::TcpListener> {
+    let mut delays = BIND_RETRY_DELAYS_MS.iter();
+    loop {
+        match bind_listener(addr, v6_only) {
+            Ok(l) => return Ok(l),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => match delays.next() {
+                Some(ms) => {
+                    warn!("Port {} in use, retrying bind in {}ms", addr.port(), ms);
+                    tokio::time::sleep(std::time::Duration::from_millis(*ms)).await;
+                }
+                None => return Err(e),
+            },
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Bind a TCP listener. `v6_only` controls IPV6_V6ONLY for IPv6 addresses and
+/// is ignored for IPv4; clearing it lets a `::` listener accept IPv4 clients as
+/// IPv4-mapped addresses.
+fn bind_listener(addr: SocketAddr, v6_only: bool) -> std::io::Result<tokio::net::TcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let domain = if addr.is_ipv6() {
+        Domain::IPV6
+    } else {
+        Domain::IPV4
+    };
+    let socket = Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(v6_only)?;
     }
+    // Match tokio's TcpListener::bind, which sets SO_REUSEADDR on Unix only
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+    tokio::net::TcpListener::from_std(socket.into())
+}
+
+/// Origins come from `server.cors_origins`, read per request so edits apply
+/// without a restart. An empty list allows any origin, as before the setting
+/// existed; requested headers are mirrored either way so clients can keep
+/// sending custom ones.
+fn cors_layer(config: Arc<Mutex<AppConfig>>) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            let origins = &config.lock().unwrap().server.cors_origins;
+            origins.is_empty() || origins.iter().any(|o| o.as_bytes() == origin.as_bytes())
+        }))
+        .allow_methods([axum::http::Method::GET, axum::http::Method::POST])
+        .allow_headers(AllowHeaders::mirror_request())
+        .expose_headers(Any)
+}
+
+/// gzip/deflate per `Accept-Encoding`. The default predicate already skips
+/// small bodies, images (screenshots are PNG) and `text/event-stream`, so SSE
+/// events are flushed as they're sent rather than buffered by the encoder.
+/// WebSocket upgrades are skipped too: a 101 has no body to compress and a
+/// `Content-Encoding` on it breaks the handshake.
+fn compression_layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(DefaultPredicate::new().and(is_not_upgrade))
+}
+
+fn is_not_upgrade(
+    status: StatusCode,
+    _: axum::http::Version,
+    _: &axum::http::HeaderMap,
+    _: &axum::http::Extensions,
+) -> bool {
+    status != StatusCode::SWITCHING_PROTOCOLS
+}
+
+/// The span `TraceLayer` opens per request. Only the path is recorded: the
+/// query can hold the deprecated `api_key` WebSocket parameter, which would
+/// otherwise land in the log at `debug`.
+fn request_span(req: &axum::extract::Request) -> tracing::Span {
+    tracing::debug_span!(
+        "request",
+        method = %req.method(),
+        path = %req.uri().path(),
+        version = ?req.version(),
+    )
+}
+
+/// Serve `/api/v{n}/...` from the `/api/...` routes. The unversioned paths
+/// stay as aliases for the current version so existing clients keep working.
+fn strip_api_version(mut req: axum::extract::Request) -> axum::extract::Request {
+    let path = req.uri().path();
+    let rest = API_VERSIONS.iter().find_map(|v| {
+        path.strip_prefix(&format!("/api/v{}", v))
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+    });
+    let Some(rest) = rest else {
+        return req;
+    };
+
+    let path_and_query = match req.uri().query() {
+        Some(query) => format!("/api{}?{}", rest, query),
+        None => format!("/api{}", rest),
+    };
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = path_and_query.parse().ok();
+    if let Ok(uri) = axum::http::Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+    req
+}
+
+async fn add_api_version_header(
+    mut response: axum::response::Response,
+) -> axum::response::Response {
+    response
+        .headers_mut()
+        .insert("x-api-version", axum::http::HeaderValue::from(API_VERSION));
+    response
 }
 
 async fn auth_middleware(
@@ -647,16 +1221,21 @@ async fn auth_middleware(
     mut req: axum::extract::Request,
     next: axum::middleware::Next,
 ) -> axum::response::Response {
-    let (allowed_ips, blocked_ips) = {
+    let (allowed_ips, blocked_ips, env_api_key) = {
         let config = state.config.lock().unwrap();
         (
             config.auth.allowed_ips.clone(),
             config.auth.blocked_ips.clone(),
+            config.auth.api_key.clone(),
         )
     };
     let auth_state = { state.auth_state.lock().unwrap().clone() };
 
-    let client_ip = addr.ip().to_string();
+    // Dual-stack listeners report IPv4 clients as ::ffff:a.b.c.d; unwrap those so
+    // IPv4 list entries still match.
+    let client_addr = addr.ip().to_canonical();
+    let client_ip = client_addr.to_string();
+    state.client_history.lock().unwrap().record(client_addr);
 
     // Always check blocked IPs first (even if auth is disabled)
     if !blocked_ips.is_empty() && is_ip_in_list(&client_ip, &blocked_ips) {
@@ -665,7 +1244,7 @@ async fn auth_middleware(
 
     // If auth is disabled/public, allow the request (local-only guard)
     if matches!(auth_state.mode, AuthMode::Public) {
-        if !is_local_network(addr.ip()) {
+        if !is_local_network(client_addr) {
             return StatusCode::FORBIDDEN.into_response();
         }
         req.extensions_mut().insert(AuthContext {
@@ -689,22 +1268,61 @@ async fn auth_middleware(
     let token = extract_token(&req);
 
     if let Some(token) = token {
-        if let Some(record) = auth_store::find_active_record(&auth_state, &token) {
-            if let Some(scope) = required_scope {
-                if !auth_store::has_scope(&record, scope) {
-                    return StatusCode::FORBIDDEN.into_response();
-                }
+        let auth_ctx = match key_context(env_api_key.as_deref(), &auth_state, &token) {
+            Ok(auth_ctx) => auth_ctx,
+            Err(e) => return e.into_response(),
+        };
+        if let Some(scope) = required_scope {
+            if !auth_ctx.has_scope(scope) {
+                return StatusCode::FORBIDDEN.into_response();
             }
-            req.extensions_mut().insert(AuthContext {
-                mode: AuthMode::Protected,
-                scopes: record.scopes.clone(),
-            });
-            return next.run(req).await;
         }
-        return StatusCode::UNAUTHORIZED.into_response();
+        req.extensions_mut().insert(auth_ctx);
+        return next.run(req).await;
+    }
+
+    // Sockets may instead send the key as their first message, keeping it
+    // out of URLs and logs. `ws_handler` sees no AuthContext and waits for it.
+    if req.uri().path() == "/api/ws" {
+        return next.run(req).await;
     }
 
-    StatusCode::UNAUTHORIZED.into_response()
+    ApiError::unauthorized(
+        "MISSING_KEY",
+        "Send an API key as \"Authorization: Bearer <key>\"",
+    )
+    .into_response()
+}
+
+/// What an API key may do, or why it's refused. `env_api_key` is the key
+/// from `CNTRL_API_KEY`, which acts as admin.
+pub(crate) fn key_context(
+    env_api_key: Option<&str>,
+    auth_state: &auth_store::AuthState,
+    token: &str,
+) -> Result<AuthContext, ApiError> {
+    // Kept in memory only, never in the key store
+    if env_api_key == Some(token) {
+        return Ok(AuthContext {
+            mode: AuthMode::Protected,
+            scopes: vec![SCOPE_ADMIN.to_string()],
+        });
+    }
+    match auth_store::lookup_key(auth_state, token) {
+        KeyLookup::Active(record) => Ok(AuthContext {
+            mode: AuthMode::Protected,
+            scopes: record.scopes,
+        }),
+        KeyLookup::Expired => Err(ApiError::unauthorized("KEY_EXPIRED", "API key has expired")),
+        KeyLookup::Revoked => Err(ApiError::unauthorized(
+            "KEY_REVOKED",
+            "API key has been revoked",
+        )),
+        KeyLookup::Unknown => Err(ApiError::unauthorized(
+            "INVALID_KEY",
+            "API key not recognized",
+        )),
+    }
 }
 
 /// Check if an IP matches any entry in a list.
@@ -716,10 +1334,16 @@ fn is_ip_in_list(client_ip: &str, list: &[String]) -> bool {
     };
 
     for entry in list {
-        // Exact IP match
+        // Exact IP match (parsed, so IPv6 spellings like "FE80::0001" compare equal)
         if entry == client_ip {
             return true;
         }
+        if let Ok(entry_ip) = entry.parse::<std::net::IpAddr>() {
+            if entry_ip.to_canonical() == client {
+                return true;
+            }
+            continue;
+        }
 
         // CIDR match
         if entry.contains('/') {
@@ -736,14 +1360,12 @@ fn is_ip_in_list(client_ip: &str, list: &[String]) -> bool {
             continue;
         }
 
-        // If entry is not a valid IP, treat it as a hostname and resolve
-        if entry.parse::<std::net::IpAddr>().is_err() {
-            use std::net::ToSocketAddrs;
-            if let Ok(addrs) = (entry.as_str(), 0u16).to_socket_addrs() {
-                for addr in addrs {
-                    if addr.ip() == client {
-                        return true;
-                    }
+        // Entry is not a valid IP, treat it as a hostname and resolve
+        use std::net::ToSocketAddrs;
+        if let Ok(addrs) = (entry.as_str(), 0u16).to_socket_addrs() {
+            for addr in addrs {
+                if addr.ip().to_canonical() == client {
+                    return true;
                 }
             }
         }
@@ -800,7 +1422,7 @@ fn extract_token(req: &axum::extract::Request) -> Option<String> {
         if let Some(query) = req.uri().query() {
             for param in query.split('&') {
                 if let Some(key) = param.strip_prefix("api_key=") {
-                    println!("[auth] Deprecated api_key query param used for /api/ws");
+                    warn!("Deprecated api_key query param used for /api/ws");
                     return Some(key.to_string());
                 }
             }
@@ -827,3 +1449,93 @@ fn is_local_network(ip: std::net::IpAddr) -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::broadcast::error::RecvError;
+
+    fn test_state(config: AppConfig) -> Arc<handlers::AppState> {
+        let (_status_tx, status) = tokio::sync::watch::channel(ServerStatus::Running);
+        let (_shutdown_tx, shutdown) = tokio::sync::watch::channel(false);
+        Arc::new(handlers::AppState {
+            system: Arc::new(Mutex::new(System::new())),
+            processes: Arc::new(Mutex::new(System::new())),
+            networks: Arc::new(Mutex::new(Networks::new())),
+            disks: Arc::new(Mutex::new(Disks::new())),
+            gpu_cache: Arc::new(Mutex::new(None)),
+            refresh: Default::default(),
+            cpu_load_average: Mutex::new(None),
+            usage_history: Mutex::new(Default::default()),
+            #[cfg(target_os = "windows")]
+            window_cache: Mutex::new(None),
+            system_info_cache: Mutex::new(None),
+            clipboard: Mutex::new(None),
+            broadcast_tx: tokio::sync::broadcast::channel(16).0,
+            active_topics: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            resume_tokens: Mutex::new(Default::default()),
+            config: Arc::new(Mutex::new(config)),
+            auth_state: Arc::new(Mutex::new(Default::default())),
+            client_history: client_history::shared(),
+            loop_manager: Arc::new(LoopManager::new()),
+            status,
+            scheduled_power: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            next_schedule_id: std::sync::atomic::AtomicU64::new(1),
+            shutdown,
+        })
+    }
+
+    /// Wait up to `within` for the next `SystemStats` frame, skipping
+    /// anything else on the channel.
+    async fn next_stats(
+        rx: &mut tokio::sync::broadcast::Receiver<types::BroadcastEvent>,
+        within: Duration,
+    ) -> bool {
+        tokio::time::timeout(within, async {
+            loop {
+                match rx.recv().await {
+                    Ok(types::BroadcastEvent::SystemStats(_)) => return true,
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return false,
+                }
+            }
+        })
+        .await
+        .unwrap_or(false)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn stats_frames_resume_after_disable_and_enable() {
+        let mut config = AppConfig::default();
+        config.websocket.stats.interval_ms = 100;
+        let state = test_state(config);
+        let mut rx = state.broadcast_tx.subscribe();
+        state
+            .active_topics
+            .lock()
+            .unwrap()
+            .insert("cpu".to_string(), 1);
+
+        let manager = LoopManager::new();
+        manager.ensure_loop_running("cpu", state.clone());
+        assert!(next_stats(&mut rx, Duration::from_secs(3)).await);
+
+        state.config.lock().unwrap().websocket.stats.enabled = false;
+        // Let a frame collected before the switch land, then expect silence
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        while rx.try_recv().is_ok() {}
+        assert!(!next_stats(&mut rx, DISABLED_POLL_INTERVAL + Duration::from_millis(200)).await);
+        assert!(
+            manager.running_loops().0,
+            "stats loop exited while disabled"
+        );
+
+        // The subscriber stayed attached and gets frames again without
+        // resubscribing
+        state.config.lock().unwrap().websocket.stats.enabled = true;
+        assert!(next_stats(&mut rx, Duration::from_secs(3)).await);
+
+        manager.stop_all();
+    }
+}
@@ -149,9 +149,92 @@ pub fn get_macos_window_map() -> std::collections::HashSet<u32> {
     pids
 }
 
+/// Titles of on-screen app windows keyed by owner PID (first titled window wins),
+/// via CoreGraphics `CGWindowListCopyWindowInfo`.
+///
+/// Since macOS 10.15 `kCGWindowName` is only filled in when the app has the
+/// Screen Recording permission. Without it the key is missing, so processes
+/// simply get no entry and callers fall back to `None`.
+#[cfg(target_os = "macos")]
+pub fn get_macos_window_titles() -> std::collections::HashMap<u32, String> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use objc::{msg_send, sel, sel_impl};
+    use std::collections::HashMap;
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> id;
+    }
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: id);
+    }
+
+    const ON_SCREEN_ONLY: u32 = 1 << 0;
+    const EXCLUDE_DESKTOP_ELEMENTS: u32 = 1 << 4;
+
+    let mut titles = HashMap::new();
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let windows = CGWindowListCopyWindowInfo(ON_SCREEN_ONLY | EXCLUDE_DESKTOP_ELEMENTS, 0);
+        if windows == nil {
+            pool.drain();
+            return titles;
+        }
+
+        let pid_key = NSString::alloc(nil).init_str("kCGWindowOwnerPID");
+        let name_key = NSString::alloc(nil).init_str("kCGWindowName");
+        let layer_key = NSString::alloc(nil).init_str("kCGWindowLayer");
+
+        let count: usize = msg_send![windows, count];
+        for i in 0..count {
+            let info: id = msg_send![windows, objectAtIndex: i];
+
+            // Layer 0 is regular app windows (skips menu bar, dock, overlays)
+            let layer: id = msg_send![info, objectForKey: layer_key];
+            if layer == nil {
+                continue;
+            }
+            let layer: i64 = msg_send![layer, longLongValue];
+            if layer != 0 {
+                continue;
+            }
+
+            let pid: id = msg_send![info, objectForKey: pid_key];
+            let name: id = msg_send![info, objectForKey: name_key];
+            if pid == nil || name == nil {
+                continue;
+            }
+            let utf8: *const c_char = msg_send![name, UTF8String];
+            if utf8.is_null() {
+                continue;
+            }
+            let title = CStr::from_ptr(utf8).to_string_lossy().into_owned();
+            if title.is_empty() {
+                continue;
+            }
+
+            let pid: i32 = msg_send![pid, intValue];
+            titles.entry(pid as u32).or_insert(title);
+        }
+
+        let _: () = msg_send![pid_key, release];
+        let _: () = msg_send![name_key, release];
+        let _: () = msg_send![layer_key, release];
+        CFRelease(windows);
+        pool.drain();
+    }
+
+    titles
+}
+
 #[cfg(not(target_os = "macos"))]
 pub fn get_processes_list(state: &Arc<AppState>) -> Vec<ProcessInfo> {
-    let mut sys = state.system.lock().unwrap();
+    let mut sys = crate::server::handlers::lock_system(&state.processes);
     sys.refresh_processes();
 
     use std::collections::HashMap;
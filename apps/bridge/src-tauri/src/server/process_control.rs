@@ -0,0 +1,246 @@
+/// Range of `set_priority`'s `priority`, as a Unix nice value: -20 is the
+/// highest priority, 19 the lowest.
+pub const PRIORITY_RANGE: std::ops::RangeInclusive<i32> = -20..=19;
+
+pub enum ProcessControlError {
+    /// The pid is 0 or the bridge's own
+    InvalidPid(String),
+    /// No process with that pid
+    NotFound,
+    /// The OS refused, e.g. raising priority without admin rights
+    Denied(String),
+    Failed(String),
+}
+
+/// Set process `pid`'s priority (a nice value in `PRIORITY_RANGE`) and
+/// return the value actually applied.
+pub fn set_priority(pid: u32, priority: i32) -> Result<i32, ProcessControlError> {
+    check_pid(pid)?;
+    platform::set_priority(pid, priority)
+}
+
+/// Pause process `pid`. Returns whether it was already suspended, in which
+/// case nothing is done.
+pub fn suspend(pid: u32) -> Result<bool, ProcessControlError> {
+    check_pid(pid)?;
+    platform::set_suspended(pid, true)
+}
+
+/// Let a suspended process `pid` run again. Returns whether it was
+/// suspended.
+pub fn resume(pid: u32) -> Result<bool, ProcessControlError> {
+    check_pid(pid)?;
+    platform::set_suspended(pid, false)
+}
+
+/// Refuse pids that would land on the bridge itself: `kill(0, ..)` signals
+/// our whole process group, `setpriority` reads 0 as the caller, and stopping
+/// our own pid freezes the server with nothing left to resume it. The bridge
+/// doesn't renice itself either; a low priority only makes its stats lag.
+fn check_pid(pid: u32) -> Result<(), ProcessControlError> {
+    if pid == 0 {
+        return Err(ProcessControlError::InvalidPid(
+            "pid 0 is not a process".to_string(),
+        ));
+    }
+    if pid == std::process::id() {
+        return Err(ProcessControlError::InvalidPid(format!(
+            "pid {} is the bridge itself",
+            pid
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ProcessControlError;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+    use windows::Win32::Foundation::{
+        CloseHandle, GetLastError, ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER, HANDLE, NTSTATUS,
+    };
+    use windows::Win32::System::Threading::{
+        OpenProcess, SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+        HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, PROCESS_ACCESS_RIGHTS,
+        PROCESS_CREATION_FLAGS, PROCESS_SET_INFORMATION, PROCESS_SUSPEND_RESUME,
+    };
+
+    // Undocumented, but what Task Manager and Process Explorer use to freeze
+    // every thread of a process at once.
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtSuspendProcess(process: HANDLE) -> NTSTATUS;
+        fn NtResumeProcess(process: HANDLE) -> NTSTATUS;
+    }
+
+    /// Pids this app suspended. Windows has no cheap way to ask whether a
+    /// process is suspended, and suspensions stack, so this keeps a second
+    /// `suspend` from needing a second `resume`.
+    static SUSPENDED: Mutex<Option<HashSet<u32>>> = Mutex::new(None);
+
+    unsafe fn open(pid: u32, access: PROCESS_ACCESS_RIGHTS) -> Result<HANDLE, ProcessControlError> {
+        OpenProcess(access, false, pid).map_err(|e| {
+            if e.code() == ERROR_INVALID_PARAMETER.to_hresult() {
+                ProcessControlError::NotFound
+            } else if e.code() == ERROR_ACCESS_DENIED.to_hresult() {
+                ProcessControlError::Denied(format!("Access to process {} denied", pid))
+            } else {
+                ProcessControlError::Failed(format!("Failed to open process {}: {}", pid, e))
+            }
+        })
+    }
+
+    /// Windows only has priority classes, so nice values are bucketed. The
+    /// value reported back is the one each class stands for. Realtime is
+    /// never used; it can starve the input and disk threads.
+    fn priority_class(priority: i32) -> (PROCESS_CREATION_FLAGS, i32) {
+        match priority {
+            i32::MIN..=-15 => (HIGH_PRIORITY_CLASS, -15),
+            -14..=-5 => (ABOVE_NORMAL_PRIORITY_CLASS, -5),
+            -4..=4 => (NORMAL_PRIORITY_CLASS, 0),
+            5..=14 => (BELOW_NORMAL_PRIORITY_CLASS, 10),
+            _ => (IDLE_PRIORITY_CLASS, 19),
+        }
+    }
+
+    pub fn set_priority(pid: u32, priority: i32) -> Result<i32, ProcessControlError> {
+        let (class, applied) = priority_class(priority);
+        unsafe {
+            let handle = open(pid, PROCESS_SET_INFORMATION)?;
+            let ok = SetPriorityClass(handle, class).as_bool();
+            let error = GetLastError();
+            let _ = CloseHandle(handle);
+            if !ok {
+                return Err(ProcessControlError::Failed(format!(
+                    "SetPriorityClass failed: {:?}",
+                    error
+                )));
+            }
+        }
+        Ok(applied)
+    }
+
+    pub fn set_suspended(pid: u32, suspend: bool) -> Result<bool, ProcessControlError> {
+        let mut suspended = SUSPENDED.lock().unwrap();
+        let suspended = suspended.get_or_insert_with(HashSet::new);
+        let was_suspended = suspended.contains(&pid);
+        if suspend && was_suspended {
+            return Ok(true);
+        }
+        unsafe {
+            let handle = open(pid, PROCESS_SUSPEND_RESUME)?;
+            let status = if suspend {
+                NtSuspendProcess(handle)
+            } else {
+                NtResumeProcess(handle)
+            };
+            let _ = CloseHandle(handle);
+            if status.is_err() {
+                return Err(ProcessControlError::Failed(format!(
+                    "{} failed: {:?}",
+                    if suspend {
+                        "NtSuspendProcess"
+                    } else {
+                        "NtResumeProcess"
+                    },
+                    status
+                )));
+            }
+        }
+        if suspend {
+            suspended.insert(pid);
+        } else {
+            suspended.remove(&pid);
+        }
+        Ok(was_suspended)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::ProcessControlError;
+    use std::io;
+    use sysinfo::{Pid, ProcessStatus, System};
+
+    /// The error for a failed libc call `what` on process `pid`.
+    fn last_error(pid: u32, what: &str) -> ProcessControlError {
+        let e = io::Error::last_os_error();
+        match e.raw_os_error() {
+            Some(libc::ESRCH) => ProcessControlError::NotFound,
+            Some(libc::EPERM) | Some(libc::EACCES) => ProcessControlError::Denied(format!(
+                "{} on process {} not allowed: {}",
+                what, pid, e
+            )),
+            _ => ProcessControlError::Failed(format!("{} failed: {}", what, e)),
+        }
+    }
+
+    pub fn set_priority(pid: u32, priority: i32) -> Result<i32, ProcessControlError> {
+        // Checked up front so a missing pid is always NotFound, whatever
+        // errno the platform picks for it
+        if !System::new().refresh_process(Pid::from(pid as usize)) {
+            return Err(ProcessControlError::NotFound);
+        }
+        // Lowering the nice value of any process needs root
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, priority) } != 0 {
+            return Err(last_error(pid, "setpriority"));
+        }
+        Ok(priority)
+    }
+
+    pub fn set_suspended(pid: u32, suspend: bool) -> Result<bool, ProcessControlError> {
+        let mut sys = System::new();
+        if !sys.refresh_process(Pid::from(pid as usize)) {
+            return Err(ProcessControlError::NotFound);
+        }
+        let was_suspended = sys
+            .process(Pid::from(pid as usize))
+            .is_some_and(|p| p.status() == ProcessStatus::Stop);
+        if suspend && was_suspended {
+            return Ok(true);
+        }
+        let signal = if suspend {
+            libc::SIGSTOP
+        } else {
+            libc::SIGCONT
+        };
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } != 0 {
+            return Err(last_error(pid, if suspend { "SIGSTOP" } else { "SIGCONT" }));
+        }
+        Ok(was_suspended)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_pid_zero_and_self() {
+        let own = std::process::id();
+        for pid in [0, own] {
+            assert!(matches!(
+                suspend(pid),
+                Err(ProcessControlError::InvalidPid(_))
+            ));
+            assert!(matches!(
+                resume(pid),
+                Err(ProcessControlError::InvalidPid(_))
+            ));
+            assert!(matches!(
+                set_priority(pid, 0),
+                Err(ProcessControlError::InvalidPid(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn missing_pid_is_not_found() {
+        // Above any real pid limit, but still a positive pid_t
+        assert!(matches!(
+            set_priority(0x7fff_fff0, 0),
+            Err(ProcessControlError::NotFound)
+        ));
+    }
+}
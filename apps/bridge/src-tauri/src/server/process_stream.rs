@@ -0,0 +1,199 @@
+use crate::server::handlers::AppState;
+use crate::server::types::{
+    BroadcastEvent, LaunchRequest, OperationFeedback, ProcessExit, ProcessOutput,
+};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{mpsc, OwnedSemaphorePermit};
+
+/// Output sent per run, stdout and stderr combined. The process is killed
+/// once it's reached.
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Longer lines are split into several `process_output` events.
+const MAX_LINE_BYTES: u64 = 8 * 1024;
+
+/// A run is killed after this long, whether or not it's still printing.
+const MAX_RUN_TIME: Duration = Duration::from_secs(5 * 60);
+
+/// Runs one connection may have going at once; more are refused until one
+/// exits.
+pub const MAX_CONCURRENT_RUNS: usize = 4;
+
+/// Feedback for a `process_run` that must not start: process control or
+/// launching is off, or `safe_mode` makes it a dry run.
+pub fn refusal(state: &AppState, req: &LaunchRequest) -> Option<BroadcastEvent> {
+    let (success, message) = {
+        let config = state.config.lock().unwrap();
+        if !config.features.enable_processes {
+            (false, "Process control disabled")
+        } else if !config.features.enable_process_launch {
+            (false, "Launching processes disabled")
+        } else if config.safe_mode {
+            (true, "Dry run: safe_mode is on")
+        } else {
+            return None;
+        }
+    };
+    Some(feedback(req, success, Some(message.to_string()), None))
+}
+
+/// Feedback for a `process_run` refused because the connection already has
+/// `MAX_CONCURRENT_RUNS` going.
+pub fn busy(req: &LaunchRequest) -> BroadcastEvent {
+    feedback(
+        req,
+        false,
+        Some(format!(
+            "Too many running processes (at most {} per connection)",
+            MAX_CONCURRENT_RUNS
+        )),
+        None,
+    )
+}
+
+fn feedback(
+    req: &LaunchRequest,
+    success: bool,
+    message: Option<String>,
+    pid: Option<u32>,
+) -> BroadcastEvent {
+    BroadcastEvent::ProcessFeedback(OperationFeedback {
+        success,
+        action: "run".to_string(),
+        message,
+        pid,
+        name: Some(req.path.clone()),
+        id: req.id.clone(),
+    })
+}
+
+/// Spawn `req` and stream its output to one connection through `tx`: a
+/// `process_feedback` once it starts (or fails to), a `process_output` per
+/// line, then a `process_exit`. Stops early, killing the process, when the
+/// output or time limit is hit or the connection goes away. `_slot` is held
+/// until the run ends.
+pub async fn run(req: LaunchRequest, tx: mpsc::Sender<String>, _slot: OwnedSemaphorePermit) {
+    let send = |event: BroadcastEvent| {
+        let tx = tx.clone();
+        async move {
+            match serde_json::to_string(&event) {
+                Ok(text) => tx.send(text).await.is_ok(),
+                Err(_) => true,
+            }
+        }
+    };
+
+    let mut child = match Command::new(&req.path)
+        .args(req.args.clone().unwrap_or_default())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            send(feedback(&req, false, Some(e.to_string()), None)).await;
+            return;
+        }
+    };
+    let pid = child.id();
+    if !send(feedback(&req, true, None, pid)).await {
+        return;
+    }
+
+    let (line_tx, mut line_rx) = mpsc::channel::<(&'static str, String)>(32);
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(read_lines(stdout, "stdout", line_tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(read_lines(stderr, "stderr", line_tx.clone()));
+    }
+    drop(line_tx);
+
+    let deadline = tokio::time::sleep(MAX_RUN_TIME);
+    tokio::pin!(deadline);
+    let mut sent_bytes = 0;
+    let mut reason = None;
+
+    // Both pipes close when the process exits (or hands them to a child
+    // that outlives it, which the deadline still covers)
+    loop {
+        tokio::select! {
+            line = line_rx.recv() => {
+                let Some((stream, line)) = line else { break };
+                sent_bytes += line.len();
+                if sent_bytes > MAX_OUTPUT_BYTES {
+                    reason = Some("output_limit");
+                    break;
+                }
+                let output = ProcessOutput {
+                    id: req.id.clone(),
+                    pid,
+                    stream: stream.to_string(),
+                    line,
+                };
+                if !send(BroadcastEvent::ProcessOutput(output)).await {
+                    return;
+                }
+            }
+            _ = &mut deadline => {
+                reason = Some("timeout");
+                break;
+            }
+            _ = tx.closed() => return,
+        }
+    }
+
+    // The pipes can close while the process keeps running, so waiting for it
+    // is still bounded by the deadline
+    if reason.is_none() {
+        let deadline = deadline.deadline();
+        if tokio::time::timeout_at(deadline, child.wait())
+            .await
+            .is_err()
+        {
+            reason = Some("timeout");
+        }
+    }
+    if reason.is_some() {
+        let _ = child.kill().await;
+    }
+    let code = child.wait().await.ok().and_then(|status| status.code());
+    send(BroadcastEvent::ProcessExit(ProcessExit {
+        id: req.id.clone(),
+        pid,
+        code,
+        reason: reason.map(str::to_string),
+    }))
+    .await;
+}
+
+/// Forward `pipe` line by line, without the line ending, until it closes.
+async fn read_lines(
+    pipe: impl AsyncRead + Unpin,
+    stream: &'static str,
+    lines: mpsc::Sender<(&'static str, String)>,
+) {
+    let mut reader = BufReader::new(pipe);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match (&mut reader)
+            .take(MAX_LINE_BYTES)
+            .read_until(b'\n', &mut buf)
+            .await
+        {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches(['\r', '\n']).to_string();
+        if lines.send((stream, line)).await.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Parts of the shared sysinfo state that are refreshed on demand.
+#[derive(Clone, Copy)]
+pub enum Part {
+    Cpu,
+    Memory,
+    Disks,
+}
+
+/// When each `Part` was last refreshed, so concurrent pollers share one
+/// refresh instead of each doing their own. Ask `due` while holding the
+/// part's lock: a caller that waited on someone else's refresh then finds it
+/// fresh and serves that snapshot.
+#[derive(Default)]
+pub struct RefreshScheduler {
+    last: Mutex<[Option<Instant>; 3]>,
+}
+
+impl RefreshScheduler {
+    /// Whether `part` was last refreshed `min_interval` or longer ago. If so
+    /// it's counted as refreshed now, and the caller must refresh it.
+    pub fn due(&self, part: Part, min_interval: Duration) -> bool {
+        let mut last = self.last.lock().unwrap();
+        let last = &mut last[part as usize];
+        let now = Instant::now();
+        if last.is_some_and(|at| now.duration_since(at) < min_interval) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+
+    /// When `part` was last refreshed, if ever.
+    pub fn last_refresh(&self, part: Part) -> Option<Instant> {
+        self.last.lock().unwrap()[part as usize]
+    }
+}
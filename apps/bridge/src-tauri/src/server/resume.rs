@@ -0,0 +1,73 @@
+use rand::RngCore;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long after a connection closes its resume token still works.
+pub const RESUME_TTL: Duration = Duration::from_secs(5 * 60);
+
+struct Session {
+    topics: HashSet<String>,
+    /// `None` while the connection that holds the token is open.
+    expires: Option<Instant>,
+}
+
+/// The subscriptions of each WebSocket connection, by the resume token it
+/// was given, so a client that reconnects can get them back in one message.
+#[derive(Default)]
+pub struct ResumeTokens {
+    sessions: HashMap<String, Session>,
+}
+
+impl ResumeTokens {
+    /// A token for a new connection, with no subscriptions yet.
+    pub fn issue(&mut self) -> String {
+        let now = Instant::now();
+        self.sessions
+            .retain(|_, s| s.expires.map_or(true, |expires| expires > now));
+
+        let token = generate_token();
+        self.sessions.insert(
+            token.clone(),
+            Session {
+                topics: HashSet::new(),
+                expires: None,
+            },
+        );
+        token
+    }
+
+    /// Record the connection's current subscriptions. No-op once the token
+    /// has been used to resume.
+    pub fn update(&mut self, token: &str, topics: &HashSet<String>) {
+        if let Some(session) = self.sessions.get_mut(token) {
+            session.topics = topics.clone();
+        }
+    }
+
+    /// The connection closed; keep its token for `RESUME_TTL`.
+    pub fn release(&mut self, token: &str) {
+        if let Some(session) = self.sessions.get_mut(token) {
+            session.expires = Some(Instant::now() + RESUME_TTL);
+        }
+    }
+
+    /// The subscriptions saved under `token`, if it's known and not expired.
+    /// A token can only be used once.
+    pub fn take(&mut self, token: &str) -> Option<HashSet<String>> {
+        let session = self.sessions.remove(token)?;
+        match session.expires {
+            Some(expires) if expires <= Instant::now() => None,
+            _ => Some(session.topics),
+        }
+    }
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        hex.push_str(&format!("{:02x}", b));
+    }
+    format!("rt_{}", hex)
+}
@@ -0,0 +1,60 @@
+use xcap::image::{
+    codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageFormat, RgbaImage,
+};
+use xcap::Monitor;
+
+pub enum Format {
+    Png,
+    /// JPEG at this quality (1-100)
+    Jpeg(u8),
+}
+
+impl Format {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Png => "image/png",
+            Format::Jpeg(_) => "image/jpeg",
+        }
+    }
+}
+
+/// Capture display `index` (in `Monitor::all()` order), or the primary one.
+/// `Ok(None)` means there is no such display.
+pub fn capture(index: Option<usize>) -> Result<Option<RgbaImage>, String> {
+    let monitors = Monitor::all().map_err(|e| format!("Failed to list displays: {}", e))?;
+    let monitor = match index {
+        Some(i) => monitors.get(i),
+        None => monitors
+            .iter()
+            .find(|m| m.is_primary())
+            .or_else(|| monitors.first()),
+    };
+    let Some(monitor) = monitor else {
+        return Ok(None);
+    };
+    monitor
+        .capture_image()
+        .map(Some)
+        .map_err(|e| format!("Failed to capture {}: {}", monitor.name(), e))
+}
+
+/// Encode `image`, first scaling it down to `width` (keeping the aspect
+/// ratio) if it is wider than that.
+pub fn encode(image: RgbaImage, width: Option<u32>, format: Format) -> Result<Vec<u8>, String> {
+    let mut image = DynamicImage::ImageRgba8(image);
+    if let Some(width) = width.filter(|&w| w > 0 && w < image.width()) {
+        image = image.resize(width, u32::MAX, FilterType::Triangle);
+    }
+
+    let mut out = Vec::new();
+    match format {
+        Format::Png => image
+            .write_to(&mut std::io::Cursor::new(&mut out), ImageFormat::Png)
+            .map_err(|e| format!("PNG encoding failed: {}", e))?,
+        // JPEG has no alpha channel
+        Format::Jpeg(quality) => JpegEncoder::new_with_quality(&mut out, quality)
+            .encode_image(&DynamicImage::ImageRgb8(image.to_rgb8()))
+            .map_err(|e| format!("JPEG encoding failed: {}", e))?,
+    }
+    Ok(out)
+}
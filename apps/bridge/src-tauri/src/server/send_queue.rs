@@ -0,0 +1,120 @@
+use axum::extract::ws::Message;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// Most frames waiting for one client. Beyond this, snapshots are dropped
+/// and other frames wait for the client to catch up.
+pub const CAPACITY: usize = 64;
+
+struct Frame {
+    /// Set for snapshots (stats, process lists), which a newer snapshot with
+    /// the same key replaces.
+    snapshot: Option<&'static str>,
+    message: Message,
+}
+
+#[derive(Default)]
+struct Inner {
+    frames: VecDeque<Frame>,
+    /// Snapshots replaced or dropped since the last `pop`.
+    dropped: u64,
+    closed: bool,
+}
+
+/// Frames waiting to be written to one WebSocket client, so a slow client
+/// doesn't hold up reading the shared broadcast. A client that can't keep up
+/// gets the newest snapshot of each kind and a count of the ones it missed;
+/// acks, feedback and errors are kept in order and never dropped.
+#[derive(Default)]
+pub struct SendQueue {
+    inner: Mutex<Inner>,
+    /// Signalled when a frame is queued or the queue is closed.
+    ready: Notify,
+    /// Signalled when a frame is taken or the queue is closed.
+    space: Notify,
+}
+
+impl SendQueue {
+    /// Queue a frame that must be delivered, after everything queued so far.
+    /// Waits while the queue is full of such frames. `false` once closed.
+    pub async fn push(&self, message: Message) -> bool {
+        self.push_frame(Frame {
+            snapshot: None,
+            message,
+        })
+        .await
+    }
+
+    /// Queue a snapshot, replacing a queued one with the same `key` in place.
+    pub async fn push_snapshot(&self, key: &'static str, message: Message) -> bool {
+        {
+            let mut guard = self.inner.lock().unwrap();
+            let inner = &mut *guard;
+            if inner.closed {
+                return false;
+            }
+            if let Some(frame) = inner.frames.iter_mut().find(|f| f.snapshot == Some(key)) {
+                frame.message = message;
+                inner.dropped += 1;
+                return true;
+            }
+        }
+        self.push_frame(Frame {
+            snapshot: Some(key),
+            message,
+        })
+        .await
+    }
+
+    async fn push_frame(&self, frame: Frame) -> bool {
+        loop {
+            {
+                let mut guard = self.inner.lock().unwrap();
+                let inner = &mut *guard;
+                if inner.closed {
+                    return false;
+                }
+                // Make room by dropping the oldest snapshot
+                if inner.frames.len() >= CAPACITY {
+                    if let Some(i) = inner.frames.iter().position(|f| f.snapshot.is_some()) {
+                        inner.frames.remove(i);
+                        inner.dropped += 1;
+                    }
+                }
+                if inner.frames.len() < CAPACITY {
+                    inner.frames.push_back(frame);
+                    self.ready.notify_one();
+                    return true;
+                }
+            }
+            self.space.notified().await;
+        }
+    }
+
+    /// The next frame to write, with how many snapshots were dropped since
+    /// the previous one. `None` once closed and drained.
+    pub async fn pop(&self) -> Option<(Message, u64)> {
+        loop {
+            {
+                let mut inner = self.inner.lock().unwrap();
+                if let Some(frame) = inner.frames.pop_front() {
+                    let dropped = std::mem::take(&mut inner.dropped);
+                    self.space.notify_one();
+                    return Some((frame.message, dropped));
+                }
+                if inner.closed {
+                    return None;
+                }
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    /// Stop accepting frames. Those already queued are still handed out.
+    pub fn close(&self) {
+        self.inner.lock().unwrap().closed = true;
+        self.ready.notify_one();
+        self.space.notify_one();
+    }
+}
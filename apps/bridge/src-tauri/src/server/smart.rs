@@ -0,0 +1,110 @@
+use crate::server::gpu::run_with_status;
+use crate::server::types::DiskHealth;
+use serde_json::Value;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// smartctl reports temperatures in Celsius; the handler converts them.
+const CELSIUS: &str = "celsius";
+
+/// smartctl's JSON output, or why it couldn't be had. Each call is bounded
+/// by the GPU tools' timeout, since a sleeping or failing drive can leave
+/// smartctl hanging.
+fn smartctl(args: &[&str]) -> Result<Value, String> {
+    let mut cmd = Command::new("smartctl");
+    cmd.args(args);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    // smartctl's exit code is a bitmask that is non-zero for failing drives
+    // too, so judge the result by the JSON body instead.
+    let (_, stdout) = run_with_status(&mut cmd)?;
+    serde_json::from_slice(&stdout).map_err(|e| format!("Unexpected smartctl output: {}", e))
+}
+
+fn unavailable(device: String, message: impl Into<String>) -> DiskHealth {
+    DiskHealth {
+        device,
+        model: None,
+        health: "UNAVAILABLE".to_string(),
+        temperature: None,
+        temp_unit: CELSIUS.to_string(),
+        power_on_hours: None,
+        message: Some(message.into()),
+    }
+}
+
+/// SMART health for every drive `smartctl --scan` finds.
+///
+/// When smartctl isn't installed, each entry of `fallback_devices` is
+/// reported as unavailable instead. Those come from the mounted volumes, so
+/// that fallback has one row per partition rather than per drive.
+///
+/// Blocks for up to a few seconds per drive; call off the async workers.
+pub fn get_disk_health(fallback_devices: &[String]) -> Vec<DiskHealth> {
+    let scan = match smartctl(&["--scan", "--json"]) {
+        Ok(scan) => scan,
+        Err(reason) => {
+            let mut devices = fallback_devices.to_vec();
+            devices.sort();
+            devices.dedup();
+            return devices
+                .into_iter()
+                .map(|d| unavailable(d, reason.clone()))
+                .collect();
+        }
+    };
+
+    scan.get("devices")
+        .and_then(|d| d.as_array())
+        .map(|devices| {
+            devices
+                .iter()
+                .filter_map(|d| d.get("name").and_then(|n| n.as_str()))
+                .map(query_device)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn query_device(device: &str) -> DiskHealth {
+    let json = match smartctl(&["-i", "-H", "-A", "--json", device]) {
+        Ok(json) => json,
+        Err(e) => return unavailable(device.to_string(), e),
+    };
+
+    // No SMART verdict usually means the device couldn't be opened
+    // (missing root/administrator rights) or doesn't support SMART.
+    let Some(passed) = json
+        .pointer("/smart_status/passed")
+        .and_then(|v| v.as_bool())
+    else {
+        let message = json
+            .pointer("/smartctl/messages/0/string")
+            .and_then(|v| v.as_str())
+            .unwrap_or("SMART status not reported (elevated rights may be required)");
+        return unavailable(device.to_string(), message);
+    };
+
+    DiskHealth {
+        device: device.to_string(),
+        model: json
+            .get("model_name")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        health: if passed { "PASSED" } else { "FAILED" }.to_string(),
+        temperature: json
+            .pointer("/temperature/current")
+            .and_then(|v| v.as_f64()),
+        temp_unit: CELSIUS.to_string(),
+        power_on_hours: json
+            .pointer("/power_on_time/hours")
+            .and_then(|v| v.as_u64()),
+        message: None,
+    }
+}
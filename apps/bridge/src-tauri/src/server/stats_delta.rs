@@ -0,0 +1,74 @@
+use crate::server::types::StreamPayload;
+use serde_json::{json, Map, Value};
+
+/// In delta mode, every this many stats frames is sent in full so a client
+/// that got out of step recovers.
+pub const KEYFRAME_INTERVAL: u32 = 30;
+
+/// Per-connection state for `set_delta`. Off by default: every stats frame
+/// is sent in full.
+#[derive(Default)]
+pub struct StatsDelta {
+    enabled: bool,
+    /// Last frame sent to this connection, as the client should now have it.
+    last: Option<Value>,
+    since_keyframe: u32,
+}
+
+impl StatsDelta {
+    /// Turn delta mode on or off. Either way the next frame is a keyframe.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.last = None;
+    }
+
+    /// The message to send for `payload` in delta mode: a `system_stats`
+    /// keyframe, or a `system_stats_delta` holding what changed since the
+    /// last frame. `None` in full-frame mode.
+    pub fn next(&mut self, payload: &StreamPayload) -> Option<Value> {
+        if !self.enabled {
+            return None;
+        }
+        let data = serde_json::to_value(payload).ok()?;
+        let message = match &self.last {
+            Some(last) if self.since_keyframe < KEYFRAME_INTERVAL => {
+                self.since_keyframe += 1;
+                let patch = merge_patch(last, &data).unwrap_or_else(|| json!({}));
+                json!({ "type": "system_stats_delta", "data": patch })
+            }
+            _ => {
+                self.since_keyframe = 1;
+                json!({ "type": "system_stats", "data": data })
+            }
+        };
+        self.last = Some(data);
+        Some(message)
+    }
+}
+
+/// A JSON Merge Patch (RFC 7396) turning `old` into `new`: changed fields,
+/// `null` for removed ones, and arrays replaced whole. `None` if equal.
+fn merge_patch(old: &Value, new: &Value) -> Option<Value> {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut patch = Map::new();
+            for (key, value) in new {
+                let changed = match old.get(key) {
+                    Some(previous) => merge_patch(previous, value),
+                    None => Some(value.clone()),
+                };
+                if let Some(changed) = changed {
+                    patch.insert(key.clone(), changed);
+                }
+            }
+            for key in old.keys() {
+                if !new.contains_key(key) {
+                    patch.insert(key.clone(), Value::Null);
+                }
+            }
+            (!patch.is_empty()).then_some(Value::Object(patch))
+        }
+        _ if old == new => None,
+        _ => Some(new.clone()),
+    }
+}
@@ -1,10 +1,12 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct SystemInfo {
     pub hostname: String,
     pub platform: String,
+    /// Unix timestamp (seconds) of the last boot
+    pub boot_time: u64,
     pub os: OsInfo,
     pub cpu: CpuInfo,
     pub gpu: Option<GpuInfo>,
@@ -13,7 +15,57 @@ pub struct SystemInfo {
     pub network: Option<NetworkInfo>,
 }
 
-#[derive(Serialize, Clone)]
+/// SMART health of one physical drive from GET /api/disks/health.
+/// `health` is "PASSED", "FAILED" or "UNAVAILABLE"; `message` says why when unavailable.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct DiskHealth {
+    pub device: String,
+    pub model: Option<String>,
+    pub health: String,
+    pub temperature: Option<f64>,
+    /// Unit of `temperature`: "celsius" or "fahrenheit" (`display.temp_unit`)
+    pub temp_unit: String,
+    pub power_on_hours: Option<u64>,
+    pub message: Option<String>,
+}
+
+/// Physical disks and their partitions from GET /api/disks/layout.
+/// `available` is false, with `message` saying why, when the platform tool
+/// (`lsblk`, `diskutil` or WMI) couldn't be used.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct DiskLayout {
+    pub available: bool,
+    pub message: Option<String>,
+    pub disks: Vec<PhysicalDisk>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PhysicalDisk {
+    pub name: String,
+    pub model: Option<String>,
+    pub size: u64, // Bytes
+    pub removable: Option<bool>,
+    /// Set when a filesystem sits on the whole disk, without partitions.
+    pub mount_points: Vec<String>,
+    pub partitions: Vec<DiskPartition>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct DiskPartition {
+    pub name: String,
+    pub size: u64, // Bytes
+    /// Partition content type (e.g. `Apple_APFS`) on macOS.
+    pub file_system: Option<String>,
+    /// Includes volumes inside the partition, like LVM volumes or an APFS
+    /// container's volumes.
+    pub mount_points: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct OsInfo {
     pub name: String,
@@ -22,17 +74,21 @@ pub struct OsInfo {
     pub arch: String,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct CpuInfo {
     pub manufacturer: String,
     pub brand: String,
     pub cores: usize,
     pub physical_cores: usize,
+    /// Clock speeds in GHz, 0.0 where unknown
     pub base_speed: f64,
+    pub max_speed: f64,
+    /// Set when the speeds are estimated from the chip model (Apple Silicon)
+    pub speed_approximate: bool,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct GpuInfo {
     pub manufacturer: String,
@@ -40,14 +96,26 @@ pub struct GpuInfo {
     pub memory_total: u64,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct MemoryInfo {
     pub total: u64,
     pub slots: usize,
+    /// Empty when the modules can't be read.
+    pub modules: Vec<MemoryModule>,
 }
 
-#[derive(Serialize, Clone)]
+/// One populated RAM slot.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct MemoryModule {
+    pub size_bytes: u64,
+    pub speed_mhz: Option<u32>,
+    pub manufacturer: Option<String>,
+    pub part_number: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct DiskInfo {
     pub fs: String, // e.g. "C:"
@@ -57,7 +125,7 @@ pub struct DiskInfo {
     pub mount: String,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct NetworkInfo {
     pub name: String,
@@ -76,6 +144,37 @@ pub struct SystemUsage {
     pub memory: MemoryUsage,
     pub gpu: Option<GpuUsage>,
     pub disks: Vec<DiskUsage>,
+    /// Empty unless `stats.sensors_enabled` is on and the platform exposes fans
+    pub fans: Vec<FanSpeed>,
+}
+
+/// `GET /api/usage/stats`. A stat is null when no sample in the window has
+/// it, e.g. `gpu` while only `stats.cpu` is subscribed.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct UsageSummary {
+    pub window_secs: u64,
+    /// Stats loop ticks in the window
+    pub samples: usize,
+    pub cpu: Option<StatSummary>,
+    pub memory_percent: Option<StatSummary>,
+    pub gpu: Option<StatSummary>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct StatSummary {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub samples: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct FanSpeed {
+    pub label: String,
+    pub rpm: u64,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -83,7 +182,12 @@ pub struct SystemUsage {
 pub struct CpuUsage {
     pub current_load: f64,
     pub current_temp: f64,
+    /// Unit of `current_temp`: "celsius" or "fahrenheit" (`display.temp_unit`)
+    pub temp_unit: String,
+    /// Average of the per-core clock speeds in GHz
     pub current_speed: f64,
+    /// 1, 5 and 15 minute load averages (Unix only)
+    pub load_avg: Option<(f64, f64, f64)>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -92,6 +196,14 @@ pub struct MemoryUsage {
     pub used: u64,
     pub free: u64,
     pub used_percent: f64,
+    /// Memory available for new allocations in bytes
+    pub available: u64,
+    /// Page cache in bytes (Linux only)
+    pub cached: Option<u64>,
+    /// Kernel buffers in bytes (Linux only)
+    pub buffers: Option<u64>,
+    pub swap_used: u64,
+    pub swap_total: u64,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -99,7 +211,12 @@ pub struct MemoryUsage {
 pub struct GpuUsage {
     pub current_load: f64,
     pub current_temp: f64,
+    /// Unit of `current_temp`: "celsius" or "fahrenheit" (`display.temp_unit`)
+    pub temp_unit: String,
     pub current_memory: i64,
+    /// How long ago these readings were taken. GPU stats are cached for
+    /// `stats.gpu_cache_seconds`, so this can be well above zero.
+    pub age_ms: u64,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -159,23 +276,194 @@ pub struct ProcessDetail {
     pub cpu: f64,
     pub title: Option<String>,
     pub has_window: bool,
+    /// Unix seconds.
+    pub start_time: u64,
+    /// Owning user's name; `None` when it can't be resolved.
+    pub user: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct GpuProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// `None` when the driver doesn't report per-process memory
+    pub gpu_memory_mb: Option<u64>,
+}
+
+/// A registered command as exposed over the API (program and args stay private).
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct CommandInfo {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ScheduledPowerInfo {
+    pub id: u64,
+    pub action: String,
+    /// Unix timestamp (seconds) when the action will run
+    pub fire_at: u64,
+    pub remaining_secs: u64,
+}
+
+/// A captured log line, as returned by `GET /api/logs`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct LogEntry {
+    /// Unix timestamp (milliseconds)
+    pub timestamp: i64,
+    /// `error`, `warn`, `info`, `debug` or `trace`
+    pub level: String,
+    /// Module that logged the line, e.g. `bridge_lib::server`
+    pub target: String,
+    pub message: String,
+}
+
+/// A client IP that has called the API, as returned by `GET /api/clients/history`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ClientSeen {
+    pub ip: String,
+    /// Unix timestamp (milliseconds) of the first request
+    pub first_seen: i64,
+    /// Unix timestamp (milliseconds) of the latest request
+    pub last_seen: i64,
+    pub request_count: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CancelPowerRequest {
+    /// Cancel only this action; omit to cancel all pending actions.
+    pub id: Option<u64>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct KillRequest {
     pub pid: Option<u32>,
     pub name: Option<String>,
+    /// Client-chosen id echoed back in the WS feedback
+    pub id: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PriorityRequest {
+    pub pid: u32,
+    /// Nice value, -20 (highest) to 19 (lowest)
+    pub priority: i32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SuspendRequest {
+    pub pid: u32,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct FocusRequest {
     pub pid: u32,
+    /// Raise every visible window of the process instead of just one (Windows only)
+    #[serde(default)]
+    pub all_windows: bool,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct LaunchRequest {
     pub path: String,
     pub args: Option<Vec<String>>,
+    /// Client-chosen id echoed back in the WS feedback
+    pub id: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct OpenUrlRequest {
+    pub url: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ClipboardContent {
+    /// `None` when the clipboard is empty or holds something other than text
+    pub text: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SetClipboardRequest {
+    pub text: String,
+}
+
+/// Body of `GET /api/autostart`, and of `POST /api/autostart`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AutostartState {
+    pub enabled: bool,
+}
+
+/// A connected display from `GET /api/displays`.
+#[derive(Serialize, Debug, Clone)]
+pub struct DisplayInfo {
+    pub id: u32,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// Position of the top-left corner on the virtual desktop
+    pub x: i32,
+    pub y: i32,
+    pub scale_factor: f32,
+    pub is_primary: bool,
+    /// Current brightness (0-100), when it can be read
+    pub brightness: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SetBrightnessRequest {
+    /// 0-100
+    pub value: u32,
+}
+
+/// Body of `POST /api/input`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputRequest {
+    Key(KeyInput),
+    Mouse(MouseInput),
+}
+
+/// Tap `key` (with `modifiers` held) or type `text`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct KeyInput {
+    /// Key name (`enter`, `f5`, `left`) or a single character
+    pub key: Option<String>,
+    /// Text to type as-is, ignoring keyboard layout
+    pub text: Option<String>,
+    /// `ctrl`, `shift`, `alt`, `meta`
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MouseInput {
+    pub action: MouseAction,
+    /// Absolute screen position; for `click`, move there first
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    #[serde(default)]
+    pub button: MouseButton,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseAction {
+    Move,
+    Click,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Right,
+    Middle,
 }
 
 // Media Types
@@ -184,6 +472,10 @@ pub struct LaunchRequest {
 pub struct MediaControlRequest {
     pub action: String,
     pub value: Option<i32>,
+    /// Send playback actions to this session only (Windows only)
+    pub app_id: Option<String>,
+    /// Client-chosen id echoed back in the WS feedback
+    pub id: Option<String>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -196,6 +488,93 @@ pub struct MediaStatus {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub supports_ctrl: bool,
+    /// Name of the default audio output device
+    pub output_device: Option<String>,
+    pub input_volume: Option<i32>,
+    pub input_muted: Option<bool>,
+    /// App id of the session the fields above describe
+    pub app_id: Option<String>,
+    /// Every media session, including the one above
+    pub sessions: Vec<MediaSession>,
+    /// Players media info is read from (macOS); empty when any app counts
+    pub supported_players: Vec<String>,
+}
+
+/// What `POST /api/media/control` accepts on this platform.
+#[derive(Serialize, Debug, Clone)]
+pub struct MediaCapabilities {
+    pub actions: Vec<String>,
+    /// Actions that can target one session with `app_id` (Windows only)
+    pub session_actions: Vec<String>,
+    /// Seeking within a track; no platform supports it yet
+    pub seek: bool,
+    /// Listing and switching output devices (`/api/media/devices`)
+    pub output_devices: bool,
+}
+
+/// What this host supports, regardless of which features the config turns
+/// on. Returned by `GET /api/capabilities`.
+#[derive(Serialize, Debug, Clone)]
+pub struct Capabilities {
+    /// `windows`, `macos` or `linux`
+    pub platform: String,
+    pub power: PowerCapabilities,
+    pub media: MediaCapabilities,
+    pub processes: ProcessCapabilities,
+    pub gpu: GpuCapabilities,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PowerCapabilities {
+    pub shutdown: bool,
+    pub restart: bool,
+    /// Only when the bridge can do it now (on Windows it must run elevated)
+    pub restart_firmware: bool,
+    pub sleep: bool,
+    pub hibernate: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProcessCapabilities {
+    pub kill: bool,
+    pub launch: bool,
+    pub focus: bool,
+    pub priority: bool,
+    pub suspend: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GpuCapabilities {
+    /// Vendor of the GPU stats are read from; `None` when none was found or
+    /// `stats.gpu_enabled` is off
+    pub vendor: Option<String>,
+    pub model: Option<String>,
+    /// Per-process GPU memory (`/api/processes/gpu`), NVIDIA only
+    pub per_process: bool,
+}
+
+/// One app's media session.
+#[derive(Serialize, Debug, Clone)]
+pub struct MediaSession {
+    /// AppUserModelID on Windows (e.g. `Spotify.exe`), app name on macOS
+    pub app_id: String,
+    pub status: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+}
+
+/// An audio output device from `GET /api/media/devices`.
+#[derive(Serialize, Debug, Clone)]
+pub struct AudioDevice {
+    /// Endpoint id on Windows, device UID on macOS
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SetAudioDeviceRequest {
+    pub id: String,
 }
 
 #[derive(Serialize, Clone, Debug, PartialEq)]
@@ -212,6 +591,8 @@ pub enum ServerStatus {
 pub struct ServerState {
     pub status: ServerStatus,
     pub port: u16,
+    /// `server.local_only` is on, so only this machine can connect.
+    pub local_only: bool,
 }
 
 // Process list payload for WebSocket broadcasting
@@ -221,6 +602,8 @@ pub struct ProcessListPayload {
     pub timestamp: i64,
     pub processes: Vec<ProcessInfo>,
     pub total_count: usize,
+    /// True when `processes` was capped by `websocket.processes.max_items`
+    pub truncated: bool,
 }
 
 // Generic operation feedback for WebSocket broadcasting
@@ -235,6 +618,36 @@ pub struct OperationFeedback {
     pub pid: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// The `id` of the command this answers, if the client sent one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// A line printed by a process started with the WS `process_run` op.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ProcessOutput {
+    /// The `id` of the `process_run` that started it, if the client sent one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub pid: Option<u32>,
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+/// Last message for a `process_run`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ProcessExit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub pid: Option<u32>,
+    /// `None` when the process was killed by a signal
+    pub code: Option<i32>,
+    /// Why the run was cut short ("timeout" or "output_limit"); `None` when
+    /// the process exited on its own
+    pub reason: Option<String>,
 }
 
 // Broadcast Types
@@ -246,6 +659,12 @@ pub enum BroadcastEvent {
     ProcessList(ProcessListPayload),
     MediaFeedback(OperationFeedback),
     ProcessFeedback(OperationFeedback),
+    ServerStatus(ServerStatus),
+    /// Reply to `get_system_info`, sent only to the requesting connection.
+    SystemInfo(SystemInfo),
+    /// `process_run` output, sent only to the requesting connection.
+    ProcessOutput(ProcessOutput),
+    ProcessExit(ProcessExit),
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -255,10 +674,60 @@ pub enum WebSocketMessage {
     ProcessKill(KillRequest),
     ProcessFocus(FocusRequest),
     ProcessLaunch(LaunchRequest),
+    /// Launch and stream the process's output back
+    ProcessRun(LaunchRequest),
     Subscribe(SubscribeRequest),
+    GetSystemInfo,
+    SetInterval(SetIntervalRequest),
+    /// Send stats as changes from the previous frame
+    SetDelta(SetDeltaRequest),
+    /// Turn a feature on or off in the saved config (admin)
+    SetFeature(SetFeatureRequest),
+    /// First message of a socket opened without a key
+    Auth(AuthRequest),
+    /// Restore the subscriptions of an earlier connection
+    Resume(ResumeRequest),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct AuthRequest {
+    pub key: String,
+}
+
+impl std::fmt::Debug for AuthRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AuthRequest { key: <redacted> }")
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ResumeRequest {
+    /// From the `session` message the earlier connection got
+    pub token: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct SubscribeRequest {
     pub topics: Vec<String>,
 }
+
+/// Switch one connection between full stats frames (the default) and deltas.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SetDeltaRequest {
+    pub enabled: bool,
+}
+
+/// A feature by its dashboard name, e.g. "media" for `enable_media`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SetFeatureRequest {
+    pub feature: String,
+    pub enabled: bool,
+}
+
+/// Slow one connection's updates for a topic without touching the shared loop.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SetIntervalRequest {
+    pub topic: String,
+    /// `None` goes back to the shared interval.
+    pub interval_ms: Option<u64>,
+}
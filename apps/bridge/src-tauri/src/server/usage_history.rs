@@ -0,0 +1,81 @@
+use crate::server::types::{StatSummary, StreamPayload, UsageSummary};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Longest window `GET /api/usage/stats` summarizes. Older samples are
+/// dropped.
+pub const MAX_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+struct Sample {
+    at: Instant,
+    cpu: Option<f64>,
+    memory: Option<f64>,
+    gpu: Option<f64>,
+}
+
+/// Loads from recent stats loop ticks. Nothing is recorded while the loop
+/// isn't running, so this costs nothing when no one is subscribed.
+#[derive(Default)]
+pub struct UsageHistory {
+    samples: VecDeque<Sample>,
+}
+
+impl UsageHistory {
+    /// Keep the parts of `payload` that were collected; the loop only reads
+    /// what its subscribers asked for.
+    pub fn record(&mut self, payload: &StreamPayload) {
+        let now = Instant::now();
+        while self
+            .samples
+            .front()
+            .is_some_and(|s| now.duration_since(s.at) > MAX_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            at: now,
+            cpu: payload.cpu.as_ref().map(|c| c.current_load),
+            memory: payload.memory.as_ref().map(|m| m.used_percent),
+            gpu: payload.gpu.as_ref().map(|g| g.current_load),
+        });
+    }
+
+    /// Min, max and average over the samples from the last `window`.
+    pub fn summary(&self, window: Duration) -> UsageSummary {
+        let now = Instant::now();
+        let recent: Vec<&Sample> = self
+            .samples
+            .iter()
+            .filter(|s| now.duration_since(s.at) <= window)
+            .collect();
+        UsageSummary {
+            window_secs: window.as_secs(),
+            samples: recent.len(),
+            cpu: summarize(recent.iter().filter_map(|s| s.cpu)),
+            memory_percent: summarize(recent.iter().filter_map(|s| s.memory)),
+            gpu: summarize(recent.iter().filter_map(|s| s.gpu)),
+        }
+    }
+}
+
+/// `None` when there are no values.
+fn summarize(values: impl Iterator<Item = f64>) -> Option<StatSummary> {
+    let mut summary: Option<StatSummary> = None;
+    let mut sum = 0.0;
+    for value in values {
+        sum += value;
+        let s = summary.get_or_insert(StatSummary {
+            min: value,
+            max: value,
+            avg: 0.0,
+            samples: 0,
+        });
+        s.min = s.min.min(value);
+        s.max = s.max.max(value);
+        s.samples += 1;
+    }
+    summary.map(|s| StatSummary {
+        avg: sum / s.samples as f64,
+        ..s
+    })
+}
@@ -1,19 +1,125 @@
 use crate::server::{
-    handlers::{subscribe_topics, unsubscribe_topics, AppState},
+    error::ApiError,
+    features,
+    handlers::{
+        build_system_info, client_limit_reached, lock_system, run_refresh, subscribe_topics,
+        unsubscribe_topics, AppState,
+    },
+    process_stream, resume,
+    send_queue::SendQueue,
+    stats_delta::StatsDelta,
     types::{BroadcastEvent, OperationFeedback, WebSocketMessage},
 };
 use crate::auth_scopes::{self, AuthContext};
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
         State,
         Extension,
     },
     response::Response,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long a socket opened without a key has to send its `auth` message.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `Sec-WebSocket-Protocol` values a client can ask for. Without one the
+/// connection uses JSON.
+const JSON_PROTOCOL: &str = "cntrl.json";
+const MSGPACK_PROTOCOL: &str = "cntrl.msgpack";
+
+/// Bounds for `set_interval`, matching the shared `interval_ms` settings.
+const MIN_INTERVAL_MS: u64 = 100;
+const MAX_INTERVAL_MS: u64 = 60000;
+
+/// How messages to a client are framed: JSON text, or MessagePack binary
+/// when the client negotiated `cntrl.msgpack`. Commands are accepted in
+/// either, whatever the connection's encoding.
+#[derive(Clone, Copy)]
+enum Encoding {
+    Json,
+    MessagePack,
+}
+
+impl Encoding {
+    fn negotiated(socket: &WebSocket) -> Self {
+        match socket.protocol().and_then(|p| p.to_str().ok()) {
+            Some(MSGPACK_PROTOCOL) => Encoding::MessagePack,
+            _ => Encoding::Json,
+        }
+    }
+
+    fn encode<T: Serialize>(self, value: &T) -> Option<Message> {
+        match self {
+            Encoding::Json => serde_json::to_string(value).ok().map(Message::Text),
+            // Structs as maps, not arrays, so the field names and the
+            // `type`/`data` tagging match the JSON events
+            Encoding::MessagePack => rmp_serde::to_vec_named(value).ok().map(Message::Binary),
+        }
+    }
+
+    /// Frame a message that was queued as JSON text (acks, errors, process
+    /// output).
+    fn encode_text(self, text: String) -> Option<Message> {
+        match self {
+            Encoding::Json => Some(Message::Text(text)),
+            Encoding::MessagePack => serde_json::from_str::<serde_json::Value>(&text)
+                .ok()
+                .and_then(|value| self.encode(&value)),
+        }
+    }
+}
+
+/// Parse a command from a text (JSON) or binary (MessagePack) frame. `None`
+/// for control frames.
+fn decode(msg: &Message) -> Option<Result<WebSocketMessage, String>> {
+    match msg {
+        Message::Text(text) => Some(serde_json::from_str(text).map_err(|e| e.to_string())),
+        Message::Binary(bytes) => Some(rmp_serde::from_slice(bytes).map_err(|e| e.to_string())),
+        _ => None,
+    }
+}
+
+/// Per-connection rates set with `set_interval`. The loops are shared, so a
+/// slower rate is approximated by forwarding only every Nth broadcast.
+#[derive(Default)]
+struct Downsample {
+    /// Requested interval and broadcasts skipped since the last one sent, by topic
+    topics: HashMap<&'static str, (u64, u64)>,
+}
+
+impl Downsample {
+    /// Whether this broadcast of `topic` should be sent, given the interval
+    /// the shared loop currently runs at.
+    fn allow(&mut self, topic: &str, loop_interval_ms: u64) -> bool {
+        let Some((requested, skipped)) = self.topics.get_mut(topic) else {
+            return true;
+        };
+        let every = ((*requested + loop_interval_ms / 2) / loop_interval_ms.max(1)).max(1);
+        if *skipped + 1 >= every {
+            *skipped = 0;
+            true
+        } else {
+            *skipped += 1;
+            false
+        }
+    }
+}
+
+/// Topics that can be downsampled. Media only broadcasts on change, so
+/// skipping one would leave the client showing a stale state.
+fn downsample_topic(topic: &str) -> Option<&'static str> {
+    match topic {
+        "stats" => Some("stats"),
+        "processes" => Some("processes"),
+        _ => None,
+    }
+}
 
 /// Expand hierarchical topic subscriptions.
 /// e.g., "stats" expands to ["stats", "stats.cpu", "stats.memory", "stats.gpu", "stats.disks", "stats.network"]
@@ -44,135 +150,312 @@ fn expand_topic(topic: &str) -> Vec<String> {
     }
 }
 
+/// `auth_ctx` is missing when the upgrade carried no key; the socket must
+/// then authenticate with its first message.
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
-    Extension(auth_ctx): Extension<AuthContext>,
+    auth_ctx: Option<Extension<AuthContext>>,
 ) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, state, auth_ctx))
+    // Browsers can't read the body of a refused upgrade, so accept it and
+    // explain in the close frame instead
+    let ws = ws.protocols([JSON_PROTOCOL, MSGPACK_PROTOCOL]);
+    if let Some(max) = client_limit_reached(&state) {
+        return ws.on_upgrade(move |socket| reject_socket(socket, max));
+    }
+    ws.on_upgrade(|socket| async move {
+        let (socket, auth_ctx) = match auth_ctx {
+            Some(Extension(auth_ctx)) => (socket, auth_ctx),
+            None => match authenticate(socket, &state).await {
+                Some(authenticated) => authenticated,
+                None => return,
+            },
+        };
+        handle_socket(socket, state, auth_ctx).await
+    })
+}
+
+/// Wait for the `auth` op on a socket opened without a key. On success the
+/// client gets an `authenticated` message; otherwise an `error` and the
+/// socket is closed.
+async fn authenticate(
+    mut socket: WebSocket,
+    state: &Arc<AppState>,
+) -> Option<(WebSocket, AuthContext)> {
+    let encoding = Encoding::negotiated(&socket);
+    let deadline = tokio::time::Instant::now() + AUTH_TIMEOUT;
+    let result = loop {
+        let auth = match tokio::time::timeout_at(deadline, socket.recv()).await {
+            Err(_) => {
+                break Err(ApiError::unauthorized(
+                    "AUTH_TIMEOUT",
+                    format!(
+                        "Send an auth message within {} seconds",
+                        AUTH_TIMEOUT.as_secs()
+                    ),
+                ))
+            }
+            Ok(Some(Ok(Message::Close(_)) | Err(_)) | None) => return None,
+            Ok(Some(Ok(msg))) => match decode(&msg) {
+                Some(Ok(WebSocketMessage::Auth(req))) => Some(req),
+                Some(_) => None,
+                None => continue,
+            },
+        };
+        let Some(req) = auth else {
+            break Err(ApiError::unauthorized(
+                "MISSING_KEY",
+                "The first message must be {\"op\": \"auth\", \"data\": {\"key\": ...}}",
+            ));
+        };
+
+        let env_api_key = state.config.lock().unwrap().auth.api_key.clone();
+        let auth_state = state.auth_state.lock().unwrap().clone();
+        break match crate::server::key_context(env_api_key.as_deref(), &auth_state, &req.key) {
+            Ok(auth_ctx) if !auth_ctx.has_scope(auth_scopes::SCOPE_WS_CONNECT) => {
+                Err(ApiError::new(
+                    axum::http::StatusCode::FORBIDDEN,
+                    "FORBIDDEN",
+                    "Key lacks the ws:connect scope",
+                ))
+            }
+            result => result,
+        };
+    };
+
+    match result {
+        Ok(auth_ctx) => {
+            let reply = serde_json::json!({
+                "type": "authenticated",
+                "data": { "scopes": auth_ctx.scopes }
+            });
+            socket.send(encoding.encode(&reply)?).await.ok()?;
+            Some((socket, auth_ctx))
+        }
+        Err(e) => {
+            let reply = serde_json::json!({ "type": "error", "data": e });
+            if let Some(msg) = encoding.encode(&reply) {
+                let _ = socket.send(msg).await;
+            }
+            let frame = CloseFrame {
+                code: close_code::POLICY,
+                reason: e.message.into(),
+            };
+            let _ = socket.send(Message::Close(Some(frame))).await;
+            None
+        }
+    }
+}
+
+async fn reject_socket(mut socket: WebSocket, max_clients: usize) {
+    let frame = CloseFrame {
+        code: close_code::AGAIN,
+        reason: format!("Too many clients connected (max {})", max_clients).into(),
+    };
+    let _ = socket.send(Message::Close(Some(frame))).await;
 }
 
 async fn handle_socket(socket: WebSocket, state: Arc<AppState>, auth_ctx: AuthContext) {
+    let encoding = Encoding::negotiated(&socket);
     let (mut sender, mut receiver) = socket.split();
     let mut rx = state.broadcast_tx.subscribe();
 
     // Channel for sending messages from recv_task (errors, acks)
     let (outgoing_tx, mut outgoing_rx) = tokio::sync::mpsc::channel::<String>(32);
 
+    // Lets the client get this connection's subscriptions back after a drop
+    let resume_token = state.resume_tokens.lock().unwrap().issue();
+    let session = serde_json::json!({
+        "type": "session",
+        "data": { "resume_token": resume_token, "ttl_secs": resume::RESUME_TTL.as_secs() }
+    });
+    let _ = outgoing_tx.send(session.to_string()).await;
+
     // Local subscription state for THIS connection
     // Starts EMPTY (None). User must subscribe to get data.
     let subscriptions: Arc<Mutex<Option<HashSet<String>>>> = Arc::new(Mutex::new(None));
+    let downsample: Arc<Mutex<Downsample>> = Arc::new(Mutex::new(Downsample::default()));
+    let stats_delta: Arc<Mutex<StatsDelta>> = Arc::new(Mutex::new(StatsDelta::default()));
 
-    // SEND TASK - handles both broadcast events and outgoing messages from recv_task
+    // SEND TASK - handles both broadcast events and outgoing messages from recv_task.
+    // Frames are queued and written by `writer`, so a slow client doesn't stop
+    // this task from keeping up with the broadcast.
     let mut send_task = tokio::spawn({
         let subs = subscriptions.clone();
+        let downsample = downsample.clone();
+        let stats_delta = stats_delta.clone();
+        let state = state.clone();
         let mut shutdown_rx = state.shutdown.clone();
         async move {
-            loop {
-                tokio::select! {
-                    // Server is shutting down — close this connection
-                    _ = shutdown_rx.changed() => {
-                        let _ = sender.close().await;
+            let queue = SendQueue::default();
+            let writer = async {
+                while let Some((msg, dropped)) = queue.pop().await {
+                    // Stats or process lists were superseded while the client
+                    // was still reading older frames
+                    if dropped > 0 {
+                        let notice = serde_json::json!({
+                            "type": "lagged",
+                            "data": { "skipped": dropped }
+                        });
+                        if let Some(notice) = encoding.encode(&notice) {
+                            if sender.send(notice).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    if sender.send(msg).await.is_err() {
                         break;
                     }
-                    // Handle outgoing messages from recv_task (errors, acks)
-                    Some(msg) = outgoing_rx.recv() => {
-                        if sender.send(Message::Text(msg)).await.is_err() {
+                }
+                queue.close();
+                let _ = sender.close().await;
+            };
+
+            let reader = async {
+                loop {
+                    tokio::select! {
+                        // Server is shutting down — close this connection
+                        _ = shutdown_rx.changed() => {
+                            // Flush the final server status so subscribers know why
+                            // the socket is closing
+                            let wants_status = subs.lock().unwrap().as_ref().map_or(false, |t| t.contains("server"));
+                            while let Ok(event) = rx.try_recv() {
+                                if wants_status && matches!(event, BroadcastEvent::ServerStatus(_)) {
+                                    if let Some(msg) = encoding.encode(&event) {
+                                        queue.push(msg).await;
+                                    }
+                                }
+                            }
                             break;
                         }
-                    }
-                    // Handle broadcast events
-                    result = rx.recv() => {
-                        let event = match result {
-                            Ok(e) => e,
-                            Err(_) => break,
-                        };
-
-                        match event {
-                            BroadcastEvent::SystemStats(payload) => {
-                                let msg_opt = {
-                                    let subs_lock = subs.lock().unwrap();
-                                    if let Some(topics) = &*subs_lock {
-                                        let mut filtered = payload.clone();
-                                        let mut has_content = false;
-
-                                        let has_cpu = topics.contains("cpu") || topics.contains("stats.cpu") || topics.contains("stats");
-                                        let has_memory = topics.contains("memory") || topics.contains("stats.memory") || topics.contains("stats");
-                                        let has_gpu = topics.contains("gpu") || topics.contains("stats.gpu") || topics.contains("stats");
-                                        let has_disks = topics.contains("disks") || topics.contains("stats.disks") || topics.contains("stats");
-                                        let has_network = topics.contains("network") || topics.contains("stats.network") || topics.contains("stats");
-
-                                        if !has_cpu { filtered.cpu = None; } else { has_content = true; }
-                                        if !has_memory { filtered.memory = None; } else { has_content = true; }
-                                        if !has_gpu { filtered.gpu = None; } else { has_content = true; }
-                                        if !has_disks { filtered.disks = None; } else { has_content = true; }
-                                        if !has_network { filtered.network = None; } else { has_content = true; }
-                                        if !topics.contains("media") { filtered.media = None; }
-
-                                        if has_content || topics.contains("system") {
-                                            Some(BroadcastEvent::SystemStats(filtered))
+                        // Handle outgoing messages from recv_task (errors, acks)
+                        Some(text) = outgoing_rx.recv() => {
+                            let Some(msg) = encoding.encode_text(text) else { continue };
+                            if !queue.push(msg).await {
+                                break;
+                            }
+                        }
+                        // Handle broadcast events
+                        result = rx.recv() => {
+                            let event = match result {
+                                Ok(e) => e,
+                                // This client fell behind and missed the oldest events;
+                                // tell it instead of silently carrying on
+                                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                                    let notice = serde_json::json!({
+                                        "type": "lagged",
+                                        "data": { "skipped": skipped }
+                                    });
+                                    if let Some(msg) = encoding.encode(&notice) {
+                                        if !queue.push(msg).await {
+                                            break;
+                                        }
+                                    }
+                                    continue;
+                                }
+                                Err(_) => break,
+                            };
+
+                            match event {
+                                BroadcastEvent::SystemStats(payload) => {
+                                    let msg_opt = {
+                                        let subs_lock = subs.lock().unwrap();
+                                        if let Some(topics) = &*subs_lock {
+                                            let mut filtered = payload.clone();
+                                            let mut has_content = false;
+
+                                            let has_cpu = topics.contains("cpu") || topics.contains("stats.cpu") || topics.contains("stats");
+                                            let has_memory = topics.contains("memory") || topics.contains("stats.memory") || topics.contains("stats");
+                                            let has_gpu = topics.contains("gpu") || topics.contains("stats.gpu") || topics.contains("stats");
+                                            let has_disks = topics.contains("disks") || topics.contains("stats.disks") || topics.contains("stats");
+                                            let has_network = topics.contains("network") || topics.contains("stats.network") || topics.contains("stats");
+
+                                            if !has_cpu { filtered.cpu = None; } else { has_content = true; }
+                                            if !has_memory { filtered.memory = None; } else { has_content = true; }
+                                            if !has_gpu { filtered.gpu = None; } else { has_content = true; }
+                                            if !has_disks { filtered.disks = None; } else { has_content = true; }
+                                            if !has_network { filtered.network = None; } else { has_content = true; }
+                                            if !topics.contains("media") { filtered.media = None; }
+
+                                            let interval = state.config.lock().unwrap().websocket.stats.interval_ms;
+                                            if (has_content || topics.contains("system"))
+                                                && downsample.lock().unwrap().allow("stats", interval)
+                                            {
+                                                Some(filtered)
+                                            } else {
+                                                None
+                                            }
                                         } else {
                                             None
                                         }
-                                    } else {
-                                        None
-                                    }
-                                };
+                                    };
 
-                                if let Some(msg) = msg_opt {
-                                    if let Ok(text) = serde_json::to_string(&msg) {
-                                        if sender.send(Message::Text(text)).await.is_err() { break; }
+                                    if let Some(filtered) = msg_opt {
+                                        let delta = stats_delta.lock().unwrap().next(&filtered);
+                                        let queued = match delta {
+                                            // Each delta builds on the frame before it, so
+                                            // none may be replaced while queued
+                                            Some(frame) => match encoding.encode(&frame) {
+                                                Some(msg) => queue.push(msg).await,
+                                                None => true,
+                                            },
+                                            None => match encoding.encode(&BroadcastEvent::SystemStats(filtered)) {
+                                                Some(msg) => queue.push_snapshot("stats", msg).await,
+                                                None => true,
+                                            },
+                                        };
+                                        if !queued { break; }
                                     }
                                 }
-                            }
-                            BroadcastEvent::MediaUpdate(status) => {
-                                let should_send = {
-                                    let subs_lock = subs.lock().unwrap();
-                                    subs_lock.as_ref().map_or(false, |t| t.contains("media") || t.contains("stats.media"))
-                                };
-                                if should_send {
-                                    if let Ok(text) = serde_json::to_string(&BroadcastEvent::MediaUpdate(status)) {
-                                        if sender.send(Message::Text(text)).await.is_err() { break; }
-                                    }
-                                }
-                            }
-                            BroadcastEvent::ProcessList(payload) => {
-                                let should_send = {
-                                    let subs_lock = subs.lock().unwrap();
-                                    subs_lock.as_ref().map_or(false, |t| t.contains("processes") || t.contains("process"))
-                                };
-                                if should_send {
-                                    if let Ok(text) = serde_json::to_string(&BroadcastEvent::ProcessList(payload)) {
-                                        if sender.send(Message::Text(text)).await.is_err() { break; }
+                                BroadcastEvent::MediaUpdate(status) => {
+                                    let should_send = {
+                                        let subs_lock = subs.lock().unwrap();
+                                        subs_lock.as_ref().map_or(false, |t| t.contains("media") || t.contains("stats.media"))
+                                    };
+                                    if should_send {
+                                        if let Some(msg) = encoding.encode(&BroadcastEvent::MediaUpdate(status)) {
+                                            if !queue.push(msg).await { break; }
+                                        }
                                     }
                                 }
-                            }
-                            BroadcastEvent::MediaFeedback(feedback) => {
-                                let should_send = {
-                                    let subs_lock = subs.lock().unwrap();
-                                    subs_lock.as_ref().map_or(false, |t| t.contains("media") || t.contains("stats.media"))
-                                };
-                                if should_send {
-                                    if let Ok(text) = serde_json::to_string(&BroadcastEvent::MediaFeedback(feedback)) {
-                                        if sender.send(Message::Text(text)).await.is_err() { break; }
+                                BroadcastEvent::ProcessList(payload) => {
+                                    let should_send = {
+                                        let subs_lock = subs.lock().unwrap();
+                                        subs_lock.as_ref().map_or(false, |t| t.contains("processes") || t.contains("process"))
+                                    };
+                                    let interval = state.config.lock().unwrap().websocket.processes.interval_ms;
+                                    if should_send && downsample.lock().unwrap().allow("processes", interval) {
+                                        if let Some(msg) = encoding.encode(&BroadcastEvent::ProcessList(payload)) {
+                                            if !queue.push_snapshot("processes", msg).await { break; }
+                                        }
                                     }
                                 }
-                            }
-                            BroadcastEvent::ProcessFeedback(feedback) => {
-                                let should_send = {
-                                    let subs_lock = subs.lock().unwrap();
-                                    subs_lock.as_ref().map_or(false, |t| t.contains("processes") || t.contains("process"))
-                                };
-                                if should_send {
-                                    if let Ok(text) = serde_json::to_string(&BroadcastEvent::ProcessFeedback(feedback)) {
-                                        if sender.send(Message::Text(text)).await.is_err() { break; }
+                                BroadcastEvent::ServerStatus(status) => {
+                                    let should_send = {
+                                        let subs_lock = subs.lock().unwrap();
+                                        subs_lock.as_ref().map_or(false, |t| t.contains("server"))
+                                    };
+                                    if should_send {
+                                        if let Some(msg) = encoding.encode(&BroadcastEvent::ServerStatus(status)) {
+                                            if !queue.push(msg).await { break; }
+                                        }
                                     }
                                 }
+                                // Command feedback, system info replies and process
+                                // output only ever go directly to the requesting connection
+                                BroadcastEvent::MediaFeedback(_)
+                                | BroadcastEvent::ProcessFeedback(_)
+                                | BroadcastEvent::SystemInfo(_)
+                                | BroadcastEvent::ProcessOutput(_)
+                                | BroadcastEvent::ProcessExit(_) => {}
                             }
                         }
                     }
                 }
-            }
+                queue.close();
+            };
+
+            tokio::join!(writer, reader);
         }
     });
 
@@ -182,12 +465,16 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, auth_ctx: AuthCo
         let subs = subscriptions.clone();
         let tx = outgoing_tx;
         let auth_ctx = auth_ctx.clone();
+        let resume_token = resume_token.clone();
+        let run_slots = Arc::new(tokio::sync::Semaphore::new(
+            process_stream::MAX_CONCURRENT_RUNS,
+        ));
         async move {
             while let Some(result) = receiver.next().await {
                 match result {
                     Ok(msg) => {
-                        if let Message::Text(text) = msg {
-                            match serde_json::from_str::<WebSocketMessage>(&text) {
+                        if let Some(parsed) = decode(&msg) {
+                            match parsed {
                                 Ok(cmd) => {
                                     if !auth_ctx.is_public() {
                                         if let Some(scope) =
@@ -237,12 +524,6 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, auth_ctx: AuthCo
                                                 }
                                             }
 
-                                            // Get old subscriptions
-                                            let old_subs = {
-                                                let lock = subs.lock().unwrap();
-                                                lock.clone()
-                                            };
-
                                             // Expand hierarchical topics
                                             let mut new_set = HashSet::new();
                                             for t in req.topics {
@@ -252,22 +533,191 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, auth_ctx: AuthCo
                                                 }
                                             }
 
-                                            // Unsubscribe from old topics
-                                            if let Some(old_set) = &old_subs {
-                                                let old_refs: Vec<&str> =
-                                                    old_set.iter().map(|s| s.as_str()).collect();
-                                                unsubscribe_topics(&state, &old_refs);
-                                            }
-
-                                            // Subscribe to new topics
-                                            let new_refs: Vec<&str> =
-                                                new_set.iter().map(|s| s.as_str()).collect();
-                                            subscribe_topics(&state, &new_refs);
+                                            set_subscriptions(
+                                                &state,
+                                                &subs,
+                                                &resume_token,
+                                                new_set,
+                                                &tx,
+                                            )
+                                            .await;
+                                        }
+                                        WebSocketMessage::Resume(req) => {
+                                            let saved = state
+                                                .resume_tokens
+                                                .lock()
+                                                .unwrap()
+                                                .take(&req.token);
+                                            let Some(topics) = saved else {
+                                                let error_msg = serde_json::json!({
+                                                    "type": "error",
+                                                    "data": {
+                                                        "code": "RESUME_FAILED",
+                                                        "message": "Resume token unknown or expired; subscribe again"
+                                                    }
+                                                });
+                                                let _ = tx.send(error_msg.to_string()).await;
+                                                continue;
+                                            };
 
-                                            // Update local subscription state
+                                            let mut list: Vec<String> =
+                                                topics.iter().cloned().collect();
+                                            list.sort();
+                                            if !auth_ctx.is_public()
+                                                && !auth_scopes::required_scopes_for_topics(&list)
+                                                    .iter()
+                                                    .all(|s| auth_ctx.has_scope(s))
                                             {
-                                                let mut lock = subs.lock().unwrap();
-                                                *lock = Some(new_set);
+                                                let error_msg = serde_json::json!({
+                                                    "type": "error",
+                                                    "data": {
+                                                        "code": "FORBIDDEN",
+                                                        "message": "Insufficient scope for topics"
+                                                    }
+                                                });
+                                                let _ = tx.send(error_msg.to_string()).await;
+                                                continue;
+                                            }
+
+                                            let resumed = serde_json::json!({
+                                                "type": "resumed",
+                                                "data": { "topics": list }
+                                            });
+                                            let _ = tx.send(resumed.to_string()).await;
+                                            set_subscriptions(
+                                                &state,
+                                                &subs,
+                                                &resume_token,
+                                                topics,
+                                                &tx,
+                                            )
+                                            .await;
+                                        }
+                                        WebSocketMessage::SetInterval(req) => {
+                                            let error = match (
+                                                downsample_topic(&req.topic.to_lowercase()),
+                                                req.interval_ms,
+                                            ) {
+                                                (None, _) => Some((
+                                                    "UNSUPPORTED_TOPIC",
+                                                    format!(
+                                                        "set_interval supports stats and processes (got \"{}\")",
+                                                        req.topic
+                                                    ),
+                                                )),
+                                                (Some(_), Some(ms))
+                                                    if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS)
+                                                        .contains(&ms) =>
+                                                {
+                                                    Some((
+                                                        "INVALID_INTERVAL",
+                                                        format!(
+                                                            "interval_ms must be between {} and {}",
+                                                            MIN_INTERVAL_MS, MAX_INTERVAL_MS
+                                                        ),
+                                                    ))
+                                                }
+                                                (Some(topic), Some(ms)) => {
+                                                    downsample
+                                                        .lock()
+                                                        .unwrap()
+                                                        .topics
+                                                        .insert(topic, (ms, 0));
+                                                    None
+                                                }
+                                                (Some(topic), None) => {
+                                                    downsample.lock().unwrap().topics.remove(topic);
+                                                    None
+                                                }
+                                            };
+                                            if let Some((code, message)) = error {
+                                                let error_msg = serde_json::json!({
+                                                    "type": "error",
+                                                    "data": { "code": code, "message": message }
+                                                });
+                                                let _ = tx.send(error_msg.to_string()).await;
+                                            }
+                                        }
+                                        WebSocketMessage::SetDelta(req) => {
+                                            stats_delta.lock().unwrap().set_enabled(req.enabled);
+                                        }
+                                        WebSocketMessage::SetFeature(req) => {
+                                            let feature = req.feature.to_lowercase();
+                                            let state = state.clone();
+                                            let result = {
+                                                let feature = feature.clone();
+                                                tokio::task::spawn_blocking(move || {
+                                                    features::set_feature(
+                                                        &state.config,
+                                                        &feature,
+                                                        req.enabled,
+                                                    )
+                                                })
+                                                .await
+                                                .unwrap_or_else(|e| Err(e.to_string()))
+                                            };
+                                            let reply = match result {
+                                                Ok(()) => serde_json::json!({
+                                                    "type": "feature_updated",
+                                                    "data": {
+                                                        "feature": feature,
+                                                        "enabled": req.enabled
+                                                    }
+                                                }),
+                                                Err(message) => serde_json::json!({
+                                                    "type": "error",
+                                                    "data": {
+                                                        "code": "SET_FEATURE_FAILED",
+                                                        "message": message
+                                                    }
+                                                }),
+                                            };
+                                            let _ = tx.send(reply.to_string()).await;
+                                        }
+                                        WebSocketMessage::GetSystemInfo => {
+                                            let enabled =
+                                                state.config.lock().unwrap().features.enable_system;
+                                            let info = if enabled {
+                                                let state = state.clone();
+                                                run_refresh(move || build_system_info(&state)).await
+                                            } else {
+                                                Err(ApiError::feature_disabled(
+                                                    "System info disabled",
+                                                ))
+                                            };
+                                            let reply = match info {
+                                                Ok(info) => serde_json::to_string(
+                                                    &BroadcastEvent::SystemInfo(info),
+                                                ),
+                                                Err(e) => serde_json::to_string(
+                                                    &serde_json::json!({"type": "error", "data": e}),
+                                                ),
+                                            };
+                                            if let Ok(text) = reply {
+                                                let _ = tx.send(text).await;
+                                            }
+                                        }
+                                        WebSocketMessage::ProcessRun(req) => {
+                                            let refusal = process_stream::refusal(&state, &req);
+                                            let slot = run_slots.clone().try_acquire_owned();
+                                            match (refusal, slot) {
+                                                (None, Ok(slot)) => {
+                                                    tokio::spawn(process_stream::run(
+                                                        req,
+                                                        tx.clone(),
+                                                        slot,
+                                                    ));
+                                                }
+                                                (refusal, _) => {
+                                                    let feedback = refusal.unwrap_or_else(|| {
+                                                        process_stream::busy(&req)
+                                                    });
+                                                    if let Ok(text) =
+                                                        serde_json::to_string(&feedback)
+                                                    {
+                                                        let _ = tx.send(text).await;
+                                                    }
+                                                }
                                             }
                                         }
                                         ref other => {
@@ -275,9 +725,13 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, auth_ctx: AuthCo
                                             let feedback =
                                                 handle_ws_command(other.clone(), &state).await;
 
-                                            // Broadcast feedback to all relevant subscribers
+                                            // Acks go only to the client that sent the
+                                            // command; resulting state changes (e.g. the
+                                            // next process list) are still broadcast
                                             if let Some(fb) = feedback {
-                                                let _ = state.broadcast_tx.send(fb);
+                                                if let Ok(text) = serde_json::to_string(&fb) {
+                                                    let _ = tx.send(text).await;
+                                                }
                                             }
                                         }
                                     }
@@ -324,6 +778,44 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, auth_ctx: AuthCo
         let topic_refs: Vec<&str> = old_set.iter().map(|s| s.as_str()).collect();
         crate::server::handlers::unsubscribe_topics(&state, &topic_refs);
     }
+    state.resume_tokens.lock().unwrap().release(&resume_token);
+}
+
+/// Replace this connection's subscriptions with `topics` (already expanded),
+/// moving the topic ref counts over and saving them under its resume token.
+async fn set_subscriptions(
+    state: &Arc<AppState>,
+    subs: &Mutex<Option<HashSet<String>>>,
+    resume_token: &str,
+    topics: HashSet<String>,
+    tx: &tokio::sync::mpsc::Sender<String>,
+) {
+    let old_subs = subs.lock().unwrap().clone();
+    if let Some(old_set) = &old_subs {
+        let old_refs: Vec<&str> = old_set.iter().map(|s| s.as_str()).collect();
+        unsubscribe_topics(state, &old_refs);
+    }
+    let new_refs: Vec<&str> = topics.iter().map(|s| s.as_str()).collect();
+    subscribe_topics(state, &new_refs);
+
+    // New "server" subscribers get the current status right away instead of
+    // waiting for the next transition
+    let newly_server =
+        topics.contains("server") && !old_subs.as_ref().map_or(false, |s| s.contains("server"));
+
+    state
+        .resume_tokens
+        .lock()
+        .unwrap()
+        .update(resume_token, &topics);
+    *subs.lock().unwrap() = Some(topics);
+
+    if newly_server {
+        let status = state.status.borrow().clone();
+        if let Ok(text) = serde_json::to_string(&BroadcastEvent::ServerStatus(status)) {
+            let _ = tx.send(text).await;
+        }
+    }
 }
 
 async fn handle_ws_command(cmd: WebSocketMessage, state: &Arc<AppState>) -> Option<BroadcastEvent> {
@@ -336,6 +828,7 @@ async fn handle_ws_command(cmd: WebSocketMessage, state: &Arc<AppState>) -> Opti
                     message: Some("Media control disabled".to_string()),
                     pid: None,
                     name: None,
+                    id: req.id,
                 }));
             }
 
@@ -345,27 +838,59 @@ async fn handle_ws_command(cmd: WebSocketMessage, state: &Arc<AppState>) -> Opti
 
             if action == "set_volume" {
                 if let Some(vol) = req.value {
-                    if unsafe { crate::server::media::set_volume(vol) }.is_none() {
+                    if let Err(e) = unsafe { crate::server::media::set_volume(vol) } {
                         success = false;
-                        error_msg = Some("Failed to set volume".to_string());
+                        error_msg = Some(format!("Failed to set volume: {}", e));
                     }
                 } else {
                     success = false;
                     error_msg = Some("Value required for set_volume".to_string());
                 }
+            } else if action == "volume_up" || action == "volume_down" {
+                let step = state.config.lock().unwrap().media.volume_step as i32;
+                let delta = if action == "volume_up" { step } else { -step };
+                if let Err(e) = unsafe { crate::server::media::step_volume(delta) } {
+                    success = false;
+                    error_msg = Some(format!("Failed to change volume: {}", e));
+                }
+            } else if action == "set_input_volume" {
+                if let Some(vol) = req.value {
+                    if let Err(e) = unsafe { crate::server::media::set_input_volume(vol) } {
+                        success = false;
+                        error_msg = Some(format!("Failed to set input volume: {}", e));
+                    }
+                } else {
+                    success = false;
+                    error_msg = Some("Value required for set_input_volume".to_string());
+                }
+            } else if action == "mute_input" || action == "unmute_input" {
+                if let Err(e) =
+                    unsafe { crate::server::media::set_input_mute(action == "mute_input") }
+                {
+                    success = false;
+                    error_msg = Some(format!("Failed to set input mute state: {}", e));
+                }
             } else if action == "mute" || action == "unmute" || action == "toggle_mute" {
                 #[cfg(target_os = "macos")]
                 {
-                    if action == "toggle_mute" {
-                        crate::server::media::run_media_action("toggle_mute").await;
+                    let result = if action == "toggle_mute" {
+                        unsafe { crate::server::media::toggle_mute() }
                     } else {
-                        unsafe { crate::server::media::set_mute(action == "mute") };
+                        unsafe { crate::server::media::set_mute(action == "mute") }
+                    };
+                    if let Err(e) = result {
+                        success = false;
+                        error_msg = Some(format!("Failed to set mute state: {}", e));
                     }
                 }
                 #[cfg(target_os = "windows")]
                 {
                     if action != "toggle_mute" {
-                        unsafe { crate::server::media::set_mute(action == "mute") };
+                        if let Err(e) = unsafe { crate::server::media::set_mute(action == "mute") }
+                        {
+                            success = false;
+                            error_msg = Some(format!("Failed to set mute state: {}", e));
+                        }
                     } else {
                         trigger_windows_media_key("mute");
                     }
@@ -373,11 +898,42 @@ async fn handle_ws_command(cmd: WebSocketMessage, state: &Arc<AppState>) -> Opti
             } else {
                 #[cfg(target_os = "macos")]
                 {
-                    crate::server::media::run_media_action(action).await;
+                    use crate::server::media::PlayerError;
+
+                    let error = match crate::server::media::run_media_action(action).await {
+                        Ok(()) => None,
+                        Err(PlayerError::NoPlayer) => {
+                            Some("No supported media player is running".to_string())
+                        }
+                        Err(PlayerError::Unsupported) => {
+                            Some(format!("The current player can't {}", action))
+                        }
+                        Err(PlayerError::Failed(e)) => Some(e),
+                    };
+                    if error.is_some() {
+                        success = false;
+                        error_msg = error;
+                    }
                 }
                 #[cfg(target_os = "windows")]
                 {
-                    trigger_windows_media_key(action);
+                    match req.app_id.as_deref() {
+                        Some(app_id) if crate::server::media::SESSION_ACTIONS.contains(&action) => {
+                            match crate::server::media::control_session(app_id, action).await {
+                                Some(true) => {}
+                                Some(false) => {
+                                    success = false;
+                                    error_msg =
+                                        Some(format!("{} did not accept {}", app_id, action));
+                                }
+                                None => {
+                                    success = false;
+                                    error_msg = Some(format!("No media session for {}", app_id));
+                                }
+                            }
+                        }
+                        _ => trigger_windows_media_key(action),
+                    }
                 }
             }
 
@@ -387,62 +943,119 @@ async fn handle_ws_command(cmd: WebSocketMessage, state: &Arc<AppState>) -> Opti
                 message: error_msg,
                 pid: None,
                 name: None,
+                id: req.id,
             }))
         }
         WebSocketMessage::ProcessKill(req) => {
-            if !state.config.lock().unwrap().features.enable_processes {
+            let disabled = {
+                let features = &state.config.lock().unwrap().features;
+                if !features.enable_processes {
+                    Some("Process control disabled")
+                } else if !features.enable_process_kill {
+                    Some("Killing processes disabled")
+                } else {
+                    None
+                }
+            };
+            if let Some(message) = disabled {
                 return Some(BroadcastEvent::ProcessFeedback(OperationFeedback {
                     success: false,
                     action: "kill".to_string(),
-                    message: Some("Process control disabled".to_string()),
+                    message: Some(message.to_string()),
+                    pid: req.pid,
+                    name: req.name,
+                    id: req.id,
+                }));
+            }
+            if state.config.lock().unwrap().safe_mode {
+                return Some(BroadcastEvent::ProcessFeedback(OperationFeedback {
+                    success: true,
+                    action: "kill".to_string(),
+                    message: Some("Dry run: safe_mode is on".to_string()),
                     pid: req.pid,
                     name: req.name,
+                    id: req.id,
                 }));
             }
 
-            let mut sys = state.system.lock().unwrap();
-            sys.refresh_processes();
+            let killed = {
+                let state = state.clone();
+                let (pid, name) = (req.pid, req.name.clone());
+                run_refresh(move || {
+                    let mut sys = lock_system(&state.processes);
+                    sys.refresh_processes();
 
-            let mut success = false;
-            let mut killed_name: Option<String> = None;
+                    let mut success = false;
+                    let mut killed_name: Option<String> = None;
 
-            if let Some(pid) = req.pid {
-                if let Some(process) = sys.process(sysinfo::Pid::from(pid as usize)) {
-                    killed_name = Some(process.name().to_string());
-                    success = process.kill();
-                }
-            } else if let Some(ref name) = req.name {
-                for process in sys.processes().values() {
-                    if process.name() == name {
-                        if process.kill() {
-                            success = true;
-                            killed_name = Some(name.clone());
-                            break;
+                    if let Some(pid) = pid {
+                        if let Some(process) = sys.process(sysinfo::Pid::from(pid as usize)) {
+                            killed_name = Some(process.name().to_string());
+                            success = process.kill();
+                        }
+                    } else if let Some(name) = name {
+                        for process in sys.processes().values() {
+                            if process.name() == name {
+                                if process.kill() {
+                                    success = true;
+                                    killed_name = Some(name);
+                                    break;
+                                }
+                            }
                         }
                     }
-                }
-            }
+                    (success, killed_name)
+                })
+                .await
+            };
+            let (success, killed_name, message) = match killed {
+                Ok((true, killed_name)) => (true, killed_name, None),
+                Ok((false, killed_name)) => (
+                    false,
+                    killed_name,
+                    Some("Process not found or could not be killed".to_string()),
+                ),
+                Err(e) => (false, None, Some(e.message)),
+            };
 
             Some(BroadcastEvent::ProcessFeedback(OperationFeedback {
                 success,
                 action: "kill".to_string(),
-                message: if success {
-                    None
-                } else {
-                    Some("Process not found or could not be killed".to_string())
-                },
+                message,
                 pid: req.pid,
                 name: killed_name.or(req.name),
+                id: req.id,
             }))
         }
         WebSocketMessage::ProcessLaunch(req) => {
-            if !state.config.lock().unwrap().features.enable_processes {
+            let disabled = {
+                let features = &state.config.lock().unwrap().features;
+                if !features.enable_processes {
+                    Some("Process control disabled")
+                } else if !features.enable_process_launch {
+                    Some("Launching processes disabled")
+                } else {
+                    None
+                }
+            };
+            if let Some(message) = disabled {
                 return Some(BroadcastEvent::ProcessFeedback(OperationFeedback {
                     success: false,
                     action: "launch".to_string(),
-                    message: Some("Process control disabled".to_string()),
+                    message: Some(message.to_string()),
+                    pid: None,
+                    name: Some(req.path),
+                    id: req.id,
+                }));
+            }
+            if state.config.lock().unwrap().safe_mode {
+                return Some(BroadcastEvent::ProcessFeedback(OperationFeedback {
+                    success: true,
+                    action: "launch".to_string(),
+                    message: Some("Dry run: safe_mode is on".to_string()),
                     pid: None,
                     name: Some(req.path),
+                    id: req.id,
                 }));
             }
 
@@ -461,6 +1074,7 @@ async fn handle_ws_command(cmd: WebSocketMessage, state: &Arc<AppState>) -> Opti
                 message,
                 pid: None,
                 name: Some(req.path),
+                id: req.id,
             }))
         }
         WebSocketMessage::ProcessFocus(_) => {
@@ -476,12 +1090,10 @@ fn trigger_windows_media_key(action: &str) {
     unsafe {
         use windows::Win32::UI::Input::KeyboardAndMouse::{
             SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_MEDIA_NEXT_TRACK,
-            VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_VOLUME_DOWN, VK_VOLUME_MUTE, VK_VOLUME_UP,
+            VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_VOLUME_MUTE,
         };
 
         let vk = match action {
-            "volume_up" => Some(VK_VOLUME_UP),
-            "volume_down" => Some(VK_VOLUME_DOWN),
             "mute" | "toggle_mute" => Some(VK_VOLUME_MUTE),
             "next" => Some(VK_MEDIA_NEXT_TRACK),
             "prev" | "previous" => Some(VK_MEDIA_PREV_TRACK),
@@ -1,11 +1,17 @@
+use crate::auth_store::{AuthMode, AuthState};
+use crate::config::AppConfig;
+use crate::server::types::ServerStatus;
+use crate::ServerControl;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
     webview::PageLoadPayload,
     Manager, Runtime, WebviewUrl, WebviewWindowBuilder,
     window::Color,
 };
+use tracing::error;
 
 /// Set to `true` right before an intentional quit so the
 /// `RunEvent::ExitRequested` handler knows not to prevent it.
@@ -38,19 +44,170 @@ pub fn show_or_create_window<R: Runtime>(app: &tauri::AppHandle<R>) {
     }
 }
 
-pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
+fn status_label(status: &ServerStatus) -> &'static str {
+    match status {
+        ServerStatus::Starting => "Server: Starting",
+        ServerStatus::Running => "Server: Running",
+        ServerStatus::Stopped => "Server: Stopped",
+        ServerStatus::Error(_) => "Server: Error",
+    }
+}
+
+fn is_server_up(status: &ServerStatus) -> bool {
+    matches!(status, ServerStatus::Starting | ServerStatus::Running)
+}
+
+fn server_toggle_label(status: &ServerStatus) -> &'static str {
+    if is_server_up(status) {
+        "Stop Server"
+    } else {
+        "Start Server"
+    }
+}
+
+fn is_protected(auth_state: &Mutex<AuthState>) -> bool {
+    matches!(auth_state.lock().unwrap().mode, AuthMode::Protected)
+}
+
+/// Menu items whose text or check state follows the server.
+struct LiveItems<R: Runtime> {
+    status: MenuItem<R>,
+    clients: MenuItem<R>,
+    server: MenuItem<R>,
+    auth: CheckMenuItem<R>,
+}
+
+/// Keep the live items in sync with the server.
+/// Status changes arrive on the watch channel; the client count and auth
+/// mode (which the dashboard can also change) have no change notification,
+/// so they are polled.
+fn spawn_status_updater<R: Runtime>(
+    items: LiveItems<R>,
+    control: ServerControl,
+    auth_state: Arc<Mutex<AuthState>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut status_rx = control.status_tx.subscribe();
+        let mut poll = tokio::time::interval(std::time::Duration::from_secs(2));
+        let mut last_count = None;
+        let mut last_protected = None;
+        status_rx.mark_changed();
+
+        loop {
+            tokio::select! {
+                changed = status_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    let (label, toggle) = {
+                        let status = status_rx.borrow_and_update();
+                        (status_label(&status), server_toggle_label(&status))
+                    };
+                    let _ = items.status.set_text(label);
+                    let _ = items.server.set_text(toggle);
+                }
+                _ = poll.tick() => {}
+            }
+
+            let count = control
+                .broadcast_tx
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map_or(0, |tx| tx.receiver_count());
+            if last_count != Some(count) {
+                let _ = items.clients.set_text(format!("Clients: {}", count));
+                last_count = Some(count);
+            }
+
+            let protected = is_protected(&auth_state);
+            if last_protected != Some(protected) {
+                let _ = items.auth.set_checked(protected);
+                last_protected = Some(protected);
+            }
+        }
+    });
+}
+
+/// Stop the server if it is up, otherwise start it the same way
+/// `start_service` does. The item's label follows via the status updater.
+fn toggle_server<R: Runtime>(app: &tauri::AppHandle<R>, control: &ServerControl) {
+    if is_server_up(&control.status_tx.borrow()) {
+        crate::signal_shutdown(&control.shutdown_tx);
+        return;
+    }
+
+    let app = app.clone();
+    let control = control.clone();
+    tauri::async_runtime::spawn(async move {
+        let config = app.state::<Arc<Mutex<AppConfig>>>().inner().clone();
+        let auth_state = app.state::<Arc<Mutex<AuthState>>>().inner().clone();
+        crate::start_from_disk(&app, config, auth_state, control).await;
+    });
+}
+
+/// Flip between public and protected auth, the same as `set_auth_mode`.
+/// The check mark is re-synced afterwards since the platform toggles it on
+/// click even when saving fails.
+fn toggle_auth<R: Runtime>(app: &tauri::AppHandle<R>, auth_i: &CheckMenuItem<R>) {
+    let config = app.state::<Arc<Mutex<AppConfig>>>();
+    let auth_state = app.state::<Arc<Mutex<AuthState>>>();
+    let mode = if is_protected(&auth_state) {
+        AuthMode::Public
+    } else {
+        AuthMode::Protected
+    };
+    if let Err(e) = crate::apply_auth_mode(app, &config, &auth_state, mode) {
+        error!("Failed to toggle auth: {}", e);
+    }
+    let _ = auth_i.set_checked(is_protected(&auth_state));
+}
+
+pub fn create_tray<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    control: ServerControl,
+) -> tauri::Result<()> {
     let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
     let open_i = MenuItem::with_id(app, "open", "Open Dashboard", true, None::<&str>)?;
-    let status_i = MenuItem::with_id(app, "status", "Server: Running", false, None::<&str>)?;
+    let (label, toggle) = {
+        let status = control.status_tx.borrow();
+        (status_label(&status), server_toggle_label(&status))
+    };
+    let status_i = MenuItem::with_id(app, "status", label, false, None::<&str>)?;
+    let clients_i = MenuItem::with_id(app, "clients", "Clients: 0", false, None::<&str>)?;
+    let server_i = MenuItem::with_id(app, "toggle_server", toggle, true, None::<&str>)?;
+    let auth_state = app.state::<Arc<Mutex<AuthState>>>().inner().clone();
+    let auth_i = CheckMenuItem::with_id(
+        app,
+        "toggle_auth",
+        "Require Auth",
+        true,
+        is_protected(&auth_state),
+        None::<&str>,
+    )?;
 
     // Separator
-    let menu = Menu::with_items(app, &[&status_i, &open_i, &quit_i])?;
+    let menu = Menu::with_items(
+        app,
+        &[&status_i, &clients_i, &server_i, &auth_i, &open_i, &quit_i],
+    )?;
+
+    spawn_status_updater(
+        LiveItems {
+            status: status_i,
+            clients: clients_i,
+            server: server_i,
+            auth: auth_i.clone(),
+        },
+        control.clone(),
+        auth_state,
+    );
 
     let _tray = TrayIconBuilder::with_id("main")
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .show_menu_on_left_click(false)
-        .on_menu_event(|app, event| match event.id.as_ref() {
+        .on_menu_event(move |app, event| match event.id.as_ref() {
             "quit" => {
                 QUIT_REQUESTED.store(true, Ordering::SeqCst);
                 app.exit(0);
@@ -58,6 +215,8 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
             "open" => {
                 show_or_create_window(app);
             }
+            "toggle_server" => toggle_server(app, &control),
+            "toggle_auth" => toggle_auth(app, &auth_i),
             _ => {}
         })
         .on_tray_icon_event(|tray, event| match event {
@@ -273,16 +273,20 @@ const Hero = () => {
                                 <Lock class="size-3 text-neutral-500" />
                             </Show>
                             <p class="text-xs font-medium text-neutral-600">
-                                Host <span class="text-neutral-400">{host()}</span>&nbsp;
+                                Host{" "}
+                                <span class="text-neutral-400">
+                                    {store.status?.local_only ? "Local only" : host()}
+                                </span>
+                                &nbsp;
                                 Port{" "}
                                 <span class="text-neutral-400">
-                                    {store.cfg?.server.port}
+                                    {store.status?.port}
                                 </span>
                             </p>
                         </div>
                         <CopyBtn
                             tip="Copy Address"
-                            content={`${host()}:${store.cfg?.server.port}`}
+                            content={`${store.status?.local_only ? "127.0.0.1" : host()}:${store.status?.port}`}
                             className="-m-2 size-auto p-2 text-neutral-500 [&_svg:not([class*='size-'])]:size-3"
                         />
                         {/* <p class="text-sm font-medium text-neutral-700">Auth <span class="text-neutral-400">{store.cfg?.auth.enabled ? "Enabled" : "Disabled"}</span></p> */}
@@ -1,11 +1,22 @@
 import {
     Activity,
     ChartSpline,
+    ClipboardList,
     Cpu,
+    Gauge,
+    ExternalLink,
     Headphones,
+    Keyboard,
+    LogIn,
     MonitorCog,
+    OctagonX,
+    Pause,
     Radio,
+    Rocket,
+    ScreenShare,
     SquareDashedMousePointer,
+    SquareTerminal,
+    SunMedium,
 } from "lucide-solid";
 import { useApp } from "../../../context/app-context";
 import * as backend from "../../../lib/backend";
@@ -88,6 +99,34 @@ const ApiScreen = () => {
                 value={store.cfg!.features.enable_processes}
                 onValueChange={() => toggle("processes")}
             />
+            <FeatureCard
+                icon={<OctagonX />}
+                title="Kill Processes"
+                description="Allow killing processes (needs Process Controls)"
+                value={store.cfg!.features.enable_process_kill}
+                onValueChange={() => toggle("process_kill")}
+            />
+            <FeatureCard
+                icon={<Rocket />}
+                title="Launch Processes"
+                description="Allow launching apps (needs Process Controls)"
+                value={store.cfg!.features.enable_process_launch}
+                onValueChange={() => toggle("process_launch")}
+            />
+            <FeatureCard
+                icon={<Gauge />}
+                title="Process Priority"
+                description="Allow changing process priority (needs Process Controls)"
+                value={store.cfg!.features.enable_process_priority}
+                onValueChange={() => toggle("process_priority")}
+            />
+            <FeatureCard
+                icon={<Pause />}
+                title="Suspend Processes"
+                description="Allow pausing and resuming processes (needs Process Controls)"
+                value={store.cfg!.features.enable_process_suspend}
+                onValueChange={() => toggle("process_suspend")}
+            />
             <FeatureCard
                 icon={<Headphones />}
                 title="Media Controls"
@@ -95,6 +134,55 @@ const ApiScreen = () => {
                 value={store.cfg!.features.enable_media}
                 onValueChange={() => toggle("media")}
             />
+            <FeatureCard
+                icon={<SquareTerminal />}
+                title="Custom Commands"
+                description="Run commands registered in config.json"
+                value={store.cfg!.features.enable_commands}
+                onValueChange={() => toggle("commands")}
+            />
+            <FeatureCard
+                icon={<ExternalLink />}
+                title="Open URLs"
+                description="Open http(s) links in the default browser"
+                value={store.cfg!.features.enable_open}
+                onValueChange={() => toggle("open")}
+            />
+            <FeatureCard
+                icon={<ClipboardList />}
+                title="Clipboard"
+                description="Read and set clipboard text (admin keys only)"
+                value={store.cfg!.features.enable_clipboard}
+                onValueChange={() => toggle("clipboard")}
+            />
+            <FeatureCard
+                icon={<ScreenShare />}
+                title="Screenshots"
+                description="Capture the screen (admin keys only)"
+                value={store.cfg!.features.enable_screenshot}
+                onValueChange={() => toggle("screenshot")}
+            />
+            <FeatureCard
+                icon={<SunMedium />}
+                title="Displays"
+                description="List monitors and set their brightness"
+                value={store.cfg!.features.enable_display}
+                onValueChange={() => toggle("display")}
+            />
+            <FeatureCard
+                icon={<Keyboard />}
+                title="Keyboard & Mouse"
+                description="Let admin keys type, click and move the cursor"
+                value={store.cfg!.features.enable_input}
+                onValueChange={() => toggle("input")}
+            />
+            <FeatureCard
+                icon={<LogIn />}
+                title="Autostart"
+                description="Let admin keys check and change start on login"
+                value={store.cfg!.features.enable_autostart_api}
+                onValueChange={() => toggle("autostart_api")}
+            />
             <div class="relative flex min-h-50 w-full flex-col items-center justify-center px-2 text-neutral-500">
                 <div class="z-1 flex flex-col items-center">
                     <SquareDashedMousePointer class="size-5" />
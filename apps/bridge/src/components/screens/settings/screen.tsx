@@ -2,6 +2,7 @@ import {
     Cpu,
     FolderOpen,
     Globe,
+    Laptop,
     Network,
     RefreshCcwDot,
     RotateCcw,
@@ -46,6 +47,13 @@ const SettingsScreen = () => {
         if (config) actions.setConfig(config);
     };
 
+    const handleLocalOnly = async () => {
+        const config = await backend.updateConfig({
+            server: { local_only: !store.cfg!.server.local_only },
+        });
+        if (config) actions.setConfig(config);
+    };
+
     const handleToggle = async (feature: backend.FeatureKey) => {
         const config = await backend.toggleFeatureWithResult(feature);
         if (config) actions.setConfig(config);
@@ -63,7 +71,7 @@ const SettingsScreen = () => {
             <div class="flex w-full gap-2 rounded-md bg-neutral-800 p-2">
                 <RotateCcw class="mt-0.5 size-3.5 shrink-0 text-blue-400" />
                 <p class="flex-1 text-xs text-neutral-400">
-                    Port and bind address changes require a server restart to take effect.
+                    Port, bind address and local only changes require a server restart to take effect.
                 </p>
             </div>
 
@@ -112,6 +120,14 @@ const SettingsScreen = () => {
                 </Select>
             </div>
 
+            <FeatureCard
+                icon={<Laptop />}
+                title="Local Only"
+                description="Only this computer can connect, whatever the bind address"
+                value={store.cfg!.server.local_only}
+                onValueChange={handleLocalOnly}
+            />
+
             <div class="w-full pt-1">
                 <p class="text-secondary text-xs font-semibold uppercase">Identity</p>
             </div>
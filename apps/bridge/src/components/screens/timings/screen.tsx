@@ -1,4 +1,4 @@
-import { Activity, Database, Headphones, Radio, Waves } from "lucide-solid";
+import { Activity, CircuitBoard, Database, Headphones, Radio, Waves } from "lucide-solid";
 import { useApp } from "../../../context/app-context";
 import * as backend from "../../../lib/backend";
 import IntervalCard, { type IntervalOption } from "../../interval-card";
@@ -63,6 +63,11 @@ const TimingScreen = () => {
         if (config) actions.setConfig(config);
     };
 
+    const handleGpuCache = async (value: number) => {
+        const config = await backend.updateGpuCacheSeconds(value);
+        if (config) actions.setConfig(config);
+    };
+
     const handleStreamInterval = async (value: number) => {
         const config = await backend.updateStreamInterval(value);
         if (config) actions.setConfig(config);
@@ -111,13 +116,22 @@ const TimingScreen = () => {
 
             <IntervalCard
                 icon={<Database />}
-                title="Disk & GPU Cache"
-                description="How long disk and GPU data is cached"
+                title="Disk Cache"
+                description="How long disk data is cached"
                 options={DISK_CACHE_OPTIONS}
                 value={store.cfg!.stats.disk_cache_seconds}
                 onChange={handleDiskCache}
             />
 
+            <IntervalCard
+                icon={<CircuitBoard />}
+                title="GPU Cache"
+                description="How long GPU data is cached"
+                options={DISK_CACHE_OPTIONS}
+                value={store.cfg!.stats.gpu_cache_seconds}
+                onChange={handleGpuCache}
+            />
+
             <IntervalCard
                 icon={<Radio />}
                 title="SSE Stream"
@@ -111,12 +111,14 @@ export const createApiKey = async (
     name?: string,
     scopes: string[] = ["admin"],
     expiresAt?: number,
+    ttlSeconds?: number,
 ): Promise<CreateApiKeyResponse | null> => {
     try {
         return await invoke<CreateApiKeyResponse>("create_api_key", {
             name: name ?? null,
             scopes,
             expiresAt: expiresAt ?? null,
+            ttlSeconds: ttlSeconds ?? null,
         });
     } catch (e) {
         console.error("Failed to create API key:", e);
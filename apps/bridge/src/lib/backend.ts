@@ -9,21 +9,35 @@ import { check, Update } from "@tauri-apps/plugin-updater";
 export interface TopicConfig {
     enabled: boolean;
     interval_ms: number;
+    max_items?: number | null;
+    coalesce?: boolean;
 }
 
 export interface WebSocketConfig {
     stats: TopicConfig;
     media: TopicConfig;
     processes: TopicConfig;
+    channel_capacity: number;
+}
+
+export interface MediaConfig {
+    volume_step: number;
 }
 
 export interface ServerConfig {
     port: number;
     host: string;
+    local_only: boolean;
+    max_clients: number;
+    advertise: boolean;
+    discovery_port: number;
+    cors_origins: string[];
 }
 
 export interface DisplayConfig {
     hostname: string;
+    temp_unit: "celsius" | "fahrenheit";
+    binary_units: boolean;
 }
 
 export interface FeaturesConfig {
@@ -38,11 +52,28 @@ export interface FeaturesConfig {
     enable_processes: boolean;
     enable_stream: boolean;
     enable_autostart: boolean;
+    enable_commands: boolean;
+    enable_open: boolean;
+    enable_clipboard: boolean;
+    enable_screenshot: boolean;
+    enable_display: boolean;
+    enable_input: boolean;
+    enable_autostart_api: boolean;
+    enable_process_kill: boolean;
+    enable_process_launch: boolean;
+    enable_process_priority: boolean;
+    enable_process_suspend: boolean;
 }
 
 export interface StatsConfig {
     gpu_enabled: boolean;
     disk_cache_seconds: number;
+    gpu_cache_seconds: number;
+    sensors_enabled: boolean;
+    cpu_smoothing: number;
+    disk_include: string[];
+    disk_exclude: string[];
+    refresh_min_interval_ms: number;
     stream_interval_seconds: number;
 }
 
@@ -53,6 +84,23 @@ export interface AuthConfig {
     blocked_ips: string[];
 }
 
+export interface CommandConfig {
+    id: string;
+    label: string;
+    program: string;
+    args: string[];
+}
+
+export interface ProfileConfig {
+    server: ServerConfig;
+    display: DisplayConfig;
+    features: FeaturesConfig;
+    stats: StatsConfig;
+    auth: AuthConfig;
+    websocket: WebSocketConfig;
+    media: MediaConfig;
+}
+
 export interface Config {
     server: ServerConfig;
     display: DisplayConfig;
@@ -60,6 +108,12 @@ export interface Config {
     stats: StatsConfig;
     auth: AuthConfig;
     websocket: WebSocketConfig;
+    media: MediaConfig;
+    commands: CommandConfig[];
+    safe_mode: boolean;
+    log_level: "error" | "warn" | "info" | "debug" | "trace";
+    active_profile: string;
+    profiles?: Record<string, ProfileConfig>;
 }
 // For compatibility with UI code that expects capitalized keys (if any) or flat structure
 // But the Rust struct was refactored to be nested.
@@ -96,6 +150,8 @@ export type ServerStatus =
 export interface ServerState {
     status: ServerStatus;
     port: number;
+    /** Listening on 127.0.0.1 and ::1 only, whatever the bind address */
+    local_only: boolean;
 }
 
 export const getServerStatus = async (): Promise<ServerState> => {
@@ -297,6 +353,30 @@ export const updateServerHost = async (host: string): Promise<Config | null> =>
     }
 };
 
+/**
+ * Allow a browser origin (e.g. "https://dash.example.com") to call the API
+ */
+export const addCorsOrigin = async (origin: string): Promise<Config | null> => {
+    try {
+        return await invoke<Config>("add_cors_origin", { origin });
+    } catch (e) {
+        console.error("Failed to add CORS origin:", e);
+        return null;
+    }
+};
+
+/**
+ * Remove an allowed browser origin
+ */
+export const removeCorsOrigin = async (origin: string): Promise<Config | null> => {
+    try {
+        return await invoke<Config>("remove_cors_origin", { origin });
+    } catch (e) {
+        console.error("Failed to remove CORS origin:", e);
+        return null;
+    }
+};
+
 /**
  * Update display hostname
  */
@@ -333,6 +413,18 @@ export const updateDiskCacheSeconds = async (seconds: number): Promise<Config |
     }
 };
 
+/**
+ * Update GPU cache duration (seconds)
+ */
+export const updateGpuCacheSeconds = async (seconds: number): Promise<Config | null> => {
+    try {
+        return await invoke<Config>("update_gpu_cache_seconds", { seconds });
+    } catch (e) {
+        console.error("Failed to update GPU cache seconds:", e);
+        return null;
+    }
+};
+
 /**
  * Update SSE stream interval (seconds)
  */
@@ -345,6 +437,51 @@ export const updateStreamInterval = async (seconds: number): Promise<Config | nu
     }
 };
 
+// ============================================================================
+// Profiles
+// ============================================================================
+
+export interface ProfileList {
+    active: string;
+    profiles: string[];
+}
+
+/**
+ * List saved config profiles and the active one
+ */
+export const listProfiles = async (): Promise<ProfileList | null> => {
+    try {
+        return await invoke<ProfileList>("list_profiles");
+    } catch (e) {
+        console.error("Failed to list profiles:", e);
+        return null;
+    }
+};
+
+/**
+ * Switch to a saved profile (restarts the server if host/port differ)
+ */
+export const switchProfile = async (name: string): Promise<Config | null> => {
+    try {
+        return await invoke<Config>("switch_profile", { name });
+    } catch (e) {
+        console.error(`Failed to switch to profile ${name}:`, e);
+        return null;
+    }
+};
+
+/**
+ * Save the current settings as a named profile and make it active
+ */
+export const saveCurrentAsProfile = async (name: string): Promise<Config | null> => {
+    try {
+        return await invoke<Config>("save_current_as_profile", { name });
+    } catch (e) {
+        console.error(`Failed to save profile ${name}:`, e);
+        return null;
+    }
+};
+
 // ============================================================================
 // Server Metrics & Status
 // ============================================================================
@@ -406,7 +543,18 @@ export type FeatureKey =
     | "restart"
     | "hibernate"
     | "sleep"
-    | "autostart";
+    | "commands"
+    | "open"
+    | "clipboard"
+    | "screenshot"
+    | "display"
+    | "input"
+    | "process_kill"
+    | "process_launch"
+    | "process_priority"
+    | "process_suspend"
+    | "autostart"
+    | "autostart_api";
 
 /**
  * Toggle a specific feature and return updated config
@@ -506,17 +654,58 @@ export const applyWsPreset = async (
 };
 
 /**
- * Get local network IPs for display
+ * Get local network IPs for display (IPv4 only unless includeIpv6 is set)
  */
-export const getLocalIps = async (): Promise<string[]> => {
+export const getLocalIps = async (includeIpv6 = false): Promise<string[]> => {
     try {
-        return await invoke<string[]>("get_local_ips");
+        return await invoke<string[]>("get_local_ips", { includeIpv6 });
     } catch (e) {
         console.error("Failed to get local IPs:", e);
         return [];
     }
 };
 
+export interface LogEntry {
+    timestamp: number;
+    level: "error" | "warn" | "info" | "debug" | "trace";
+    target: string;
+    message: string;
+}
+
+/**
+ * Recent server log lines, oldest first
+ */
+export const getRecentLogs = async (
+    level?: LogEntry["level"],
+    limit?: number,
+): Promise<LogEntry[]> => {
+    try {
+        return await invoke<LogEntry[]>("get_recent_logs", { level, limit });
+    } catch (e) {
+        console.error("Failed to get recent logs:", e);
+        return [];
+    }
+};
+
+export interface ClientSeen {
+    ip: string;
+    first_seen: number;
+    last_seen: number;
+    request_count: number;
+}
+
+/**
+ * IPs that have called the API, most recently seen first
+ */
+export const getClientHistory = async (): Promise<ClientSeen[]> => {
+    try {
+        return await invoke<ClientSeen[]>("get_client_history");
+    } catch (e) {
+        console.error("Failed to get client history:", e);
+        return [];
+    }
+};
+
 /**
  * Copy text to clipboard
  */
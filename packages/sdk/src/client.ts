@@ -1,5 +1,20 @@
 import type { BridgeConfig, ConnectionStatus } from "./types";
-import type { StatusResponse, SystemInfoResponse } from "./types/api";
+import type {
+  AutostartState,
+  Capabilities,
+  ClientSeen,
+  ClipboardContent,
+  DisplayInfo,
+  FeaturesResponse,
+  InputAction,
+  LogEntry,
+  LogLevel,
+  MediaCapabilities,
+  ScreenshotOptions,
+  StatusResponse,
+  SystemInfoResponse,
+  UsageSummary,
+} from "./types/api";
 
 /**
  * Low-level Cntrl Bridge client for REST API communication.
@@ -70,6 +85,32 @@ export class BridgeClient {
     return response.json();
   }
 
+  /**
+   * Get enabled features and which feature-gated routes are live
+   */
+  async getFeatures(): Promise<FeaturesResponse> {
+    const response = await fetch(`${this.baseUrl}/api/features`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to get features: ${response.statusText}`);
+    }
+    return response.json();
+  }
+
+  /**
+   * What the host's OS and hardware support, whatever features are enabled
+   */
+  async getCapabilities(): Promise<Capabilities> {
+    const response = await fetch(`${this.baseUrl}/api/capabilities`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to get capabilities: ${response.statusText}`);
+    }
+    return response.json();
+  }
+
   /**
    * Get static system info (CPU, GPU, RAM specs)
    */
@@ -83,6 +124,20 @@ export class BridgeClient {
     return response.json();
   }
 
+  /**
+   * Min/max/avg CPU, memory and GPU load over the last `windowSecs` (default 60)
+   */
+  async getUsageSummary(windowSecs?: number): Promise<UsageSummary> {
+    const query = windowSecs !== undefined ? `?window_secs=${windowSecs}` : "";
+    const response = await fetch(`${this.baseUrl}/api/usage/stats${query}`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to get usage summary: ${response.statusText}`);
+    }
+    return response.json();
+  }
+
   /**
    * Power control: shutdown
    */
@@ -109,6 +164,19 @@ export class BridgeClient {
     }
   }
 
+  /**
+   * Power control: restart into BIOS/UEFI setup (Windows, Linux)
+   */
+  async restartFirmware(): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/api/pw/restart_firmware`, {
+      method: "POST",
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to restart into firmware: ${response.statusText}`);
+    }
+  }
+
   /**
    * Power control: sleep
    */
@@ -185,6 +253,195 @@ export class BridgeClient {
       throw new Error(`Failed to launch process: ${response.statusText}`);
     }
   }
+
+  /**
+   * Open an http(s) URL in the host's default browser
+   */
+  async openUrl(url: string): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/api/open`, {
+      method: "POST",
+      headers: {
+        ...this.headers,
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({ url }),
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to open URL: ${response.statusText}`);
+    }
+  }
+
+  /**
+   * Read text from the host's clipboard (null if empty or not text)
+   */
+  async getClipboard(): Promise<string | null> {
+    const response = await fetch(`${this.baseUrl}/api/clipboard`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to read clipboard: ${response.statusText}`);
+    }
+    const data: ClipboardContent = await response.json();
+    return data.text;
+  }
+
+  /**
+   * Put text on the host's clipboard
+   */
+  async setClipboard(text: string): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/api/clipboard`, {
+      method: "POST",
+      headers: {
+        ...this.headers,
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({ text }),
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to set clipboard: ${response.statusText}`);
+    }
+  }
+
+  /**
+   * Media actions this platform supports
+   */
+  async getMediaCapabilities(): Promise<MediaCapabilities> {
+    const response = await fetch(`${this.baseUrl}/api/media/capabilities`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to get media capabilities: ${response.statusText}`);
+    }
+    return response.json();
+  }
+
+  /**
+   * List connected displays
+   */
+  async getDisplays(): Promise<DisplayInfo[]> {
+    const response = await fetch(`${this.baseUrl}/api/displays`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to list displays: ${response.statusText}`);
+    }
+    return response.json();
+  }
+
+  /**
+   * Set a display's brightness (0-100)
+   */
+  async setBrightness(id: number, value: number): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/api/displays/${id}/brightness`, {
+      method: "POST",
+      headers: {
+        ...this.headers,
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({ value }),
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to set brightness: ${response.statusText}`);
+    }
+  }
+
+  /**
+   * Inject a key press, typed text, mouse move or click
+   */
+  async sendInput(input: InputAction): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/api/input`, {
+      method: "POST",
+      headers: {
+        ...this.headers,
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(input),
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to send input: ${response.statusText}`);
+    }
+  }
+
+  /**
+   * Whether the bridge starts on login
+   */
+  async getAutostart(): Promise<boolean> {
+    const response = await fetch(`${this.baseUrl}/api/autostart`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to get autostart: ${response.statusText}`);
+    }
+    const data: AutostartState = await response.json();
+    return data.enabled;
+  }
+
+  /**
+   * Turn start on login on or off
+   */
+  async setAutostart(enabled: boolean): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/api/autostart`, {
+      method: "POST",
+      headers: {
+        ...this.headers,
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({ enabled }),
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to set autostart: ${response.statusText}`);
+    }
+  }
+
+  /**
+   * Capture a display as a PNG or JPEG image
+   */
+  async getScreenshot(options?: ScreenshotOptions): Promise<Blob> {
+    const params = new URLSearchParams();
+    if (options?.display !== undefined) params.set("display", String(options.display));
+    if (options?.format) params.set("format", options.format);
+    if (options?.quality !== undefined) params.set("quality", String(options.quality));
+    if (options?.width !== undefined) params.set("width", String(options.width));
+    const query = params.toString();
+    const response = await fetch(
+      `${this.baseUrl}/api/screenshot${query ? `?${query}` : ""}`,
+      { headers: this.headers }
+    );
+    if (!response.ok) {
+      throw new Error(`Failed to capture screenshot: ${response.statusText}`);
+    }
+    return response.blob();
+  }
+
+  /**
+   * Recent bridge log lines, oldest first (requires admin)
+   */
+  async getLogs(options?: { level?: LogLevel; limit?: number }): Promise<LogEntry[]> {
+    const params = new URLSearchParams();
+    if (options?.level) params.set("level", options.level);
+    if (options?.limit !== undefined) params.set("limit", String(options.limit));
+    const query = params.toString();
+    const response = await fetch(`${this.baseUrl}/api/logs${query ? `?${query}` : ""}`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to get logs: ${response.statusText}`);
+    }
+    return response.json();
+  }
+
+  /**
+   * IPs that have called the API, most recently seen first (requires admin)
+   */
+  async getClientHistory(): Promise<ClientSeen[]> {
+    const response = await fetch(`${this.baseUrl}/api/clients/history`, {
+      headers: this.headers,
+    });
+    if (!response.ok) {
+      throw new Error(`Failed to get client history: ${response.statusText}`);
+    }
+    return response.json();
+  }
 }
 
 /**
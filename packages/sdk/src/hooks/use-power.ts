@@ -71,6 +71,7 @@ export function usePower(bridgeId: string) {
   return {
     shutdown: createPowerMutation("shutdown"),
     restart: createPowerMutation("restart"),
+    restartFirmware: createPowerMutation("restart_firmware"),
     sleep: createPowerMutation("sleep"),
     hibernate: createPowerMutation("hibernate"),
     wakeOnLan,
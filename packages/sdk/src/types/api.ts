@@ -17,7 +17,11 @@ export interface CpuInfo {
   brand: string;
   cores: number;
   physical_cores: number;
+  /** Clock speeds in GHz, `0` when unknown */
   base_speed: number;
+  max_speed: number;
+  /** `true` when the speeds are estimated from the chip model (Apple Silicon) */
+  speed_approximate: boolean;
 }
 
 export interface GpuInfo {
@@ -29,6 +33,15 @@ export interface GpuInfo {
 export interface MemoryInfo {
   total: number;
   slots: number;
+  /** Empty when the modules can't be read */
+  modules: MemoryModule[];
+}
+
+export interface MemoryModule {
+  size_bytes: number;
+  speed_mhz: number | null;
+  manufacturer: string | null;
+  part_number: string | null;
 }
 
 export interface DiskInfo {
@@ -45,12 +58,60 @@ export interface NetworkInfo {
   ipv6: string;
 }
 
+/**
+ * SMART drive health from GET /api/disks/health
+ */
+export interface DiskHealth {
+  device: string;
+  model: string | null;
+  health: "PASSED" | "FAILED" | "UNAVAILABLE";
+  temperature: number | null;
+  /** Unit of `temperature`, from the bridge's `display.temp_unit` */
+  temp_unit: TempUnit;
+  power_on_hours: number | null;
+  /** Why the drive is unavailable */
+  message: string | null;
+}
+
+/**
+ * Physical disks and their partitions from GET /api/disks/layout
+ */
+export interface DiskLayout {
+  /** false when lsblk / diskutil / WMI couldn't be used; see `message` */
+  available: boolean;
+  message: string | null;
+  disks: PhysicalDisk[];
+}
+
+export interface PhysicalDisk {
+  name: string;
+  model: string | null;
+  /** Bytes */
+  size: number;
+  removable: boolean | null;
+  /** Only set when a filesystem sits on the whole disk */
+  mount_points: string[];
+  partitions: DiskPartition[];
+}
+
+export interface DiskPartition {
+  name: string;
+  /** Bytes */
+  size: number;
+  /** Partition content type (e.g. "Apple_APFS") on macOS */
+  file_system: string | null;
+  /** Includes LVM/LUKS volumes or an APFS container's volumes inside it */
+  mount_points: string[];
+}
+
 /**
  * Static system info response from GET /api/system
  */
 export interface SystemInfo {
   hostname: string;
   platform: string;
+  /** Unix timestamp (seconds) of the last boot */
+  boot_time: number;
   os: OsInfo;
   cpu: CpuInfo;
   gpu: GpuInfo | null;
@@ -61,22 +122,40 @@ export interface SystemInfo {
 
 // ============ Dynamic Usage (/api/usage) ============
 
+export type TempUnit = "celsius" | "fahrenheit";
+
 export interface CpuUsage {
   current_load: number;
   current_temp: number;
+  /** Unit of `current_temp`, from the bridge's `display.temp_unit` */
+  temp_unit: TempUnit;
+  /** Average of the per-core clock speeds in GHz */
   current_speed: number;
+  /** 1, 5 and 15 minute load averages. `null` on Windows. */
+  load_avg: [number, number, number] | null;
 }
 
 export interface MemoryUsage {
   used: number;
   free: number;
   used_percent: number;
+  available: number;
+  /** Linux only */
+  cached: number | null;
+  /** Linux only */
+  buffers: number | null;
+  swap_used: number;
+  swap_total: number;
 }
 
 export interface GpuUsage {
   current_load: number;
   current_temp: number;
+  /** Unit of `current_temp`, from the bridge's `display.temp_unit` */
+  temp_unit: TempUnit;
   current_memory: number;
+  /** Milliseconds since the readings were taken; GPU stats are cached on the bridge */
+  age_ms: number;
 }
 
 export interface DiskUsage {
@@ -95,6 +174,32 @@ export interface SystemUsage {
   memory: MemoryUsage;
   gpu: GpuUsage | null;
   disks: DiskUsage[];
+  /** Empty unless sensors are enabled on the bridge */
+  fans: FanSpeed[];
+}
+
+export interface FanSpeed {
+  label: string;
+  rpm: number;
+}
+
+export interface StatSummary {
+  min: number;
+  max: number;
+  avg: number;
+  samples: number;
+}
+
+/**
+ * Load summary over a window from GET /api/usage/stats.
+ * Only filled while a WebSocket client is subscribed to stats.
+ */
+export interface UsageSummary {
+  window_secs: number;
+  samples: number;
+  cpu: StatSummary | null;
+  memory_percent: StatSummary | null;
+  gpu: StatSummary | null;
 }
 
 // ============ Stream/WebSocket Stats ============
@@ -131,6 +236,82 @@ export interface MediaStatus {
   title: string | null;
   artist: string | null;
   supports_ctrl: boolean;
+  /** Name of the default audio output device */
+  output_device: string | null;
+  input_volume: number | null;
+  input_muted: boolean | null;
+  /** App id of the session the fields above describe */
+  app_id: string | null;
+  /** Every media session, including the one above */
+  sessions: MediaSession[];
+  /** Players media info is read from (macOS); empty when any app counts */
+  supported_players: string[];
+}
+
+/**
+ * One app's media session
+ */
+export interface MediaSession {
+  /** AppUserModelID on Windows, app name on macOS */
+  app_id: string;
+  status: string;
+  title: string | null;
+  artist: string | null;
+}
+
+/**
+ * Audio output device from GET /api/media/devices
+ */
+export interface AudioDevice {
+  /** Endpoint id on Windows, device UID on macOS */
+  id: string;
+  name: string;
+  is_default: boolean;
+}
+
+/**
+ * Media actions this platform supports, from GET /api/media/capabilities
+ */
+export interface MediaCapabilities {
+  actions: string[];
+  /** Actions that can target one session with `app_id` (Windows only) */
+  session_actions: string[];
+  /** Seeking within a track; no platform supports it yet */
+  seek: boolean;
+  /** Whether output devices can be listed and switched */
+  output_devices: boolean;
+}
+
+/**
+ * What the host supports, from GET /api/capabilities. Independent of which
+ * features are enabled.
+ */
+export interface Capabilities {
+  /** "windows", "macos" or "linux" */
+  platform: string;
+  power: {
+    shutdown: boolean;
+    restart: boolean;
+    /** Only true when it would work now (Windows: bridge runs elevated) */
+    restart_firmware: boolean;
+    sleep: boolean;
+    hibernate: boolean;
+  };
+  media: MediaCapabilities;
+  processes: {
+    kill: boolean;
+    launch: boolean;
+    focus: boolean;
+    priority: boolean;
+    suspend: boolean;
+  };
+  gpu: {
+    /** null when no GPU was found or GPU stats are off */
+    vendor: string | null;
+    model: string | null;
+    /** Per-process GPU memory is available (NVIDIA only) */
+    per_process: boolean;
+  };
 }
 
 /**
@@ -148,7 +329,10 @@ export type MediaActionType =
   | "volume_down"
   | "mute"
   | "unmute"
-  | "toggle_mute";
+  | "toggle_mute"
+  | "set_input_volume"
+  | "mute_input"
+  | "unmute_input";
 
 /**
  * Media control request
@@ -156,6 +340,8 @@ export type MediaActionType =
 export interface MediaAction {
   action: MediaActionType;
   value?: number;
+  /** Send playback actions to this session only (Windows only) */
+  app_id?: string;
 }
 
 // ============ Processes ============
@@ -172,7 +358,7 @@ export interface ProcessInfo {
 }
 
 /**
- * Detailed process info from GET /api/processes/{name}
+ * Detailed process info from GET /api/processes/{name} and GET /api/process/{pid}
  */
 export interface ProcessDetail {
   pid: number;
@@ -181,6 +367,20 @@ export interface ProcessDetail {
   cpu: number;
   title: string | null;
   has_window: boolean;
+  /** Unix seconds */
+  start_time: number;
+  /** null when the owner can't be resolved (e.g. exited users, no access) */
+  user: string | null;
+}
+
+/**
+ * Per-process NVIDIA GPU memory from GET /api/processes/gpu
+ */
+export interface GpuProcessInfo {
+  pid: number;
+  name: string;
+  /** null when the driver doesn't report per-process memory */
+  gpu_memory_mb: number | null;
 }
 
 /**
@@ -190,6 +390,8 @@ export interface ProcessListPayload {
   timestamp: number;
   processes: ProcessInfo[];
   total_count: number;
+  /** True when the list was capped by `websocket.processes.max_items` */
+  truncated: boolean;
 }
 
 /**
@@ -201,11 +403,159 @@ export interface OperationFeedback {
   message?: string;
   pid?: number;
   name?: string;
+  /** Echo of the command's `id`, if one was sent */
+  id?: string;
+}
+
+// ============ Commands ============
+
+/**
+ * Registered command from GET /api/commands
+ */
+export interface CommandInfo {
+  id: string;
+  label: string;
+}
+
+// ============ Clipboard ============
+
+/**
+ * Response from GET /api/clipboard
+ */
+export interface ClipboardContent {
+  /** null when the clipboard is empty or doesn't hold text */
+  text: string | null;
+}
+
+// ============ Autostart ============
+
+/**
+ * Response from GET /api/autostart, and body of POST /api/autostart
+ */
+export interface AutostartState {
+  /** Whether the bridge starts on login */
+  enabled: boolean;
+}
+
+// ============ Displays ============
+
+/**
+ * Connected display from GET /api/displays
+ */
+export interface DisplayInfo {
+  id: number;
+  name: string;
+  width: number;
+  height: number;
+  /** Position of the top-left corner on the virtual desktop */
+  x: number;
+  y: number;
+  scale_factor: number;
+  is_primary: boolean;
+  /** Current brightness (0-100), null when it can't be read */
+  brightness: number | null;
+}
+
+// ============ Input ============
+
+/**
+ * Body of POST /api/input
+ */
+export type InputAction =
+  | {
+      type: "key";
+      /** Key name (`enter`, `f5`, `left`) or a single character */
+      key?: string;
+      /** Text to type as-is */
+      text?: string;
+      /** `ctrl`, `shift`, `alt`, `meta` */
+      modifiers?: string[];
+    }
+  | {
+      type: "mouse";
+      action: "move" | "click";
+      /** Absolute screen position; required for `move` */
+      x?: number;
+      y?: number;
+      button?: "left" | "right" | "middle";
+    };
+
+// ============ Screenshot ============
+
+/**
+ * Query options for GET /api/screenshot
+ */
+export interface ScreenshotOptions {
+  /** Display index (defaults to the primary display) */
+  display?: number;
+  format?: "png" | "jpeg";
+  /** JPEG quality, 1-100 (default 80) */
+  quality?: number;
+  /** Scale down to this width, keeping the aspect ratio */
+  width?: number;
+}
+
+// ============ Logs ============
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+/**
+ * A log line from GET /api/logs
+ */
+export interface LogEntry {
+  /** Unix timestamp (milliseconds) */
+  timestamp: number;
+  level: LogLevel;
+  /** Module that logged the line */
+  target: string;
+  message: string;
+}
+
+// ============ Clients ============
+
+/**
+ * A client IP from GET /api/clients/history
+ */
+export interface ClientSeen {
+  ip: string;
+  /** Unix timestamp (milliseconds) of the first request */
+  first_seen: number;
+  /** Unix timestamp (milliseconds) of the latest request */
+  last_seen: number;
+  request_count: number;
 }
 
 // ============ Power ============
 
-export type PowerAction = "shutdown" | "restart" | "sleep" | "hibernate";
+export type PowerAction =
+  | "shutdown"
+  | "restart"
+  | "restart_firmware"
+  | "sleep"
+  | "hibernate";
+
+/**
+ * Pending delayed power action from GET /api/pw/scheduled
+ */
+export interface ScheduledPowerAction {
+  id: number;
+  action: PowerAction;
+  /** Unix timestamp (seconds) when the action will run */
+  fire_at: number;
+  remaining_secs: number;
+}
+
+// ============ Features ============
+
+/**
+ * Feature toggles from GET /api/features
+ */
+export interface FeaturesResponse {
+  /** Every `features.enable_*` toggle in the bridge config */
+  features: Record<string, boolean>;
+  /** Whether each feature-gated route is served, keyed like `"POST /api/media/control"` */
+  endpoints: Record<string, boolean>;
+}
 
 // ============ Status ============
 
@@ -214,6 +564,12 @@ export type PowerAction = "shutdown" | "restart" | "sleep" | "hibernate";
  */
 export interface StatusResponse {
   status: string;
+  /** Bridge app version */
+  version?: string;
+  /** API version served under unversioned `/api/...` paths (bridges before versioning omit this) */
+  api_version?: number;
+  /** Versions available under `/api/v{n}/...` */
+  api_versions?: number[];
 }
 
 /**
@@ -231,3 +587,16 @@ export interface PingResult {
   responseTime: number | null;
   lastChecked: number;
 }
+
+// ============ Errors ============
+
+/**
+ * Error body returned by REST endpoints on failure
+ */
+export interface ApiError {
+  /** Machine-readable code, e.g. "FEATURE_DISABLED" or "PROCESS_NOT_FOUND" */
+  code: string;
+  message: string;
+  /** With `UNSUPPORTED_ACTION` from media control: the actions that are valid */
+  supported?: string[];
+}
@@ -9,6 +9,7 @@ import type {
   OperationFeedback,
   ProcessListPayload,
   StreamPayload,
+  SystemInfo,
 } from "./api";
 
 // --- Outgoing Messages (Client -> Bridge) ---
@@ -27,41 +28,113 @@ export interface WSUnsubscribeMessage {
   data: SubscribeRequest;
 }
 
+/** Optional id echoed back in the matching `*_feedback` event */
+type WithCommandId<T> = T & { id?: string };
+
 export interface WSMediaMessage {
   op: "media";
-  data: MediaAction;
+  data: WithCommandId<MediaAction>;
 }
 
 export interface WSProcessKillMessage {
   op: "process_kill";
-  data: {
+  data: WithCommandId<{
     pid?: number;
     name?: string;
-  };
+  }>;
 }
 
 export interface WSProcessFocusMessage {
   op: "process_focus";
   data: {
     pid: number;
+    /** Raise every visible window of the process (Windows only) */
+    all_windows?: boolean;
   };
 }
 
 export interface WSProcessLaunchMessage {
   op: "process_launch";
-  data: {
+  data: WithCommandId<{
     path: string;
     args?: string[];
+  }>;
+}
+
+/** Launch and stream stdout/stderr back as `process_output`, then `process_exit` */
+export interface WSProcessRunMessage {
+  op: "process_run";
+  data: WithCommandId<{
+    path: string;
+    args?: string[];
+  }>;
+}
+
+export interface WSGetSystemInfoMessage {
+  op: "get_system_info";
+}
+
+/** Thin out one topic for this connection only (the shared loop is unchanged) */
+export interface WSSetIntervalMessage {
+  op: "set_interval";
+  data: {
+    topic: "stats" | "processes";
+    /** 100-60000, or null to go back to the shared interval */
+    interval_ms: number | null;
+  };
+}
+
+/** Get stats as `system_stats_delta` changes between periodic full frames */
+export interface WSSetDeltaMessage {
+  op: "set_delta";
+  data: {
+    enabled: boolean;
+  };
+}
+
+/** Turn a bridge feature on or off and save it to the config (admin only) */
+export interface WSSetFeatureMessage {
+  op: "set_feature";
+  data: {
+    /** Feature name without the `enable_` prefix, e.g. "media" */
+    feature: string;
+    enabled: boolean;
+  };
+}
+
+/**
+ * First message on a socket opened without a key, in place of `?api_key=`.
+ * Must arrive within 10 seconds or the socket is closed.
+ */
+export interface WSAuthMessage {
+  op: "auth";
+  data: {
+    key: string;
+  };
+}
+
+/** Restore an earlier connection's subscriptions from its `session` token */
+export interface WSResumeMessage {
+  op: "resume";
+  data: {
+    token: string;
   };
 }
 
 export type WSOutgoingMessage =
+  | WSAuthMessage
+  | WSResumeMessage
   | WSSubscribeMessage
   | WSUnsubscribeMessage
   | WSMediaMessage
   | WSProcessKillMessage
   | WSProcessFocusMessage
-  | WSProcessLaunchMessage;
+  | WSProcessLaunchMessage
+  | WSProcessRunMessage
+  | WSGetSystemInfoMessage
+  | WSSetIntervalMessage
+  | WSSetDeltaMessage
+  | WSSetFeatureMessage;
 
 // --- Incoming Events (Bridge -> Client) ---
 // These match BroadcastEvent enum in types.rs
@@ -71,6 +144,15 @@ export interface WSSystemStatsEvent {
   data: StreamPayload;
 }
 
+/**
+ * Sent instead of `system_stats` after `set_delta`: a JSON Merge Patch
+ * (RFC 7396) against the previous stats frame
+ */
+export interface WSSystemStatsDeltaEvent {
+  type: "system_stats_delta";
+  data: Record<string, unknown>;
+}
+
 export interface WSMediaUpdateEvent {
   type: "media_update";
   data: MediaStatus;
@@ -91,6 +173,53 @@ export interface WSProcessFeedbackEvent {
   data: OperationFeedback;
 }
 
+export interface WSServerStatusEvent {
+  type: "server_status";
+  data: {
+    status: "Starting" | "Running" | "Stopped" | "Error";
+    /** Only set for "Error" */
+    message?: string;
+  };
+}
+
+/** Reply to `get_system_info`, sent only to the requesting connection */
+export interface WSSystemInfoEvent {
+  type: "system_info";
+  data: SystemInfo;
+}
+
+/** A line printed by a `process_run` process */
+export interface WSProcessOutputEvent {
+  type: "process_output";
+  data: {
+    id?: string;
+    pid: number | null;
+    stream: "stdout" | "stderr";
+    line: string;
+  };
+}
+
+/** Last event for a `process_run` */
+export interface WSProcessExitEvent {
+  type: "process_exit";
+  data: {
+    id?: string;
+    pid: number | null;
+    /** null when killed by a signal */
+    code: number | null;
+    /** Set when the run was cut short; null when it exited on its own */
+    reason: "timeout" | "output_limit" | null;
+  };
+}
+
+/** This connection fell behind and `skipped` events were dropped */
+export interface WSLaggedEvent {
+  type: "lagged";
+  data: {
+    skipped: number;
+  };
+}
+
 export interface WSConnectedEvent {
   type: "connected";
   data: {
@@ -98,6 +227,41 @@ export interface WSConnectedEvent {
   };
 }
 
+/** Reply to an accepted `auth` message */
+export interface WSAuthenticatedEvent {
+  type: "authenticated";
+  data: {
+    scopes: string[];
+  };
+}
+
+/** Sent when a connection opens; keep `resume_token` to `resume` after a drop */
+export interface WSSessionEvent {
+  type: "session";
+  data: {
+    resume_token: string;
+    /** How long the token stays valid after this connection closes */
+    ttl_secs: number;
+  };
+}
+
+/** Reply to an accepted `resume` message */
+export interface WSResumedEvent {
+  type: "resumed";
+  data: {
+    topics: string[];
+  };
+}
+
+/** Reply to an applied `set_feature` message */
+export interface WSFeatureUpdatedEvent {
+  type: "feature_updated";
+  data: {
+    feature: string;
+    enabled: boolean;
+  };
+}
+
 export interface WSErrorEvent {
   type: "error";
   data: {
@@ -108,11 +272,21 @@ export interface WSErrorEvent {
 
 export type WSIncomingEvent =
   | WSSystemStatsEvent
+  | WSSystemStatsDeltaEvent
   | WSMediaUpdateEvent
   | WSMediaFeedbackEvent
   | WSProcessListEvent
   | WSProcessFeedbackEvent
+  | WSServerStatusEvent
+  | WSSystemInfoEvent
+  | WSProcessOutputEvent
+  | WSProcessExitEvent
+  | WSLaggedEvent
   | WSConnectedEvent
+  | WSAuthenticatedEvent
+  | WSSessionEvent
+  | WSResumedEvent
+  | WSFeatureUpdatedEvent
   | WSErrorEvent;
 
 /**
@@ -126,4 +300,5 @@ export type WSTopic =
   | "stats.disks"
   | "stats.network"
   | "media"
-  | "processes";
+  | "processes"
+  | "server";
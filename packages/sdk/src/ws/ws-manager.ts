@@ -207,6 +207,13 @@ export class WebSocketManager {
         this.queryClient.setQueryData(["stats", this.bridgeId], message.data);
         break;
 
+      case "system_stats_delta":
+        this.queryClient.setQueryData(["stats", this.bridgeId], (previous: unknown) =>
+          // Without a keyframe yet there is nothing to apply the delta to
+          previous === undefined ? undefined : applyMergePatch(previous, message.data),
+        );
+        break;
+
       case "media_update":
         this.queryClient.setQueryData(["media", this.bridgeId], message.data);
         break;
@@ -225,10 +232,32 @@ export class WebSocketManager {
         console.log(`[WS ${this.bridgeId}] Process feedback:`, message.data);
         break;
 
+      case "server_status":
+        this.queryClient.setQueryData(["server", this.bridgeId], message.data);
+        break;
+
+      case "system_info":
+        this.queryClient.setQueryData(["system-info", this.bridgeId], message.data);
+        break;
+
+      case "lagged":
+        console.warn(`[WS ${this.bridgeId}] Missed ${message.data.skipped} events`);
+        break;
+
       case "connected":
         console.log(`[WS ${this.bridgeId}] Server ack:`, message.data.message);
         break;
 
+      case "session":
+      case "resumed":
+        // Subscriptions are tracked here and re-sent on every open, so the
+        // resume token isn't needed
+        break;
+
+      case "feature_updated":
+        // The bridge's config isn't cached by the SDK
+        break;
+
       case "error":
         console.error(`[WS ${this.bridgeId}] Server error:`, message.data);
         // Call error callback for DX (toasts, etc.)
@@ -304,3 +333,22 @@ export class WebSocketManager {
     }
   }
 }
+
+/** Apply a JSON Merge Patch (RFC 7396) to `target` without mutating it */
+function applyMergePatch(target: unknown, patch: unknown): unknown {
+  if (typeof patch !== "object" || patch === null || Array.isArray(patch)) {
+    return patch;
+  }
+  const result: Record<string, unknown> =
+    typeof target === "object" && target !== null && !Array.isArray(target)
+      ? { ...(target as Record<string, unknown>) }
+      : {};
+  for (const [key, value] of Object.entries(patch)) {
+    if (value === null) {
+      delete result[key];
+    } else {
+      result[key] = applyMergePatch(result[key], value);
+    }
+  }
+  return result;
+}